@@ -0,0 +1,107 @@
+//! Benchmarks of the engine's hot loops on canned boards.
+//!
+//! Run with `cargo bench --features bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ndsquared_rustapi::battlesnake::bench::{self, board, coord};
+use ndsquared_rustapi::battlesnake::GameState;
+
+/// Two snakes in the middle of a game.
+const DUEL: &str = "
+    |  |  |  |  |  |  |  |  |  |  |  |
+    |  |  |  |  |  |  |  |  |  |  |  |
+    |  |  |Y0|Y1|Y2|Y3|  |  |F |  |  |
+    |  |  |  |  |  |Y4|  |  |  |  |  |
+    |  |  |  |  |  |Y5|  |  |  |  |  |
+    |  |  |F |  |  |  |  |  |  |  |  |
+    |  |  |  |  |  |  |  |A0|A1|A2|  |
+    |  |  |  |  |  |  |  |  |  |A3|  |
+    |  |  |  |  |  |  |F |  |  |A4|  |
+    |  |  |  |  |  |  |  |  |  |  |  |
+    |  |  |  |  |  |  |  |  |  |  |  |
+";
+
+/// Four snakes, food and a row of hazards.
+const FOUR: &str = "
+    |  |  |  |  |  |  |  |  |  |  |  |
+    |  |Y0|Y1|Y2|Y3|  |  |  |  |  |  |
+    |  |  |  |  |Y4|  |  |  |F |  |  |
+    |  |  |  |  |  |  |  |  |  |A0|  |
+    |  |  |F |  |  |  |  |  |  |A1|  |
+    |  |  |  |  |  |F |  |  |  |A2|  |
+    |  |  |  |  |  |  |  |  |  |A3|  |
+    |B3|B2|B1|B0|  |  |  |  |  |  |  |
+    |  |  |  |  |  |  |F |  |  |  |  |
+    |  |  |  |  |  |C2|C1|C0|  |  |  |
+    |H |H |H |H |H |H |H |H |H |H |H |
+";
+
+fn boards() -> Vec<(&'static str, GameState)> {
+    vec![("duel", board(DUEL)), ("four", board(FOUR))]
+}
+
+fn advance_undo(c: &mut Criterion) {
+    let mut group = c.benchmark_group("advance_undo");
+    for (name, mut gs) in boards() {
+        let moves = bench::joint_move(&gs);
+        group.bench_function(name, |b| {
+            b.iter(|| bench::advance_undo(black_box(&mut gs), &moves))
+        });
+    }
+    group.finish();
+}
+
+fn compute_metadata(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_metadata");
+    for (name, mut gs) in boards() {
+        group.bench_function(name, |b| {
+            b.iter(|| bench::compute_metadata(black_box(&mut gs)))
+        });
+    }
+    group.finish();
+}
+
+fn compute_territory_info(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_territory_info");
+    for (name, gs) in boards() {
+        group.bench_function(name, |b| {
+            b.iter(|| bench::compute_territory_info(black_box(&gs)))
+        });
+    }
+    group.finish();
+}
+
+fn shortest_distance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shortest_distance");
+    // Corner to corner, routing around the snakes in the way
+    let (start, end) = (coord(0, 0), coord(10, 10));
+    for (name, gs) in boards() {
+        group.bench_function(name, |b| {
+            b.iter(|| bench::shortest_distance(black_box(&gs), &start, &end))
+        });
+    }
+    group.finish();
+}
+
+fn search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search");
+    group.sample_size(10);
+    for (name, mut gs) in boards() {
+        for depth in [2, 4] {
+            group.bench_with_input(BenchmarkId::new(name, depth), &depth, |b, &depth| {
+                b.iter(|| bench::search(black_box(&mut gs), depth))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    advance_undo,
+    compute_metadata,
+    compute_territory_info,
+    shortest_distance,
+    search
+);
+criterion_main!(benches);
@@ -0,0 +1,13 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    // Reported by GET /version, so games can be traced back to the build that played them
+    let built_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", built_at);
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/engine.proto")
+        .expect("unable to compile proto/engine.proto");
+}
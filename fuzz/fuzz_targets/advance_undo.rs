@@ -0,0 +1,29 @@
+//! Structurally valid game states advanced by arbitrary joint moves, then undone.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ndsquared_rustapi::battlesnake::bench;
+use ndsquared_rustapi_fuzz::FuzzBoard;
+
+/// Turns advanced before undoing, well within the undo buffers.
+const MAX_TURNS: usize = 50;
+
+fuzz_target!(|input: (FuzzBoard, Vec<Vec<u8>>)| {
+    let (board, turns) = input;
+    let mut gs = match board.state() {
+        Some(gs) => gs,
+        None => return,
+    };
+    bench::prepare(&mut gs);
+    let before = bench::snapshot(&gs);
+    let turns = &turns[..turns.len().min(MAX_TURNS)];
+    for choices in turns {
+        let moves = bench::moves(&gs, choices);
+        bench::advance(&mut gs, &moves);
+    }
+    for _ in turns {
+        bench::undo(&mut gs);
+    }
+    assert_eq!(bench::snapshot(&gs), before);
+});
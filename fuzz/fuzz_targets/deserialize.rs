@@ -0,0 +1,16 @@
+//! Arbitrary bytes posted as a move request.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ndsquared_rustapi::battlesnake::GameState;
+use ndsquared_rustapi_fuzz::check_move;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(gs) = serde_json::from_slice::<GameState>(data) {
+        // The move handlers reject states that fail validation before searching them
+        if gs.validate().is_ok() {
+            check_move(&gs);
+        }
+    }
+});
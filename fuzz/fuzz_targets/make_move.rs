@@ -0,0 +1,13 @@
+//! Structurally valid game states searched by every strategy.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ndsquared_rustapi_fuzz::{check_move, FuzzBoard};
+
+fuzz_target!(|board: FuzzBoard| {
+    if let Some(gs) = board.state() {
+        assert!(gs.validate().is_ok());
+        check_move(&gs);
+    }
+});
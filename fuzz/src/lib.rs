@@ -0,0 +1,219 @@
+//! Shared input generation and checks for the fuzz targets.
+
+use arbitrary::Arbitrary;
+use ndsquared_rustapi::battlesnake::{make_move, GameState, Personalities, Profile, Strategy};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Search time given to each strategy, short enough to keep the fuzzer fast.
+const SEARCH_BUDGET: Duration = Duration::from_millis(5);
+
+const MODES: [&str; 6] = [
+    "standard",
+    "solo",
+    "royale",
+    "squad",
+    "constrictor",
+    "wrapped",
+];
+
+#[derive(Debug, Arbitrary)]
+pub struct FuzzSnake {
+    start: (u8, u8),
+    /// Direction of each body part from the one before it, turning when blocked.
+    turns: Vec<u8>,
+    health: u8,
+    stacked: bool,
+}
+
+/// Raw material for a structurally valid game state: snakes with connected bodies that don't
+/// overlap each other, on a board of up to 19x19.
+#[derive(Debug, Arbitrary)]
+pub struct FuzzBoard {
+    width: u8,
+    height: u8,
+    mode: u8,
+    hazard_damage: u8,
+    turn: u16,
+    you: u8,
+    snakes: Vec<FuzzSnake>,
+    food: Vec<(u8, u8)>,
+    hazards: Vec<(u8, u8)>,
+}
+
+const STEPS: [(i32, i32); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
+
+impl FuzzBoard {
+    /// Builds the state as a move request, so it goes through the same deserialization.
+    pub fn state(&self) -> Option<GameState> {
+        let width = 1 + self.width as i32 % 19;
+        let height = 1 + self.height as i32 % 19;
+        let mut occupied: HashSet<(i32, i32)> = HashSet::new();
+        let mut snakes: Vec<Value> = Vec::new();
+        for (i, seed) in self.snakes.iter().take(8).enumerate() {
+            let start = (seed.start.0 as i32 % width, seed.start.1 as i32 % height);
+            if !occupied.insert(start) {
+                continue;
+            }
+            let mut body = vec![start];
+            for turn in seed.turns.iter().take(20) {
+                let (x, y) = *body.last().unwrap();
+                let next = (0..4)
+                    .map(|offset| STEPS[(*turn as usize + offset) % 4])
+                    .map(|(dx, dy)| (x + dx, y + dy))
+                    .find(|&(x, y)| {
+                        x >= 0 && y >= 0 && x < width && y < height && !occupied.contains(&(x, y))
+                    });
+                match next {
+                    Some(next) => {
+                        occupied.insert(next);
+                        body.push(next);
+                    }
+                    None => break,
+                }
+            }
+            if seed.stacked {
+                body.push(*body.last().unwrap());
+            }
+            let body: Vec<Value> = body.iter().map(|&(x, y)| json!({"x": x, "y": y})).collect();
+            snakes.push(json!({
+                "id": format!("snake-{}", i),
+                "name": format!("snake-{}", i),
+                "health": 1 + seed.health % 100,
+                "head": body[0],
+                "length": body.len(),
+                "body": body,
+                "latency": "0",
+                "shout": "",
+                "squad": "",
+                "customizations": {"color": "#888888", "head": "default", "tail": "default"},
+            }));
+        }
+        if snakes.is_empty() {
+            return None;
+        }
+        let coords = |coords: &[(u8, u8)]| -> Vec<Value> {
+            coords
+                .iter()
+                .map(|&(x, y)| json!({"x": x as i32 % width, "y": y as i32 % height}))
+                .collect()
+        };
+        let food: Vec<Value> = coords(&self.food)
+            .into_iter()
+            .filter(|coord| {
+                let (x, y) = (coord["x"].as_i64().unwrap(), coord["y"].as_i64().unwrap());
+                !occupied.contains(&(x as i32, y as i32))
+            })
+            .collect();
+        let you = snakes[self.you as usize % snakes.len()].clone();
+        let state = json!({
+            "game": {
+                "id": "fuzz",
+                "ruleset": {
+                    "name": MODES[self.mode as usize % MODES.len()],
+                    "version": "fuzz",
+                    "settings": {
+                        "foodSpawnChance": 15,
+                        "minimumFood": 1,
+                        "hazardDamagePerTurn": self.hazard_damage % 101,
+                        "royale": {"shrinkEveryNTurns": 25},
+                        "squad": {
+                            "allowBodyCollisions": false,
+                            "sharedElimination": false,
+                            "sharedHealth": false,
+                            "sharedLength": false,
+                        },
+                    },
+                },
+                "map": "standard",
+                "timeout": 500,
+                "source": "custom",
+            },
+            "turn": self.turn,
+            "board": {
+                "height": height,
+                "width": width,
+                "food": food,
+                "hazards": coords(&self.hazards),
+                "snakes": snakes,
+            },
+            "you": you,
+        });
+        Some(serde_json::from_value(state).expect("fuzz boards are valid move requests"))
+    }
+}
+
+/// Searches the state with every strategy, checking none moves off the board or into a snake's
+/// body while it has a free square to move to.
+pub fn check_move(gs: &GameState) {
+    let state = serde_json::to_value(gs).unwrap();
+    let (width, height) = (
+        state["board"]["width"].as_i64().unwrap(),
+        state["board"]["height"].as_i64().unwrap(),
+    );
+    let wrapped = state["game"]["ruleset"]["name"] == "wrapped";
+    let at = |coord: &Value| (coord["x"].as_i64().unwrap(), coord["y"].as_i64().unwrap());
+    let body: Vec<(i64, i64)> = state["you"]["body"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(at)
+        .collect();
+    let head = body[0];
+    // Squares taken by any snake next turn, which leaves out tails
+    let mut taken: HashSet<(i64, i64)> = HashSet::new();
+    for snake in state["board"]["snakes"].as_array().unwrap() {
+        let body: Vec<(i64, i64)> = snake["body"].as_array().unwrap().iter().map(at).collect();
+        taken.extend(&body[..body.len() - 1]);
+    }
+    // Hazards may be lethal, so they don't count as free squares
+    let hazards: HashSet<(i64, i64)> = state["board"]["hazards"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(at)
+        .collect();
+    let target = |direction: &str| {
+        let (dx, dy) = match direction {
+            "up" => (0, 1),
+            "down" => (0, -1),
+            "left" => (-1, 0),
+            "right" => (1, 0),
+            _ => panic!("unknown move {:?}", direction),
+        };
+        let (x, y) = (head.0 + dx, head.1 + dy);
+        if wrapped {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x, y)
+        }
+    };
+    let on_board = |(x, y): (i64, i64)| x >= 0 && y >= 0 && x < width && y < height;
+    let free = ["up", "down", "left", "right"].iter().any(|direction| {
+        let square = target(direction);
+        on_board(square) && !taken.contains(&square) && !hazards.contains(&square)
+    });
+    let personalities = Personalities::default();
+    for strategy in [Strategy::Minimax, Strategy::Basic, Strategy::Greedy] {
+        let personality = personalities
+            .default_personality()
+            .clone()
+            .with_strategy(strategy);
+        let deadline = Instant::now() + SEARCH_BUDGET;
+        let (response, _) = make_move(
+            gs.clone(),
+            &personality,
+            deadline,
+            Profile::Tournament,
+            &HashMap::new(),
+        )
+        .unwrap_or_else(|e| panic!("{:?} rejected a valid state: {}", strategy, e));
+        let response = serde_json::to_value(&response).unwrap();
+        let moved = target(response["move"].as_str().unwrap());
+        if free {
+            assert!(on_board(moved), "{:?} moved off the board", strategy);
+            assert!(!taken.contains(&moved), "{:?} moved into a snake", strategy);
+        }
+    }
+}
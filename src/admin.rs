@@ -0,0 +1,103 @@
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::{
+    Object, SecurityRequirement, SecurityScheme, SecuritySchemeData,
+};
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+
+/// Header carrying the admin API key.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// The admin API key, read from the `admin_key` config value.
+///
+/// Admin endpoints are closed to everyone when no key is configured.
+#[derive(Debug, Default)]
+pub struct AdminAuth {
+    key: Option<String>,
+}
+
+impl AdminAuth {
+    pub fn new(key: Option<String>) -> Self {
+        let key = key.filter(|key| !key.is_empty());
+        if key.is_none() {
+            warn!("no admin_key configured, admin endpoints are disabled");
+        }
+        AdminAuth { key }
+    }
+    fn verify(&self, key: &str) -> bool {
+        match &self.key {
+            Some(expected) => constant_time_eq(expected.as_bytes(), key.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+/// Compares keys without returning early, so response times don't leak how much of a guess matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Request guard for endpoints that expose game logs, stats or configuration.
+pub struct Admin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let auth = match request.rocket().state::<AdminAuth>() {
+            Some(auth) if auth.key.is_some() => auth,
+            _ => return Outcome::Error((Status::Forbidden, "admin endpoints are disabled")),
+        };
+        match request.headers().get_one(API_KEY_HEADER) {
+            Some(key) if auth.verify(key) => Outcome::Success(Admin),
+            _ => Outcome::Error((Status::Unauthorized, "missing or invalid api key")),
+        }
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for Admin {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        let scheme = SecurityScheme {
+            description: Some(format!(
+                "Admin API key, configured with admin_key, sent in the {} header.",
+                API_KEY_HEADER
+            )),
+            data: SecuritySchemeData::ApiKey {
+                name: API_KEY_HEADER.to_owned(),
+                location: "header".to_owned(),
+            },
+            extensions: Object::default(),
+        };
+        let mut requirement = SecurityRequirement::new();
+        requirement.insert("ApiKeyAuth".to_owned(), Vec::new());
+        Ok(RequestHeaderInput::Security(
+            "ApiKeyAuth".to_owned(),
+            scheme,
+            requirement,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let auth = AdminAuth::new(Some("secret".to_owned()));
+        assert!(auth.verify("secret"));
+        assert!(!auth.verify("secreT"));
+        assert!(!auth.verify("secrets"));
+        assert!(!auth.verify(""));
+        let auth = AdminAuth::new(Some("".to_owned()));
+        assert!(!auth.verify(""));
+        assert!(!AdminAuth::new(None).verify(""));
+    }
+}
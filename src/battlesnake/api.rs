@@ -0,0 +1,1077 @@
+//! What the server answers with: move responses and their telemetry, personalities, deadlines,
+//! post-mortems, and the analysis and simulation tools.
+
+use super::*;
+use crate::profiling::{self, PhaseTimes};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Customizations {
+    /// Hex color code used to display this Battlesnake. Must start with "#" and be 7 characters long. Example: "#888888"
+    pub(crate) color: String,
+    /// Displayed head of this Battlesnake. Example: "default"
+    pub(crate) head: String,
+    /// Displayed tail of this Battlesnake. Example: "default"
+    pub(crate) tail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Info {
+    /// Version of the Battlesnake API implemented by this Battlesnake. Currently only API version 1 is valid. Example: "1"
+    apiversion: String,
+    /// Username of the author of this Battlesnake. If provided, this will be used to verify ownership. Example: "BattlesnakeOfficial"
+    pub(crate) author: String,
+    /// The collection of customizations applied to this Battlesnake that represent how it is viewed.
+    #[serde(flatten)]
+    pub(crate) customizations: Customizations,
+    /// A version number or tag for your snake.
+    pub(crate) version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct MoveResponse {
+    /// Your Battlesnake's move for this turn. Valid moves are up, down, left, or right. Example: "up"
+    #[serde(rename = "move")]
+    pub(crate) direction: Direction,
+    /// An optional message sent to all other Battlesnakes on the next turn. Must be 256 characters or less. Example: "I am moving up!"
+    shout: String,
+}
+
+impl MoveResponse {
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RootScore {
+    /// Direction of the root move. Example: "up"
+    #[serde(rename = "move")]
+    direction: Direction,
+    /// Score sum of the root move in the last completed search iteration.
+    score: i32,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SearchStats {
+    /// Deepest fully completed search iteration.
+    pub depth: u32,
+    /// Total leaf positions evaluated across all iterations.
+    pub nodes: u32,
+    /// Time spent searching, in milliseconds.
+    pub time: u64,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Analysis {
+    /// The move the engine would play. Example: "up"
+    #[serde(rename = "move")]
+    pub(crate) direction: Direction,
+    /// Sum of the best score found.
+    score: i32,
+    /// The best score found, broken down by evaluation term.
+    score_breakdown: Score,
+    /// Our moves along the principal variation. Example: ["up", "left"]
+    pub(crate) pv: Vec<Direction>,
+    /// Every snake's moves along the principal variation, ours first.
+    pub(crate) pv_lines: Vec<PvLine>,
+    /// The board after every turn of the principal variation, in the notation of /board. Only
+    /// with `analyze_with_boards`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) pv_boards: Option<Vec<String>>,
+    /// The principal variation as searched, for drawing its boards.
+    #[serde(skip)]
+    #[cfg_attr(not(feature = "render"), allow(dead_code))]
+    pub(crate) best_pv: Vec<(SnakeId, Coord)>,
+    /// Scores of each root move searched.
+    pub(crate) root_scores: Vec<RootScore>,
+    /// Id of the snake controlling each square, indexed as territory[y][x].
+    pub(crate) territory: Vec<Vec<Option<String>>>,
+    /// Who gets to each square first, contested squares included, indexed as
+    /// first_arrivals[y][x]. Null for squares nobody can get to.
+    pub(crate) first_arrivals: Vec<Vec<Option<FirstArrival>>>,
+    /// Each of our four moves compared by the same shallow search, for coloring the squares
+    /// around our head.
+    pub(crate) candidates: Vec<Candidate>,
+    /// Free squares whose taking would wall off part of the board, with the sizes of the parts.
+    pub(crate) chokepoints: Vec<Chokepoint>,
+    /// Zobrist key of the position analyzed, as in game recordings, in hex since JSON numbers
+    /// can't hold every key. Example: "3f2a9c04d1e87b56"
+    pub(crate) position_key: String,
+    /// Statistics about the search that produced this analysis.
+    stats: SearchStats,
+}
+
+/// One of our moves from the root, searched on its own to a fixed depth.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Candidate {
+    /// The move. Example: "up"
+    #[serde(rename = "move")]
+    pub(crate) direction: Direction,
+    /// Square the move takes our head to.
+    pub(crate) square: Coord,
+    /// Score sum of searching the move `CANDIDATE_DEPTH` turns deep, or null if the move is
+    /// fatal at once or the search ran out of time.
+    pub(crate) score: Option<i32>,
+    /// Squares we'd control after the move, with the other snakes where they are now, less the
+    /// squares we control now. Null if the move is fatal at once.
+    pub(crate) territory_delta: Option<i32>,
+}
+
+/// The snake that gets to a square first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct FirstArrival {
+    /// Id of the snake, ours when we're among those tied for first. Example: "gs_abc123"
+    pub snake: String,
+    /// Moves it takes to get there.
+    pub distance: u32,
+    /// Whether a snake that isn't on the same squad gets there just as soon.
+    pub tied: bool,
+}
+
+/// One snake's moves along a line of the search.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct PvLine {
+    /// Id of the snake. Example: "gs_abc123"
+    pub snake: String,
+    /// Its moves, a turn at a time. Example: ["up", "left"]
+    pub moves: Vec<Direction>,
+}
+
+/// What the engine saw and decided on a single turn of a live game.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Telemetry {
+    /// Id of the game this turn belongs to.
+    game_id: String,
+    /// Name of our snake in this game.
+    snake: String,
+    turn: u32,
+    /// The board as received in the move request.
+    board: Board,
+    /// The move that was played. Example: "up"
+    #[serde(rename = "move")]
+    direction: Direction,
+    /// Sum of the best score found.
+    score: i32,
+    /// The best score found, broken down by evaluation term.
+    score_breakdown: Score,
+    /// Deepest fully completed search iteration.
+    depth: u32,
+    /// Time spent searching, in milliseconds.
+    pub(crate) time: u64,
+    /// Where the time of the turn went, when built with the `profiling` feature.
+    phases: PhaseTimes,
+}
+
+impl Telemetry {
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+    Survived,
+    Wall,
+    Starvation,
+    Hazard,
+    SelfCollision,
+    BodyCollision,
+    HeadToHead,
+    Unknown,
+}
+
+/// Summary of how a game went for our snake, built from the final game state.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GameOutcome {
+    pub game_id: String,
+    /// Id of our snake the outcome is for, as several of ours may play in one game. Empty in
+    /// outcomes stored before it was added.
+    #[serde(default)]
+    pub snake_id: String,
+    pub mode: String,
+    pub map: String,
+    /// Names of the other snakes in the game.
+    pub opponents: Vec<String>,
+    /// Number of turns our snake survived.
+    pub turns: u32,
+    /// Finishing position, where 1 is the last snake standing.
+    pub placement: u32,
+    pub won: bool,
+    pub cause: DeathCause,
+    /// Experiment variant the game was assigned to, as "<experiment>/<strategy>".
+    pub variant: Option<String>,
+}
+
+/// Name of a unit enum variant as it appears in the Battlesnake API.
+pub(crate) fn variant_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// Drop in our best score from one turn to the next that counts as the game turning against us.
+const SCORE_COLLAPSE_DROP: i64 = 20000;
+
+/// Change in our best score between two consecutive moves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScoreSwing {
+    /// Turn of the second move.
+    pub(crate) turn: u32,
+    pub(crate) from: i32,
+    pub(crate) to: i32,
+}
+
+impl ScoreSwing {
+    fn size(&self) -> i64 {
+        (self.to as i64 - self.from as i64).abs()
+    }
+}
+
+/// What decided a game for our snake, worked out when it ends.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PostMortem {
+    pub game_id: String,
+    pub cause: DeathCause,
+    /// Number of turns our snake survived.
+    pub turns: u32,
+    /// First turn the search saw every line losing, or our best score dropped sharply.
+    pub collapse_turn: Option<u32>,
+    /// Largest change in our best score between consecutive moves, leaving out won and lost positions.
+    pub largest_swing: Option<ScoreSwing>,
+}
+
+/// Looks back over the scores kept in the session for where the game turned.
+pub fn post_mortem(outcome: &GameOutcome, session: Option<&GameSession>) -> PostMortem {
+    let decided = |score: i32| score == i32::MIN || score == i32::MAX;
+    let mut collapse_turn: Option<u32> = None;
+    let mut largest_swing: Option<ScoreSwing> = None;
+    let mut previous: Option<i32> = None;
+    for &(turn, score) in session.map(|s| s.scores.as_slice()).unwrap_or_default() {
+        if collapse_turn.is_none() && score == i32::MIN {
+            collapse_turn = Some(turn);
+        }
+        if let Some(from) = previous.filter(|&from| !decided(from) && !decided(score)) {
+            let swing = ScoreSwing {
+                turn,
+                from,
+                to: score,
+            };
+            if collapse_turn.is_none() && (score as i64 - from as i64) <= -SCORE_COLLAPSE_DROP {
+                collapse_turn = Some(turn);
+            }
+            let larger = match &largest_swing {
+                Some(largest) => swing.size() > largest.size(),
+                None => true,
+            };
+            if larger {
+                largest_swing = Some(swing);
+            }
+        }
+        previous = Some(score);
+    }
+    PostMortem {
+        game_id: outcome.game_id.clone(),
+        cause: outcome.cause,
+        turns: outcome.turns,
+        collapse_turn,
+        largest_swing,
+    }
+}
+
+/// Builds the outcome of a game from its end request and, if available, the session kept during it.
+pub fn outcome(gs: &GameState, session: Option<&GameSession>) -> GameOutcome {
+    let cause = gs.death_cause();
+    let survived = cause == DeathCause::Survived;
+    let opponents = match session {
+        Some(session) => session.opponents.clone(),
+        None => gs
+            .board
+            .snakes
+            .iter()
+            .filter(|snake| snake.id != gs.you.id)
+            .map(|snake| snake.details.name.clone())
+            .collect(),
+    };
+    let (turns, placement) = match (survived, session) {
+        (true, _) => (gs.turn, 1),
+        // Every snake still alive on our last move outlasted us
+        (false, Some(session)) => (session.turn + 1, session.snakes_alive as u32),
+        (false, None) => (gs.turn, gs.board.snakes.len() as u32 + 1),
+    };
+    GameOutcome {
+        game_id: gs.game.id.clone(),
+        snake_id: gs.you.id.clone(),
+        mode: variant_name(&gs.game.ruleset.name),
+        map: variant_name(&gs.game.map),
+        opponents,
+        turns,
+        placement,
+        won: survived && gs.board.snakes.len() <= 1,
+        cause,
+        variant: session.and_then(|session| {
+            let experiment = session.experiment.as_ref()?;
+            Some(format!(
+                "{}/{}",
+                experiment,
+                variant_name(&session.variant?)
+            ))
+        }),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Iterative deepening minimax, using territory evaluation for up to four snakes.
+    Minimax,
+    /// Iterative deepening minimax that always uses the cheaper basic evaluation.
+    Basic,
+    /// Heads for the closest reachable food without searching.
+    Greedy,
+    /// Moves to a random safe square, as a baseline to measure the others against.
+    Random,
+    /// Monte Carlo tree search with random playouts.
+    #[cfg(feature = "mcts")]
+    Mcts,
+}
+
+/// A snake we can field, with its own appearance and strategy.
+#[derive(Debug, Clone)]
+pub struct Personality {
+    author: String,
+    version: String,
+    customizations: Customizations,
+    pub(crate) strategy: Strategy,
+    /// Nodes evaluated after which every search stops, in place of its timeout.
+    node_budget: Option<u32>,
+    /// Deepest leaf, in turns, given the full evaluation rather than the basic one.
+    pub(crate) full_evaluation_depth: Option<i32>,
+    /// Directory every search tree is written to, a file per turn.
+    pub(crate) search_trees: Option<PathBuf>,
+    /// Directory a crash dump is written to when a search panics.
+    pub(crate) crash_dumps: Option<PathBuf>,
+}
+
+impl Personality {
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+    /// Stops every search after evaluating a fixed number of nodes rather than at its timeout,
+    /// so its moves don't depend on how fast the machine is.
+    pub fn with_node_budget(mut self, node_budget: u32) -> Self {
+        self.node_budget = Some(node_budget);
+        self
+    }
+    /// The policy that chooses this personality's moves, expecting opponents to play as they have.
+    pub fn policy(
+        &self,
+        profile: Profile,
+        tendencies: &HashMap<String, Tendencies>,
+    ) -> Box<dyn MovePolicy> {
+        let mut minimax = MinimaxPolicy {
+            node_budget: self.node_budget,
+            full_evaluation_depth: self.full_evaluation_depth,
+            record_tree: self.search_trees.is_some(),
+            tendencies: tendencies.clone(),
+            ..MinimaxPolicy::default()
+        };
+        if profile == Profile::Custom {
+            minimax.log_level = log::Level::Info;
+        }
+        self.strategy.policy(minimax)
+    }
+}
+
+/// Body of a strategy override request.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StrategyOverride {
+    /// Strategy to switch to, or null to go back to the personality's own. Example: "greedy"
+    pub strategy: Option<Strategy>,
+}
+
+/// Overrides for a personality's customizations. Unset fields keep the built-in value.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CustomizationsConfig {
+    pub(crate) color: Option<String>,
+    pub(crate) head: Option<String>,
+    pub(crate) tail: Option<String>,
+}
+
+/// Snake info settings, read from the `snake` table of the Rocket config and `SNAKE_` environment variables.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SnakeConfig {
+    /// Username reported as the author of every personality.
+    pub(crate) author: String,
+    /// Version reported by every personality. Defaults to the crate version plus the git SHA it was built from.
+    pub(crate) version: String,
+    /// Customization overrides, keyed by personality name.
+    pub(crate) personalities: HashMap<String, CustomizationsConfig>,
+    /// Deepest leaf, in turns, that searches score with the full evaluation. Deeper leaves get
+    /// the basic evaluation, which is much cheaper. Unset scores every leaf in full.
+    pub(crate) full_evaluation_depth: Option<i32>,
+    /// Directory to write the tree of every search to, as `<game id>-<turn>.json`, for debugging.
+    /// Unset writes none.
+    pub(crate) search_trees: Option<PathBuf>,
+    /// Directory to write the state of every move whose search panics to, with the line it was
+    /// searching, as `crash-<game id>-<turn>-<unix millis>.json`. Unset writes none.
+    pub(crate) crash_dumps: Option<PathBuf>,
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        let version = match option_env!("GIT_SHA") {
+            Some(sha) if !sha.is_empty() => format!("{}+{}", env!("CARGO_PKG_VERSION"), sha),
+            _ => env!("CARGO_PKG_VERSION").to_owned(),
+        };
+        SnakeConfig {
+            author: "DeanRefined".to_owned(),
+            version,
+            personalities: HashMap::new(),
+            full_evaluation_depth: None,
+            search_trees: None,
+            crash_dumps: None,
+        }
+    }
+}
+
+/// Personalities mounted under /snakes/<name>, keyed by name.
+#[derive(Debug)]
+pub struct Personalities {
+    personalities: HashMap<String, Personality>,
+}
+
+/// Name of the personality served from the root routes.
+pub const DEFAULT_PERSONALITY: &str = "minimax";
+
+impl Default for Personalities {
+    fn default() -> Self {
+        Personalities::new(&SnakeConfig::default())
+    }
+}
+
+impl Personalities {
+    pub fn new(config: &SnakeConfig) -> Self {
+        let personality = |color: &str, strategy: Strategy| Personality {
+            author: config.author.clone(),
+            version: config.version.clone(),
+            customizations: Customizations {
+                color: color.to_owned(),
+                head: "pixel".to_owned(),
+                tail: "pixel".to_owned(),
+            },
+            strategy,
+            node_budget: None,
+            full_evaluation_depth: config.full_evaluation_depth,
+            search_trees: config.search_trees.clone(),
+            crash_dumps: config.crash_dumps.clone(),
+        };
+        let mut personalities: HashMap<String, Personality> = HashMap::new();
+        personalities.insert(
+            DEFAULT_PERSONALITY.to_owned(),
+            personality("#6434eb", Strategy::Minimax),
+        );
+        personalities.insert("basic".to_owned(), personality("#eb9834", Strategy::Basic));
+        personalities.insert(
+            "greedy".to_owned(),
+            personality("#34eb5c", Strategy::Greedy),
+        );
+        personalities.insert(
+            "random".to_owned(),
+            personality("#999999", Strategy::Random),
+        );
+        #[cfg(feature = "mcts")]
+        personalities.insert("mcts".to_owned(), personality("#eb3434", Strategy::Mcts));
+        for (name, overrides) in &config.personalities {
+            let personality = match personalities.get_mut(name) {
+                Some(personality) => personality,
+                None => {
+                    warn!("no personality named {:?} to customize", name);
+                    continue;
+                }
+            };
+            let customizations = &mut personality.customizations;
+            if let Some(color) = &overrides.color {
+                customizations.color = color.clone();
+            }
+            if let Some(head) = &overrides.head {
+                customizations.head = head.clone();
+            }
+            if let Some(tail) = &overrides.tail {
+                customizations.tail = tail.clone();
+            }
+        }
+        Personalities { personalities }
+    }
+    pub fn get(&self, name: &str) -> Option<&Personality> {
+        self.personalities.get(name)
+    }
+    pub fn default_personality(&self) -> &Personality {
+        &self.personalities[DEFAULT_PERSONALITY]
+    }
+}
+
+pub fn info(personality: &Personality) -> Info {
+    let result = Info {
+        apiversion: "1".to_owned(),
+        author: personality.author.clone(),
+        customizations: personality.customizations.clone(),
+        version: personality.version.clone(),
+    };
+
+    info!("{:?}", result);
+
+    result
+}
+
+/// Chooses our move, stopping the search in time to respond before `deadline`. States that
+/// don't pass `GameState::validate` aren't searched.
+pub fn make_move(
+    mut gs: GameState,
+    personality: &Personality,
+    deadline: Instant,
+    profile: Profile,
+    tendencies: &HashMap<String, Tendencies>,
+) -> Result<(MoveResponse, Telemetry), EngineError> {
+    gs.validate()?;
+    info!(
+        "########## TURN {:?} | {:?} ##########",
+        gs.turn, gs.you.details.name
+    );
+    let board = gs.board.clone();
+    profiling::reset();
+    tracing::info_span!("metadata").in_scope(|| gs.init());
+
+    // Solo games are solved rather than searched, whatever the personality, and so are the
+    // first turns of the others
+    let mut policy: Box<dyn MovePolicy> = if MazePolicy::suits(&gs) {
+        Box::new(MazePolicy)
+    } else if CyclePolicy::suits(&gs) {
+        Box::new(CyclePolicy)
+    } else if OpeningPolicy::suits(&gs) {
+        Box::new(OpeningPolicy)
+    } else {
+        personality.policy(profile, tendencies)
+    };
+    let deadline = deadline.checked_sub(RESPONSE_HEADROOM).unwrap_or(deadline);
+    let chosen = panic::catch_unwind(AssertUnwindSafe(|| policy.choose(&gs, deadline)));
+    let (direction, report) = match chosen {
+        Ok(chosen) => chosen,
+        Err(payload) => {
+            if let Some(directory) = &personality.crash_dumps {
+                write_crash_dump(
+                    directory,
+                    &gs,
+                    &take_panic_line(),
+                    &panic_message(&*payload),
+                );
+            }
+            panic::resume_unwind(payload);
+        }
+    };
+    let direction = gs.legal_direction(direction);
+
+    let mr = MoveResponse {
+        direction,
+        shout: format!(
+            "MOVE: {:?} | SCORE: {:?} | TIME: {:?} | ITERATIONS: {:?} | PV LENGTH: {:?}",
+            direction,
+            report.score.sum(),
+            report.time,
+            report.depth,
+            report.pv.len()
+        ),
+    };
+
+    info!("{:?}", mr);
+    info!("{:?}", report.score);
+    if report.ordering.cutoffs > 0 {
+        info!(
+            "ORDERING: FIRST MOVE CUTOFFS {:.0}% | AVERAGE CUTOFF INDEX {:.2} | HINT HITS {:.0}%",
+            report.ordering.first_move_cutoff_rate() * 100.0,
+            report.ordering.average_cutoff_index(),
+            report.ordering.hint_hit_rate() * 100.0
+        );
+    }
+    for line in gs.pv_lines(&report.pv) {
+        info!("PV {}: {:?}", line.snake, line.moves);
+    }
+    #[cfg(feature = "render")]
+    if log::log_enabled!(log::Level::Debug) {
+        for board in pv_boards(&gs, &report.pv) {
+            debug!("\n{}", board);
+        }
+    }
+    if let (Some(directory), Some(tree)) = (&personality.search_trees, &report.tree) {
+        write_search_tree(directory, &gs, tree);
+    }
+    let phases = profiling::times();
+    if cfg!(feature = "profiling") {
+        info!("{:?}", phases);
+    }
+
+    let telemetry = Telemetry {
+        game_id: gs.game.id.clone(),
+        snake: gs.you.details.name.clone(),
+        turn: gs.turn,
+        board,
+        direction,
+        score: report.score.sum(),
+        score_breakdown: report.score,
+        depth: report.depth,
+        time: report.time as u64,
+        phases,
+    };
+
+    Ok((mr, telemetry))
+}
+
+/// Writes the tree of a turn's search to `directory` as `<game id>-<turn>.json`, logging rather
+/// than failing the move if it can't.
+fn write_search_tree(directory: &Path, gs: &GameState, tree: &SearchTree) {
+    let path = directory.join(format!("{}-{}.json", gs.game.id, gs.turn));
+    let written = fs::create_dir_all(directory)
+        .and_then(|_| fs::File::create(&path))
+        .and_then(|file| Ok(serde_json::to_writer(io::BufWriter::new(file), tree)?));
+    if let Err(e) = written {
+        warn!("unable to write search tree to {:?}: {}", path, e);
+    }
+}
+
+/// A move whose search panicked, written out to be replayed as a test case.
+#[derive(Debug, Serialize)]
+pub(crate) struct CrashDump<'a> {
+    /// What the panic said.
+    pub(crate) error: &'a str,
+    /// The state as the move request sent it.
+    pub(crate) state: &'a GameState,
+    /// Each snake's moves from the state to where the search panicked.
+    pub(crate) line: Vec<PvLine>,
+}
+
+/// The message a panic was raised with, if it was raised with one.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => (*message).to_owned(),
+        (_, Some(message)) => message.clone(),
+        _ => "unknown panic".to_owned(),
+    }
+}
+
+/// Writes the state and line of a search that panicked to `directory`, timestamped so repeated
+/// crashes on a turn don't overwrite each other, and logs where. Returns the path written.
+pub(crate) fn write_crash_dump(
+    directory: &Path,
+    gs: &GameState,
+    line: &[(SnakeId, Coord)],
+    error: &str,
+) -> Option<PathBuf> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    let path = directory.join(format!("crash-{}-{}-{}.json", gs.game.id, gs.turn, millis));
+    let dump = CrashDump {
+        error,
+        state: gs,
+        line: gs.pv_lines(line),
+    };
+    let written = fs::create_dir_all(directory)
+        .and_then(|_| fs::File::create(&path))
+        .and_then(|file| Ok(serde_json::to_writer(io::BufWriter::new(file), &dump)?));
+    match written {
+        Ok(_) => {
+            error!("search panicked, wrote a crash dump to {:?}", path);
+            Some(path)
+        }
+        Err(e) => {
+            warn!("unable to write crash dump to {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Headroom left under the game timeout for the move response to reach the engine, until the
+/// network overhead of the game has been estimated.
+pub(crate) const MOVE_DEADLINE_MARGIN: u32 = 50;
+
+/// Added to the estimated network overhead to absorb jitter.
+pub(crate) const LATENCY_SAFETY_MARGIN: u32 = 15;
+
+/// Bounds on the adaptive margin, so one bad sample can neither leave us no headroom nor no time to search.
+pub(crate) const MIN_MOVE_DEADLINE_MARGIN: u32 = 20;
+pub(crate) const MAX_MOVE_DEADLINE_MARGIN: u32 = 250;
+
+/// Extra headroom in custom games, which log every search iteration.
+pub(crate) const CUSTOM_GAME_MARGIN: u32 = 50;
+
+/// Time left between stopping the search and the move deadline, for building and serializing the response.
+const RESPONSE_HEADROOM: Duration = Duration::from_millis(25);
+
+/// Hard limit on how long a move request may take, measured from its arrival.
+pub fn move_deadline(gs: &GameState, session: &GameSession) -> Duration {
+    Duration::from_millis(gs.game.timeout.saturating_sub(session.move_margin()) as u64)
+}
+
+/// Cheap move used when the search fails, panics or overruns its deadline. States the engine
+/// can't play at all get an arbitrary move.
+pub fn fallback_move(mut gs: GameState) -> MoveResponse {
+    let direction = match gs.validate() {
+        Ok(()) => {
+            gs.init();
+            gs.legal_direction(gs.greedy_move())
+        }
+        Err(_) => Direction::Up,
+    };
+    let mr = MoveResponse {
+        direction,
+        shout: format!("MOVE: {:?} | FALLBACK", direction),
+    };
+    warn!("{:?}", mr);
+    mr
+}
+
+/// Search time spent warming up at the start of every game, unless configured otherwise.
+pub const WARM_UP_BUDGET: Duration = Duration::from_millis(50);
+
+/// Searches the start position for `budget`, so the first move doesn't pay for cold caches, a
+/// cold allocator or a cold search worker. Returns the depth reached.
+pub fn warm_up(mut gs: GameState, personality: &Personality, budget: Duration) -> u32 {
+    gs.init();
+    let mut policy = personality.policy(Profile::Ladder, &HashMap::new());
+    policy.choose(&gs, Instant::now() + budget).1.depth
+}
+
+/// The move the `shadow` strategy would play for `personality`, searched for its budget or until
+/// the real move's `deadline`, whichever comes first. The search is quiet whatever the game, and
+/// never writes a search tree.
+pub fn shadow_move(
+    mut gs: GameState,
+    personality: &Personality,
+    shadow: &Shadow,
+    deadline: Instant,
+    tendencies: &HashMap<String, Tendencies>,
+) -> Result<Direction, EngineError> {
+    gs.validate()?;
+    gs.init();
+    let mut personality = personality.clone().with_strategy(shadow.strategy);
+    personality.search_trees = None;
+    let mut policy = personality.policy(Profile::Ladder, tendencies);
+    let deadline = deadline.checked_sub(RESPONSE_HEADROOM).unwrap_or(deadline);
+    let deadline = deadline.min(Instant::now() + Duration::from_millis(shadow.budget));
+    let (direction, _) = policy.choose(&gs, deadline);
+    Ok(gs.legal_direction(direction))
+}
+
+/// Outcome of searching a canned position, used by the readiness probe.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SelfCheck {
+    /// Whether the search found the only safe move.
+    pub passed: bool,
+    /// The move the search chose. Example: "right"
+    #[serde(rename = "move")]
+    pub(crate) direction: Direction,
+    /// Deepest fully completed search iteration.
+    depth: u32,
+    pub(crate) nodes: u32,
+    /// Time spent searching, in milliseconds.
+    time: u64,
+}
+
+/// Searches a fixed two snake position for `budget`, checking the engine can still find a move.
+pub fn self_check(budget: Duration) -> SelfCheck {
+    let mut gs = GameState::new_probe();
+    let mut search = Search::new(&gs);
+    search.deadline = Some(Instant::now() + budget);
+    search.iterative_deepening(&mut gs, 50);
+    SelfCheck {
+        passed: search.best_direction == Direction::Right && search.nodes > 0,
+        direction: search.best_direction,
+        depth: search.iteration_reached,
+        nodes: search.nodes,
+        time: search.search_time as u64,
+    }
+}
+
+/// Depth, in turns, of the search that scores every candidate move of an analysis alike.
+const CANDIDATE_DEPTH: u32 = 2;
+
+/// Search time of each candidate move, in milliseconds.
+const CANDIDATE_TIMEOUT: u128 = SEARCH_TIMEOUT / 4;
+
+/// Scores each of our moves from `gs`, which has to have been initialized, with a search of the
+/// same depth, rather than as deep as each one happened to get in the full search.
+fn candidates(gs: &mut GameState) -> Vec<Candidate> {
+    let you = gs.you().clone();
+    let territory = gs.compute_territory_info().controlled[you.sid.index()] as i32;
+    gs.adjacent_moves(&you.head)
+        .into_iter()
+        .map(|(square, direction)| {
+            let mut candidate = Candidate {
+                direction,
+                square,
+                score: None,
+                territory_delta: None,
+            };
+            if !gs.viable(&square) || gs.neck_at(&you.head) == Some(square) {
+                return candidate;
+            }
+            let mut search = Search::new(gs);
+            search.root_move = Some(direction);
+            search.timeout = CANDIDATE_TIMEOUT;
+            let plies = CANDIDATE_DEPTH * search.snake_order.len() as u32;
+            search.iterative_deepening(gs, plies);
+            candidate.score = search
+                .root_scores
+                .first()
+                .filter(|_| search.iteration_reached == plies)
+                .map(|(_, score, _)| score.sum());
+            if gs.advance(&[(you.sid, square)]).is_ok() {
+                let controlled = gs.compute_territory_info().controlled[you.sid.index()] as i32;
+                candidate.territory_delta = Some(controlled - territory);
+                let _ = gs.undo();
+            }
+            candidate
+        })
+        .collect()
+}
+
+pub fn analyze(mut gs: GameState) -> Analysis {
+    gs.init();
+
+    let territory_info = gs.compute_territory_info();
+    let chokepoints = gs.partition().chokepoints().to_vec();
+    let mut territory = vec![vec![None; gs.board.width as usize]; gs.board.height as usize];
+    for (square, owner) in territory_info.owners.iter() {
+        if let Some((index, _)) = owner {
+            territory[square.y as usize][square.x as usize] =
+                Some(gs.board.snakes[*index].id.clone());
+        }
+    }
+    let mut first_arrivals = vec![vec![None; gs.board.width as usize]; gs.board.height as usize];
+    for (square, arrival) in territory_info.arrivals.iter() {
+        if let Some(arrival) = arrival {
+            first_arrivals[square.y as usize][square.x as usize] = Some(FirstArrival {
+                snake: gs.board.snakes[arrival.snake].id.clone(),
+                distance: arrival.distance,
+                tied: arrival.tied,
+            });
+        }
+    }
+
+    let mut search = Search::new(&gs);
+    search.iterative_deepening(&mut gs, 50);
+    let candidates = candidates(&mut gs);
+
+    let pv_lines = gs.pv_lines(&search.best_pv);
+    let pv = pv_lines
+        .iter()
+        .find(|line| line.snake == gs.you.id)
+        .map(|line| line.moves.clone())
+        .unwrap_or_default();
+
+    Analysis {
+        direction: search.best_direction,
+        score: search.best_score.sum(),
+        score_breakdown: search.best_score.clone(),
+        pv,
+        pv_lines,
+        pv_boards: None,
+        best_pv: search.best_pv.clone(),
+        root_scores: search
+            .root_scores
+            .iter()
+            .map(|(direction, score, _)| RootScore {
+                direction: *direction,
+                score: score.sum(),
+            })
+            .collect(),
+        territory,
+        first_arrivals,
+        candidates,
+        chokepoints,
+        position_key: format!("{:016x}", gs.position_key()),
+        stats: search.stats(),
+    }
+}
+
+/// A root move and the line the search expects to follow it.
+#[derive(Debug, Clone)]
+pub struct Variation {
+    /// The root move, named as in move responses. Example: "up"
+    pub direction: String,
+    /// Score sum of the root move in the last completed search iteration.
+    pub score: i32,
+    /// Whether the engine would play this move.
+    pub best: bool,
+    /// The position before the move, then after every turn of the line.
+    pub positions: Vec<GameState>,
+}
+
+impl GameState {
+    /// Each snake's moves along a line of the search, in the order they first move in it.
+    pub fn pv_lines(&self, line: &[(SnakeId, Coord)]) -> Vec<PvLine> {
+        let mut heads: Vec<(SnakeId, Coord)> = Vec::new();
+        let mut lines: Vec<PvLine> = Vec::new();
+        for &(sid, coord) in line {
+            let i = match heads.iter().position(|(moved, _)| *moved == sid) {
+                Some(i) => i,
+                None => match self.board.snake(sid) {
+                    Some(snake) => {
+                        heads.push((sid, snake.head));
+                        lines.push(PvLine {
+                            snake: snake.id.clone(),
+                            moves: Vec::new(),
+                        });
+                        lines.len() - 1
+                    }
+                    None => continue,
+                },
+            };
+            if let Some(direction) = self.direction_to(&heads[i].1, &coord) {
+                lines[i].moves.push(direction);
+            }
+            heads[i].1 = coord;
+        }
+        lines
+    }
+    /// Plays out a line of the search, returning the position after each of its complete turns.
+    pub(crate) fn line_positions(
+        &self,
+        line: &[(SnakeId, Coord)],
+        snakes: usize,
+    ) -> Vec<GameState> {
+        let mut gs = self.clone();
+        let mut positions = vec![self.clone()];
+        for moves in line.chunks_exact(snakes.max(1)) {
+            if gs.advance(moves).is_err() {
+                break;
+            }
+            // Lines are never undone, so the undo buffers can be reused from the start. Later
+            // moves name snakes by `SnakeId`, so only the copies are committed.
+            gs.undo_index = 0;
+            gs.turn += 1;
+            let mut position = gs.clone();
+            position.commit();
+            positions.push(position);
+        }
+        positions
+    }
+}
+
+/// Searches the position like `analyze`, returning every root move with the line it expects to
+/// follow, the move it would play first.
+pub fn variations(mut gs: GameState) -> Vec<Variation> {
+    gs.init();
+    let mut search = Search::new(&gs);
+    search.iterative_deepening(&mut gs, 50);
+    let mut variations: Vec<Variation> = search
+        .root_scores
+        .iter()
+        .map(|(direction, score, line)| Variation {
+            direction: variant_name(direction),
+            score: score.sum(),
+            best: *direction == search.best_direction,
+            positions: gs.line_positions(line, search.snake_order.len()),
+        })
+        .collect();
+    variations.sort_by_key(|variation| (!variation.best, Reverse(variation.score)));
+    variations
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SimulateRequest {
+    /// Game state to simulate from.
+    pub(crate) state: GameState,
+    /// Moves to apply for each snake id, one per turn. Snakes without a move for a turn continue in their current direction. Example: {"snake-one": ["up", "left"]}
+    pub(crate) moves: HashMap<String, Vec<Direction>>,
+}
+
+impl SimulateRequest {
+    pub fn validate(&self) -> Result<(), EngineError> {
+        self.state.validate()?;
+        match self
+            .moves
+            .keys()
+            .find(|id| self.state.board.get_snake(id).is_none())
+        {
+            Some(id) => Err(EngineError::UnknownSnake(id.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Steps the posted game state forward, returning the state after each turn.
+///
+/// Food spawning and royale hazard growth aren't simulated.
+pub fn simulate(request: SimulateRequest) -> Result<Vec<GameState>, EngineError> {
+    request.validate()?;
+    let mut gs = request.state;
+    gs.init();
+    let turns = request.moves.values().map(Vec::len).max().unwrap_or(0);
+    let mut states: Vec<GameState> = Vec::new();
+    for turn in 0..turns {
+        let mut moves: Vec<(SnakeId, Coord)> = Vec::new();
+        for snake in &gs.board.snakes {
+            let direction = match request.moves.get(&snake.id).and_then(|m| m.get(turn)) {
+                Some(direction) => *direction,
+                None => gs.current_direction(snake),
+            };
+            moves.push((snake.sid, gs.adjacent_coord(&snake.head, &direction)));
+        }
+        gs.advance(&moves)?;
+        gs.commit();
+        gs.turn += 1;
+        states.push(gs.clone());
+    }
+    Ok(states)
+}
+
+/// Output format of `render`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, FromFormField,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderFormat {
+    #[default]
+    Svg,
+    Ascii,
+    /// The notation of `boards::parse`, which ignores territory.
+    Text,
+}
+
+pub fn start(gs: GameState) {
+    info!("START: {:?}", gs);
+}
+
+pub fn end(gs: GameState, post_mortem: &PostMortem) {
+    info!("END: {:?}", gs);
+    if post_mortem.cause == DeathCause::Survived {
+        info!("POST-MORTEM: {:?}", post_mortem);
+    } else {
+        warn!("POST-MORTEM: {:?}", post_mortem);
+    }
+}
@@ -0,0 +1,75 @@
+use super::*;
+use strum::IntoEnumIterator;
+
+/// A board in the notation of `boards::parse`, with its metadata computed.
+pub fn board(text: &str) -> GameState {
+    let mut gs = crate::boards::parse(text).unwrap();
+    gs.init();
+    gs
+}
+/// Computes the metadata of a deserialized game state, as every request does first.
+pub fn prepare(gs: &mut GameState) {
+    gs.init();
+}
+pub fn coord(x: i32, y: i32) -> Coord {
+    Coord { x, y }
+}
+/// Every snake's first viable move, so every run advances the same way.
+pub fn joint_move(gs: &GameState) -> Vec<(SnakeId, Coord)> {
+    gs.board
+        .snakes
+        .iter()
+        .map(|snake| {
+            let next = gs
+                .adjacent_moves(&snake.head)
+                .into_iter()
+                .map(|(coord, _)| coord)
+                .find(|coord| gs.viable(coord))
+                .unwrap_or_else(|| gs.adjacent_coord(&snake.head, &Direction::Up));
+            (snake.sid, next)
+        })
+        .collect()
+}
+/// Moves every snake on the board in the direction picked by its byte, or up when it has none.
+pub fn moves(gs: &GameState, choices: &[u8]) -> Vec<(SnakeId, Coord)> {
+    gs.board
+        .snakes
+        .iter()
+        .enumerate()
+        .map(|(i, snake)| {
+            let choice = choices.get(i).copied().unwrap_or(0) as usize;
+            let direction = Direction::iter().nth(choice % 4).unwrap();
+            (snake.sid, gs.adjacent_coord(&snake.head, &direction))
+        })
+        .collect()
+}
+pub fn advance(gs: &mut GameState, moves: &[(SnakeId, Coord)]) {
+    gs.advance(moves).unwrap();
+}
+pub fn undo(gs: &mut GameState) {
+    gs.undo().unwrap();
+}
+/// Everything advancing and undoing a move may touch, for comparing states.
+pub fn snapshot(gs: &GameState) -> impl PartialEq + std::fmt::Debug {
+    undoable_fields(gs)
+}
+pub fn advance_undo(gs: &mut GameState, moves: &[(SnakeId, Coord)]) {
+    gs.advance(moves).unwrap();
+    gs.undo().unwrap();
+}
+pub fn compute_metadata(gs: &mut GameState) {
+    gs.compute_metadata();
+}
+pub fn compute_territory_info(gs: &GameState) -> TerritoryInfo {
+    gs.compute_territory_info()
+}
+pub fn shortest_distance(gs: &GameState, start: &Coord, end: &Coord) -> Option<u32> {
+    gs.shortest_distance(start, end)
+}
+/// Searches to exactly `depth` with no time limit, returning the nodes searched.
+pub fn search(gs: &mut GameState, depth: u32) -> u32 {
+    let mut search = Search::new(gs);
+    search.timeout = u128::MAX;
+    search.iterative_deepening(gs, depth);
+    search.nodes
+}
@@ -0,0 +1,756 @@
+//! The game as the Battlesnake API describes it, and the grids the simulation keeps beside it.
+
+use super::*;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Source {
+    #[default]
+    #[serde(rename = "")]
+    Empty,
+    Tournament,
+    League,
+    Arena,
+    Challenge,
+    Ladder,
+    Custom,
+}
+
+/// Settings chosen from where a game is played, when its session starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Tournament games play the personality's own, battle-tested strategy and never join experiments.
+    #[default]
+    Tournament,
+    /// Ladder games (leagues, arenas and challenges) may be assigned experiment variants.
+    Ladder,
+    /// Custom games log every search iteration and leave extra time headroom for the logging.
+    Custom,
+}
+
+impl Profile {
+    pub(crate) fn for_source(source: &Source) -> Self {
+        match source {
+            Source::Tournament => Profile::Tournament,
+            Source::League | Source::Arena | Source::Challenge | Source::Ladder => Profile::Ladder,
+            Source::Custom | Source::Empty => Profile::Custom,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    Standard,
+    Solo,
+    Royale,
+    Squad,
+    Constrictor,
+    Wrapped,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMap {
+    Standard,
+    Empty,
+    ArcadeMaze,
+    Royale,
+    SoloMaze,
+    HzInnerWall,
+    HzRings,
+    HzColumns,
+    HzIslandsBridges,
+    HzRiversBridges,
+    HzSpiral,
+    HzScatter,
+    HzGrowBox,
+    HzExpandBox,
+    HzExpandScatter,
+    HzCastleWall,
+}
+
+#[derive(Debug, EnumIter, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// The direction back the way this one came.
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+    /// Change in x and y of a step this way, with up as +y.
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+    /// The direction of a single step by `dx` and `dy`, or `None` unless exactly one of them
+    /// is 1 or -1 and the other 0.
+    pub fn from_delta(dx: i32, dy: i32) -> Option<Direction> {
+        DIRECTIONS
+            .into_iter()
+            .find(|direction| direction.delta() == (dx, dy))
+    }
+}
+
+/// Every direction, in the order neighbors are listed.
+pub(crate) const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RoyaleSettings {
+    /// The number of turns between generating new hazards (shrinking the safe board space).
+    pub(crate) shrink_every_n_turns: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SquadSettings {
+    /// Allow members of the same squad to move over each other without dying.
+    pub(crate) allow_body_collisions: bool,
+    /// All squad members are eliminated when one is eliminated.
+    pub(crate) shared_elimination: bool,
+    /// All squad members share health.
+    pub(crate) shared_health: bool,
+    /// All squad members share length.
+    pub(crate) shared_length: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesetSettings {
+    /// Percentage chance of spawning a new food every round.
+    pub(crate) food_spawn_chance: u32,
+    /// Minimum food to keep on the board every turn.
+    pub(crate) minimum_food: u32,
+    /// Health damage a snake will take when ending its turn in a hazard. This stacks on top of the regular 1 damage a snake takes per turn.
+    pub(crate) hazard_damage_per_turn: i32,
+    /// Royale game mode specific settings.
+    pub(crate) royale: RoyaleSettings,
+    /// Squad game mode specific settings.
+    pub(crate) squad: SquadSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Ruleset {
+    /// Name of the ruleset being used to run this game.
+    pub(crate) name: GameMode,
+    /// The release version of the Rules module used in this game. Example: "version": "v1.2.3"
+    pub(crate) version: String,
+    /// A collection of specific settings being used by the current game that control how the rules are applied.
+    pub(crate) settings: RulesetSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Game {
+    /// A unique identifier for this Game. Example: "totally-unique-game-id"
+    pub(crate) id: String,
+    /// Information about the ruleset being used to run this game. Example: {"name": "standard", "version": "v1.2.3"}
+    pub(crate) ruleset: Ruleset,
+    /// The name of the map used to populate the game board with snakes, food, and hazards. Example: "standard"
+    pub(crate) map: GameMap,
+    /// How much time your snake has to respond to requests for this Game. Example: 500
+    pub(crate) timeout: u32,
+    /// The source of this game.
+    #[serde(default)]
+    pub(crate) source: Source,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct Coord {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+}
+
+impl From<(i32, i32)> for Coord {
+    fn from((x, y): (i32, i32)) -> Self {
+        Coord { x, y }
+    }
+}
+
+impl Coord {
+    pub fn new(x: i32, y: i32) -> Self {
+        Coord { x, y }
+    }
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    /// The square one step away, which may be off the board.
+    pub fn step(&self, direction: Direction) -> Coord {
+        let (dx, dy) = direction.delta();
+        Coord {
+            x: self.x + dx,
+            y: self.y + dy,
+        }
+    }
+    /// The square one step away on a `width` by `height` board whose edges wrap around.
+    pub fn step_wrapped(&self, direction: Direction, width: i32, height: i32) -> Coord {
+        let Coord { x, y } = self.step(direction);
+        Coord {
+            x: x.rem_euclid(width),
+            y: y.rem_euclid(height),
+        }
+    }
+    pub fn manhattan_distance(&self, other: &Coord) -> i32 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+    /// Steps between two squares of a `width` by `height` board whose edges wrap around, going
+    /// across an edge wherever that's shorter.
+    pub fn wrapped_distance(&self, other: &Coord, width: i32, height: i32) -> i32 {
+        let dx = (self.x - other.x).rem_euclid(width);
+        let dy = (self.y - other.y).rem_euclid(height);
+        dx.min(width - dx) + dy.min(height - dy)
+    }
+}
+
+/// A value for every square of a board, stored row by row so that looking up a square is an
+/// index rather than a hash.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Grid<T> {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    cells: Vec<T>,
+}
+
+impl<T: Clone + Default> Grid<T> {
+    pub(crate) fn new(width: i32, height: i32) -> Self {
+        let mut grid = Grid {
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        };
+        grid.reset(width, height);
+        grid
+    }
+    /// Resizes the grid and sets every square back to the default, reusing its allocation.
+    pub(crate) fn reset(&mut self, width: i32, height: i32) {
+        self.width = width.max(0);
+        self.height = height.max(0);
+        self.cells.clear();
+        self.cells
+            .resize((self.width * self.height) as usize, T::default());
+    }
+    fn index(&self, coord: &Coord) -> Option<usize> {
+        in_bounds(coord, self.width, self.height).then(|| (coord.y * self.width + coord.x) as usize)
+    }
+    /// The value of a square, or `None` off the grid.
+    pub(crate) fn get(&self, coord: &Coord) -> Option<&T> {
+        self.index(coord).map(|i| &self.cells[i])
+    }
+    pub(crate) fn get_mut(&mut self, coord: &Coord) -> Option<&mut T> {
+        let i = self.index(coord)?;
+        Some(&mut self.cells[i])
+    }
+    /// Sets the value of a square, ignoring squares off the grid.
+    pub(crate) fn set(&mut self, coord: &Coord, value: T) {
+        if let Some(cell) = self.get_mut(coord) {
+            *cell = value;
+        }
+    }
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Coord, &T)> + '_ {
+        let width = self.width.max(1);
+        self.cells.iter().enumerate().map(move |(i, value)| {
+            let coord = Coord {
+                x: i as i32 % width,
+                y: i as i32 / width,
+            };
+            (coord, value)
+        })
+    }
+}
+
+/// Words a `BitGrid` row can be kept in, so a board only pays for the width it has.
+pub trait BitRow:
+    Copy
+    + Eq
+    + std::ops::BitOr<Output = Self>
+    + std::ops::BitAnd<Output = Self>
+    + std::ops::BitOrAssign
+    + std::ops::BitAndAssign
+    + std::ops::Shl<usize, Output = Self>
+    + std::ops::Shr<usize, Output = Self>
+{
+    const BITS: usize;
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+    fn count_ones(self) -> u32;
+}
+
+macro_rules! bit_row {
+    ($($word:ty),*) => {
+        $(
+            impl BitRow for $word {
+                const BITS: usize = <$word>::BITS as usize;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$word>::MAX;
+                fn count_ones(self) -> u32 {
+                    <$word>::count_ones(self)
+                }
+            }
+        )*
+    };
+}
+
+bit_row!(u16, u32, u64);
+
+/// One bit per square with a word to a row, so flood fills work on whole rows at a time. Fits
+/// boards up to `N` squares high and as wide as the word `W`, which is picked per board size:
+/// 16 bits up to 16x16, 32 up to 32x32 and 64 beyond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitGrid<W, const N: usize> {
+    width: i32,
+    height: i32,
+    rows: [W; N],
+}
+
+/// Up to 16x16, which takes in every standard board size up to 11x11.
+pub(crate) type SmallBitGrid = BitGrid<u16, 16>;
+/// Up to 32x32, for 19x19 and 25x25 boards.
+pub(crate) type LargeBitGrid = BitGrid<u32, 32>;
+/// Up to 64x64, for anything bigger.
+pub(crate) type HugeBitGrid = BitGrid<u64, 64>;
+
+impl<W: BitRow, const N: usize> BitGrid<W, N> {
+    /// An empty grid, or `None` for a board too big to fit.
+    pub(crate) fn new(width: i32, height: i32) -> Option<Self> {
+        if !(0..=W::BITS as i32).contains(&width) || !(0..=N as i32).contains(&height) {
+            return None;
+        }
+        Some(BitGrid {
+            width,
+            height,
+            rows: [W::ZERO; N],
+        })
+    }
+    pub(crate) fn insert(&mut self, coord: &Coord) {
+        if in_bounds(coord, self.width, self.height) {
+            self.rows[coord.y as usize] |= W::ONE << coord.x as usize;
+        }
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.rows.iter().map(|row| row.count_ones() as usize).sum()
+    }
+    /// The squares of the grid and every square next to one, wrapping around the edges when
+    /// `wrapped`.
+    fn dilate(&self, wrapped: bool) -> Self {
+        let (width, height) = (self.width as usize, self.height as usize);
+        if width == 0 || height == 0 {
+            return *self;
+        }
+        let mask = W::MAX >> (W::BITS - width);
+        let mut dilated = *self;
+        for y in 0..height {
+            let row = self.rows[y];
+            let mut spread = row | row << 1 | row >> 1;
+            if wrapped {
+                spread |= ((row >> (width - 1)) & W::ONE) | ((row & W::ONE) << (width - 1));
+            }
+            dilated.rows[y] |= spread & mask;
+            if y > 0 {
+                dilated.rows[y - 1] |= row;
+            } else if wrapped {
+                dilated.rows[height - 1] |= row;
+            }
+            if y + 1 < height {
+                dilated.rows[y + 1] |= row;
+            } else if wrapped {
+                dilated.rows[0] |= row;
+            }
+        }
+        dilated
+    }
+    /// Grows the grid over the squares of `free` until nothing more can be reached.
+    pub(crate) fn flood_fill(mut self, free: &Self, wrapped: bool) -> Self {
+        loop {
+            let mut grown = self.dilate(wrapped);
+            for (row, free_row) in grown.rows.iter_mut().zip(free.rows.iter()) {
+                *row &= *free_row;
+            }
+            for (row, reached_row) in grown.rows.iter_mut().zip(self.rows.iter()) {
+                *row |= *reached_row;
+            }
+            if grown == self {
+                return self;
+            }
+            self = grown;
+        }
+    }
+}
+
+/// The neighbors of every square in `DIRECTIONS` order, with wrapping resolved, so generating
+/// moves is a lookup. Neighbors off a board that doesn't wrap are kept, as moves into walls are
+/// still moves. Only depends on the board size and whether it wraps, so copies of a state share
+/// it.
+#[derive(Debug, Default, PartialEq)]
+pub struct Neighbors {
+    pub(crate) wrapped: bool,
+    pub(crate) coords: Grid<[Coord; 4]>,
+}
+
+impl Grid<Option<u32>> {
+    pub(crate) fn distance(&self, coord: &Coord) -> Option<u32> {
+        self.get(coord).copied().flatten()
+    }
+}
+
+impl Grid<bool> {
+    pub(crate) fn contains(&self, coord: &Coord) -> bool {
+        self.get(coord).copied().unwrap_or(false)
+    }
+}
+
+impl Grid<u8> {
+    pub(crate) fn add(&mut self, coord: &Coord) {
+        if let Some(count) = self.get_mut(coord) {
+            *count += 1;
+        }
+    }
+    pub(crate) fn remove(&mut self, coord: &Coord) {
+        if let Some(count) = self.get_mut(coord) {
+            *count = count.saturating_sub(1);
+        }
+    }
+    pub(crate) fn occupied(&self, coord: &Coord) -> bool {
+        self.get(coord).is_some_and(|&count| count > 0)
+    }
+}
+
+// Body edits that keep `Board::segments` in step, where every part of a body but its last counts.
+
+pub(crate) fn push_head(body: &mut VecDeque<Coord>, segments: &mut Grid<u8>, head: Coord) {
+    body.push_front(head);
+    if body.len() > 1 {
+        segments.add(&head);
+    }
+}
+
+pub(crate) fn pop_head(body: &mut VecDeque<Coord>, segments: &mut Grid<u8>) -> Option<Coord> {
+    let head = body.pop_front()?;
+    if !body.is_empty() {
+        segments.remove(&head);
+    }
+    Some(head)
+}
+
+pub(crate) fn push_tail(body: &mut VecDeque<Coord>, segments: &mut Grid<u8>, tail: Coord) {
+    if let Some(last) = body.back() {
+        segments.add(last);
+    }
+    body.push_back(tail);
+}
+
+pub(crate) fn pop_tail(body: &mut VecDeque<Coord>, segments: &mut Grid<u8>) -> Option<Coord> {
+    let tail = body.pop_back()?;
+    if let Some(last) = body.back() {
+        segments.remove(last);
+    }
+    Some(tail)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PriorityCoord {
+    pub(crate) coord: Coord,
+    pub(crate) priority: u32,
+}
+
+impl Ord for PriorityCoord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl PartialOrd for PriorityCoord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Who a snake plays for, from its squad and ours. Outside squad games every other snake is an
+/// enemy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Side {
+    Us,
+    /// On our squad, sharing its wins.
+    Teammate,
+    Enemy,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Board {
+    /// The number of rows in the y-axis of the game board. Example: 11
+    pub(crate) height: i32,
+    /// The number of columns in the x-axis of the game board. Example: 11
+    pub(crate) width: i32,
+    /// Array of coordinates representing food locations on the game board. Example: [{"x": 5, "y": 5}, ..., {"x": 2, "y": 6}]
+    pub(crate) food: Vec<Coord>,
+    /// Array of coordinates representing hazardous locations on the game board. These will only appear in some game modes. Example: [{"x": 0, "y": 0}, ..., {"x": 0, "y": 1}]
+    pub(crate) hazards: Vec<Coord>,
+    /// Array of Battlesnake Objects representing all Battlesnakes remaining on the game board (including yourself if you haven't been eliminated). Example: [{"id": "snake-one", ...}, ...]
+    pub(crate) snakes: Vec<Battlesnake>,
+    /// Whether each square has food, kept up to date with `food` by `advance` and `undo`.
+    #[serde(skip)]
+    pub(crate) food_squares: Grid<bool>,
+    /// Number of body parts of snakes still on the board on each square, leaving out tails.
+    /// Kept up to date by `advance` and `undo` rather than recounted.
+    #[serde(skip)]
+    pub(crate) segments: Grid<u8>,
+    /// Damage of the hazards on each square, if any.
+    #[serde(skip)]
+    pub(crate) hazard_damage: Grid<Option<i32>>,
+    /// Squares adjacent to enemy snake heads that are smaller in size.
+    #[serde(skip)]
+    pub(crate) stomps: Vec<Coord>,
+    /// Squares adjacent to enemy snake heads that are equal or bigger in size.
+    #[serde(skip)]
+    pub(crate) avoids: Vec<Coord>,
+    /// Neighbors of each square, built with the rest of the metadata.
+    #[serde(skip)]
+    pub(crate) neighbors: Arc<Neighbors>,
+    /// Which side each snake, by index, plays for.
+    #[serde(skip)]
+    pub(crate) sides: Vec<Side>,
+}
+
+impl Board {
+    pub(crate) fn get_snake(&self, id: &str) -> Option<&Battlesnake> {
+        self.alive().find(|snake| snake.id == id)
+    }
+    /// The snake with the given `SnakeId`, unless it's been eliminated.
+    pub(crate) fn snake(&self, sid: SnakeId) -> Option<&Battlesnake> {
+        self.snakes
+            .get(sid.index())
+            .filter(|snake| snake.sid == sid && !snake.eliminated)
+    }
+    /// Snakes that haven't been eliminated. Eliminated snakes keep their place in the snakes
+    /// array until the turn is committed, so indexes stay stable while searching.
+    pub(crate) fn alive(&self) -> impl Iterator<Item = &Battlesnake> + '_ {
+        self.snakes.iter().filter(|snake| !snake.eliminated)
+    }
+    /// Which side the snake at `index` plays for. Snakes the metadata hasn't caught up with are
+    /// taken for enemies.
+    pub(crate) fn side(&self, index: usize) -> Side {
+        self.sides.get(index).copied().unwrap_or(Side::Enemy)
+    }
+    /// Whether a snake is on our squad, and so not one to kill.
+    pub(crate) fn teammate(&self, snake: &Battlesnake) -> bool {
+        self.side(snake.sid.index()) == Side::Teammate
+    }
+    #[cfg(test)]
+    pub(crate) fn enemy(&self, snake: &Battlesnake) -> bool {
+        self.side(snake.sid.index()) == Side::Enemy
+    }
+    /// Whether the snakes at indexes `a` and `b` play for each other: they're the same snake, or
+    /// both on our squad.
+    pub(crate) fn allied(&self, a: usize, b: usize) -> bool {
+        a == b || (self.side(a) != Side::Enemy && self.side(b) != Side::Enemy)
+    }
+    pub(crate) fn center(&self) -> Coord {
+        Coord {
+            x: self.width / 2,
+            y: self.height / 2,
+        }
+    }
+    /// Puts food on a square that has none.
+    pub(crate) fn add_food(&mut self, coord: Coord) {
+        if !self.food_squares.contains(&coord) {
+            self.food.push(coord);
+            self.food_squares.set(&coord, true);
+        }
+    }
+    /// Takes the food off a square, if it has any.
+    pub(crate) fn remove_food(&mut self, coord: &Coord) {
+        if self.food_squares.contains(coord) {
+            self.food.retain(|food| food != coord);
+            self.food_squares.set(coord, false);
+        }
+    }
+    /// Damage of the hazards on a square, if it has any.
+    pub(crate) fn hazard_at(&self, coord: &Coord) -> Option<i32> {
+        self.hazard_damage.get(coord).copied().flatten()
+    }
+    pub(crate) fn size(&self) -> BoardSize {
+        match self.width.max(self.height) {
+            side if side <= 7 => BoardSize::Small,
+            side if side <= STANDARD_SIDE => BoardSize::Medium,
+            side if side <= 19 => BoardSize::Large,
+            _ => BoardSize::XLarge,
+        }
+    }
+    /// `score` for a distance on this board: as it is up to 11x11, and shrunk on bigger boards
+    /// in step with how far there is to go across them, so distances don't swamp other terms.
+    pub(crate) fn scale_distance(&self, score: i32) -> i32 {
+        let span = (self.width + self.height).max(STANDARD_SIDE * 2);
+        score * STANDARD_SIDE * 2 / span
+    }
+    /// `score` for a count of squares on this board: as it is up to 11x11, and shrunk on bigger
+    /// boards in step with their area, so a share of the board is worth the same on any of them.
+    pub(crate) fn scale_area(&self, score: i32) -> i32 {
+        let area = (self.width * self.height).max(STANDARD_SIDE * STANDARD_SIDE);
+        score * STANDARD_SIDE * STANDARD_SIDE / area
+    }
+}
+
+/// Side of the standard board, which evaluation weights are tuned for.
+pub(crate) const STANDARD_SIDE: i32 = 11;
+
+/// The board sizes games are played on, by their longer side, which decide the settings that
+/// depend on how much board there is to search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BoardSize {
+    /// Up to 7x7.
+    Small,
+    /// Up to 11x11, the standard board.
+    Medium,
+    /// Up to 19x19.
+    Large,
+    /// 25x25, or anything bigger.
+    XLarge,
+}
+
+impl BoardSize {
+    /// Deepest leaf, in turns, that gets the full evaluation by default. Territory is computed
+    /// over every square, so big boards leave it to the first few turns to search as deep.
+    pub(crate) fn full_evaluation_depth(self) -> Option<i32> {
+        match self {
+            BoardSize::Small | BoardSize::Medium => None,
+            BoardSize::Large => Some(6),
+            BoardSize::XLarge => Some(4),
+        }
+    }
+}
+
+/// Who gets to a square first, as the territory fill reaches it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Arrival {
+    /// Index on the board of the snake that gets there first, or our snake when it's among those
+    /// tied for first.
+    pub(crate) snake: usize,
+    /// Moves it takes them.
+    pub(crate) distance: u32,
+    /// Whether a snake on another side gets there just as soon.
+    pub(crate) tied: bool,
+}
+
+#[derive(Debug)]
+pub struct TerritoryInfo {
+    /// Index on the board of the snake controlling each square, and its distance from the head.
+    pub(crate) owners: Grid<Option<(usize, u32)>>,
+    /// Who gets to each square first, contested squares included. The fill doesn't go through
+    /// contested squares, so squares behind them are reached around them.
+    pub(crate) arrivals: Grid<Option<Arrival>>,
+    /// Number of squares each snake controls, by index on the board.
+    pub(crate) controlled: Vec<usize>,
+    /// Number of squares our snake can reach.
+    pub(crate) available: usize,
+}
+
+impl TerritoryInfo {
+    /// Squares controlled by each snake, keyed by id.
+    #[cfg_attr(not(any(test, feature = "render")), allow(dead_code))]
+    pub(crate) fn controlled_squares(&self, gs: &GameState) -> HashMap<String, HashSet<Coord>> {
+        let mut squares: HashMap<String, HashSet<Coord>> = gs
+            .board
+            .snakes
+            .iter()
+            .filter(|snake| !snake.eliminated)
+            .map(|snake| (snake.id.clone(), HashSet::new()))
+            .collect();
+        for (coord, owner) in self.owners.iter() {
+            let snake = owner.and_then(|(index, _)| gs.board.snakes.get(index));
+            if let Some(snake) = snake {
+                squares.entry(snake.id.clone()).or_default().insert(coord);
+            }
+        }
+        squares
+    }
+}
+
+/// Number a snake is known by inside the simulation, assigned in board order when a game state is
+/// initialized. Ids are only used at the API boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SnakeId(pub(crate) u8);
+
+impl SnakeId {
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The parts of a Battlesnake that never change while it's simulated.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct SnakeDetails {
+    /// Name given to this Battlesnake by its author. Example: "Sneky McSnek Face"
+    pub(crate) name: String,
+    /// The previous response time of this Battlesnake, in milliseconds. If the Battlesnake timed out and failed to respond, the game timeout will be returned (game.timeout) Example: "500"
+    pub(crate) latency: String,
+    /// Message shouted by this Battlesnake on the previous turn. Example: "why are we shouting??"
+    pub(crate) shout: String,
+    /// The squad that the Battlesnake belongs to. Used to identify squad members in Squad Mode games. Example: "1"
+    pub(crate) squad: String,
+    /// The collection of customizations applied to this Battlesnake that represent how it is viewed.
+    pub(crate) customizations: Customizations,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Battlesnake {
+    /// Unique identifier for this Battlesnake in the context of the current Game. Example: "totally-unique-snake-id"
+    pub(crate) id: String,
+    /// Health value of this Battlesnake, between 0 and 100 inclusively. Example: 54
+    pub(crate) health: i32,
+    /// Array of coordinates representing this Battlesnake's location on the game board. This array is ordered from head to tail. Example: [{"x": 0, "y": 0}, ..., {"x": 2, "y": 0}]
+    pub(crate) body: VecDeque<Coord>,
+    /// Coordinates for this Battlesnake's head. Equivalent to the first element of the body array. Example: {"x": 0, "y": 0}
+    pub(crate) head: Coord,
+    /// Length of this Battlesnake from head to tail. Equivalent to the length of the body array. Example: 3
+    pub(crate) length: u32,
+    /// Shared between every copy of the snake, so cloning a game state for simulation only
+    /// copies what moves.
+    #[serde(flatten)]
+    pub(crate) details: Arc<SnakeDetails>,
+    #[serde(skip)]
+    pub(crate) eliminated: bool,
+    #[serde(skip)]
+    pub(crate) sid: SnakeId,
+}
+
+impl Battlesnake {
+    /// Whether the snake has growth pending from food it just ate, which stacks its tail so the
+    /// tail square stays taken a turn longer. Read from the body, so it's as `advance` and `undo`
+    /// leave it.
+    pub(crate) fn growing(&self) -> bool {
+        let len = self.body.len();
+        len >= 2 && self.body[len - 1] == self.body[len - 2]
+    }
+}
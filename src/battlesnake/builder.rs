@@ -0,0 +1,219 @@
+//! Game states put together in code rather than deserialized from a move request.
+
+use super::*;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Builds a `GameState` square by square, for tests, benchmarks, self-play and library users.
+///
+/// Anything not set is as on the public servers: a standard game on a standard map, with the
+/// default food and hazard settings, every snake at full health, and the first snake added as us.
+#[derive(Debug, Clone)]
+pub struct GameStateBuilder {
+    width: i32,
+    height: i32,
+    game_id: String,
+    mode: GameMode,
+    map: GameMap,
+    timeout: u32,
+    turn: u32,
+    settings: RulesetSettings,
+    food: Vec<Coord>,
+    hazards: Vec<Coord>,
+    snakes: Vec<Battlesnake>,
+    you: Option<String>,
+}
+
+impl GameStateBuilder {
+    pub fn new(width: i32, height: i32) -> Self {
+        GameStateBuilder {
+            width,
+            height,
+            game_id: "local".to_owned(),
+            mode: GameMode::Standard,
+            map: GameMap::Standard,
+            timeout: 500,
+            turn: 0,
+            settings: RulesetSettings {
+                food_spawn_chance: 15,
+                minimum_food: 1,
+                hazard_damage_per_turn: 14,
+                royale: RoyaleSettings {
+                    shrink_every_n_turns: 25,
+                },
+                squad: SquadSettings {
+                    allow_body_collisions: false,
+                    shared_elimination: false,
+                    shared_health: false,
+                    shared_length: false,
+                },
+            },
+            food: Vec::new(),
+            hazards: Vec::new(),
+            snakes: Vec::new(),
+            you: None,
+        }
+    }
+    pub fn game_id(mut self, id: &str) -> Self {
+        self.game_id = id.to_owned();
+        self
+    }
+    pub fn mode(mut self, mode: GameMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    pub fn map(mut self, map: GameMap) -> Self {
+        self.map = map;
+        self
+    }
+    /// Time snakes have to respond to each request, in milliseconds.
+    pub fn timeout(mut self, timeout: u32) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    pub fn turn(mut self, turn: u32) -> Self {
+        self.turn = turn;
+        self
+    }
+    /// Percentage chance of food spawning each turn.
+    pub fn food_spawn_chance(mut self, chance: u32) -> Self {
+        self.settings.food_spawn_chance = chance;
+        self
+    }
+    pub fn minimum_food(mut self, minimum: u32) -> Self {
+        self.settings.minimum_food = minimum;
+        self
+    }
+    /// Damage taken each turn in a hazard, on top of the usual 1.
+    pub fn hazard_damage(mut self, damage: i32) -> Self {
+        self.settings.hazard_damage_per_turn = damage;
+        self
+    }
+    /// Turns between the hazards of a royale game growing.
+    pub fn shrink_every_n_turns(mut self, turns: u32) -> Self {
+        self.settings.royale.shrink_every_n_turns = turns;
+        self
+    }
+    /// Squad rules, all on or all off.
+    pub fn squad_rules(mut self, shared: bool) -> Self {
+        self.settings.squad = SquadSettings {
+            allow_body_collisions: shared,
+            shared_elimination: shared,
+            shared_health: shared,
+            shared_length: shared,
+        };
+        self
+    }
+    pub fn food(mut self, coords: impl IntoIterator<Item = impl Into<Coord>>) -> Self {
+        self.food.extend(coords.into_iter().map(Into::into));
+        self
+    }
+    /// Hazard squares, listed twice to stack their damage.
+    pub fn hazards(mut self, coords: impl IntoIterator<Item = impl Into<Coord>>) -> Self {
+        self.hazards.extend(coords.into_iter().map(Into::into));
+        self
+    }
+    /// Adds a snake at full health with its body given head first, named after its id.
+    pub fn snake(mut self, id: &str, body: impl IntoIterator<Item = impl Into<Coord>>) -> Self {
+        let body: VecDeque<Coord> = body.into_iter().map(Into::into).collect();
+        self.snakes.push(Battlesnake {
+            id: id.to_owned(),
+            health: 100,
+            head: body.front().copied().unwrap_or_default(),
+            length: body.len() as u32,
+            body,
+            details: Arc::new(SnakeDetails {
+                name: id.to_owned(),
+                latency: "0".to_owned(),
+                shout: String::new(),
+                squad: String::new(),
+                customizations: Customizations {
+                    color: "#888888".to_owned(),
+                    head: "default".to_owned(),
+                    tail: "default".to_owned(),
+                },
+            }),
+            eliminated: false,
+            sid: SnakeId::default(),
+        });
+        self
+    }
+    /// Health of the snake added last.
+    pub fn health(mut self, health: i32) -> Self {
+        if let Some(snake) = self.snakes.last_mut() {
+            snake.health = health;
+        }
+        self
+    }
+    /// Name of the snake added last.
+    pub fn name(mut self, name: &str) -> Self {
+        if let Some(snake) = self.snakes.last_mut() {
+            Arc::make_mut(&mut snake.details).name = name.to_owned();
+        }
+        self
+    }
+    /// Squad of the snake added last, in squad games.
+    pub fn squad(mut self, squad: &str) -> Self {
+        if let Some(snake) = self.snakes.last_mut() {
+            Arc::make_mut(&mut snake.details).squad = squad.to_owned();
+        }
+        self
+    }
+    /// Plays as the snake with this id rather than the first one added.
+    pub fn you(mut self, id: &str) -> Self {
+        self.you = Some(id.to_owned());
+        self
+    }
+    /// The game state, checked as a move request would be and initialized for simulation.
+    pub fn build(self) -> Result<GameState, EngineError> {
+        if let Some(snake) = self.snakes.iter().find(|snake| snake.body.is_empty()) {
+            return Err(EngineError::InvalidState(format!(
+                "snake {:?} has no body",
+                snake.id
+            )));
+        }
+        let you = match &self.you {
+            Some(id) => self.snakes.iter().find(|snake| &snake.id == id),
+            None => self.snakes.first(),
+        }
+        .cloned()
+        .ok_or_else(|| EngineError::UnknownSnake(self.you.unwrap_or_default()))?;
+        let mut gs = GameState {
+            game: Arc::new(Game {
+                id: self.game_id,
+                ruleset: Ruleset {
+                    name: self.mode,
+                    version: "local".to_owned(),
+                    settings: self.settings,
+                },
+                map: self.map,
+                timeout: self.timeout,
+                source: Source::Custom,
+            }),
+            turn: self.turn,
+            board: Board {
+                height: self.height,
+                width: self.width,
+                food: self.food,
+                food_squares: Grid::default(),
+                hazards: self.hazards,
+                snakes: self.snakes,
+                segments: Grid::default(),
+                hazard_damage: Grid::default(),
+                stomps: Vec::new(),
+                avoids: Vec::new(),
+                sides: Vec::new(),
+                neighbors: Arc::default(),
+            },
+            you,
+            undo: UndoInfo::default(),
+            undo_index: 0,
+            shrink: None,
+            food_hotspot: None,
+            safe_zone: None,
+        };
+        gs.validate()?;
+        gs.init();
+        Ok(gs)
+    }
+}
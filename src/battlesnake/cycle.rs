@@ -0,0 +1,117 @@
+//! Surviving solo games by following a Hamiltonian cycle, a loop through every square of the
+//! board that a snake can follow forever without running into itself.
+
+use super::*;
+use std::time::Instant;
+
+/// Plays solo games for survival by following a cycle through every square, taking shortcuts to
+/// food while the snake is short enough for them to be safe.
+///
+/// A shortcut skips ahead along the cycle, so it's only taken when it lands short of the part of
+/// our body ahead of us, with a square to spare, and doesn't skip past the next food. Boards
+/// without a cycle, such as those with both sides odd or with hazards, are played as `MazePolicy`
+/// plays them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CyclePolicy;
+
+impl CyclePolicy {
+    /// Whether a game is one this policy plays rather than the personality's own: a solo game that
+    /// `MazePolicy` doesn't play, where surviving is all there is to it.
+    pub fn suits(gs: &GameState) -> bool {
+        gs.game.ruleset.name == GameMode::Solo && !MazePolicy::suits(gs)
+    }
+}
+
+impl MovePolicy for CyclePolicy {
+    fn choose(&mut self, gs: &GameState, deadline: Instant) -> (Direction, Report) {
+        let start = Instant::now();
+        let cycle = match hamiltonian_cycle(gs.board.width, gs.board.height) {
+            Some(cycle) if gs.board.hazards.is_empty() => cycle,
+            _ => return MazePolicy.choose(gs, deadline),
+        };
+        let direction = match cycle_move(gs, &cycle) {
+            Some(direction) => direction,
+            None => return MazePolicy.choose(gs, deadline),
+        };
+        let mut report = Report::unsearched();
+        report.time = start.elapsed().as_millis();
+        (direction, report)
+    }
+}
+
+/// A cycle through every square of a `width` by `height` board, or `None` if it has none because
+/// both sides are odd or one is shorter than 2.
+///
+/// With an even height, the cycle runs along the bottom row, snakes back and forth across the other
+/// rows leaving out the first column, and comes back down the first column. An odd height with an
+/// even width is the same turned on its side.
+pub fn hamiltonian_cycle(width: i32, height: i32) -> Option<Vec<Coord>> {
+    if width < 2 || height < 2 {
+        return None;
+    }
+    if height % 2 == 1 {
+        if width % 2 == 1 {
+            return None;
+        }
+        let cycle = hamiltonian_cycle(height, width)?;
+        return Some(
+            cycle
+                .into_iter()
+                .map(|c| Coord { x: c.y, y: c.x })
+                .collect(),
+        );
+    }
+    let mut cycle = Vec::with_capacity((width * height) as usize);
+    cycle.extend((0..width).map(|x| Coord { x, y: 0 }));
+    for y in 1..height {
+        if y % 2 == 1 {
+            cycle.extend((1..width).rev().map(|x| Coord { x, y }));
+        } else {
+            cycle.extend((1..width).map(|x| Coord { x, y }));
+        }
+    }
+    cycle.extend((1..height).rev().map(|y| Coord { x: 0, y }));
+    Some(cycle)
+}
+
+/// The next move along `cycle`, or the furthest safe shortcut ahead of it, or `None` if the next
+/// square of the cycle isn't free.
+fn cycle_move(gs: &GameState, cycle: &[Coord]) -> Option<Direction> {
+    let you = gs.you();
+    let squares = cycle.len();
+    let mut order: Grid<usize> = Grid::new(gs.board.width, gs.board.height);
+    for (i, coord) in cycle.iter().enumerate() {
+        order.set(coord, i);
+    }
+    let head_order = order.get(&you.head).copied()?;
+    // Steps along the cycle from our head to a square
+    let ahead = |coord: &Coord| {
+        order
+            .get(coord)
+            .map(|&i| (i + squares - head_order) % squares)
+    };
+    // Our body ahead of us blocks the cycle, and the next food is as far as we want to skip
+    let blocked = you
+        .body
+        .iter()
+        .skip(1)
+        .filter_map(ahead)
+        .filter(|&steps| steps > 0)
+        .min();
+    let food = gs.board.food.iter().filter_map(ahead).min();
+    // Shortcuts are only safe while there's plenty of room left on the cycle for the body
+    let shortcuts = (you.length as usize) * 2 < squares;
+    gs.adjacent_moves(&you.head)
+        .into_iter()
+        .filter(|(coord, _)| gs.viable(coord))
+        .filter_map(|(coord, direction)| Some((ahead(&coord)?, direction)))
+        .filter(|&(steps, _)| {
+            steps == 1
+                || (shortcuts
+                    && steps > 1
+                    && !matches!(blocked, Some(blocked) if steps + 1 >= blocked)
+                    && !matches!(food, Some(food) if steps > food))
+        })
+        .max_by_key(|&(steps, _)| steps)
+        .map(|(_, direction)| direction)
+}
@@ -0,0 +1,19 @@
+//! What can go wrong in the engine on input it can't play, rather than panicking the search.
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EngineError {
+    /// The game state isn't one the engine can play, with why.
+    #[error("{0}")]
+    InvalidState(String),
+    /// Moves were given for a snake id that isn't on the board.
+    #[error("no snake {0:?} on the board")]
+    UnknownSnake(String),
+    /// A turn was advanced before `GameState::init`, or deeper than the undo history holds.
+    #[error("can't advance {} turns without committing", .0 + 1)]
+    TooDeep(usize),
+    /// A turn was undone with none advanced.
+    #[error("no turn to undo")]
+    NothingToUndo,
+}
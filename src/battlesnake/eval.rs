@@ -0,0 +1,319 @@
+//! Scores of the positions at the leaves of the search.
+
+use super::*;
+
+pub(crate) fn basic_evaluate(gs: &GameState, depth: i32) -> Result<Score, EngineError> {
+    let mut score = Score::new();
+    let you = gs.you();
+    // Elimination is bad
+    if you.eliminated {
+        score.min = true;
+        return Ok(score);
+    }
+
+    // Other snakes being eliminated is good
+    if gs.rules().last_snake_wins() && gs.board.alive().count() == 1 {
+        score.max = true;
+        return Ok(score);
+    }
+
+    // The closer we are to where food spawns the better, which is the center until we know.
+    // Distances are scaled down on boards bigger than 11x11, as they are further across.
+    let anchor = gs.food_hotspot.unwrap_or_else(|| gs.board.center());
+    score.center_dist = gs
+        .board
+        .scale_distance(-you.head.manhattan_distance(&anchor) * 100);
+
+    // Walls and corners leave fewer escape squares
+    score.edge_safety = gs.edge_safety();
+
+    // Being outside where the hazards will leave safe is worse the sooner they get there
+    score.safe_zone = gs.safe_zone_penalty();
+
+    // Penalize moving to where a bigger or equal snakes head might be
+    // Incentivize moving to where a smaller snakes head might be
+    if gs.board.avoids.contains(&you.head) {
+        score.snake_avoids = -5000;
+    } else if gs.board.stomps.contains(&you.head) {
+        score.snake_stomps = 5000;
+    }
+
+    // Having a path to our own tail is good
+    let tail = you
+        .body
+        .back()
+        .ok_or_else(|| EngineError::InvalidState(format!("snake {:?} has no body", you.id)))?;
+    score.tail_dist = gs
+        .board
+        .scale_distance(-you.head.manhattan_distance(tail) * 100);
+
+    // Prioritize moving towards food
+    let food_option = gs
+        .board
+        .food
+        .iter()
+        .map(|food| you.head.manhattan_distance(food))
+        .min();
+
+    if let Some(food_distance) = food_option {
+        score.food_dist = gs.board.scale_distance(-food_distance * 100);
+    } else if you.health < 20 {
+        score.food_dist = -100000;
+    }
+
+    // Growing bigger is good
+    score.length = you.length as i32 * 10000;
+
+    // More health is better
+    score.survival = depth * 10000 + you.health * 100;
+
+    Ok(score)
+}
+
+/// `basic_evaluate` at the leaves of a search, where a position it can't score counts as lost.
+pub(crate) fn basic_leaf_evaluate(gs: &GameState, depth: i32) -> Score {
+    basic_evaluate(gs, depth).unwrap_or_else(|e| {
+        warn!("unable to evaluate the position: {}", e);
+        let mut score = Score::new();
+        score.min = true;
+        score
+    })
+}
+
+pub(crate) fn territory_evaluate(gs: &GameState, depth: i32) -> Score {
+    let mut score = Score::new();
+    let you = gs.you();
+    // Elimination is bad
+    if you.eliminated {
+        score.min = true;
+        return score;
+    }
+
+    // Other snakes being eliminated is good
+    if gs.rules().last_snake_wins() && gs.board.alive().count() == 1 {
+        score.max = true;
+    }
+
+    // One search from the head answers the path queries below
+    let distances = gs.distances_from(&you.head);
+
+    // The closer we are to where food spawns the better, which is the center until we know.
+    // While we aren't hungry, the richest cluster of food we can reach is better still.
+    let region = if you.health >= COMFORTABLE_HEALTH {
+        gs.richest_food_region(&distances)
+    } else {
+        None
+    };
+    let anchor = region
+        .or(gs.food_hotspot)
+        .unwrap_or_else(|| gs.board.center());
+    score.center_dist = gs
+        .board
+        .scale_distance(-you.head.manhattan_distance(&anchor) * 100);
+
+    // Walls and corners leave fewer escape squares
+    score.edge_safety = gs.edge_safety();
+
+    // Being outside where the hazards will leave safe is worse the sooner they get there
+    score.safe_zone = gs.safe_zone_penalty();
+
+    // Penalize moving to where a bigger or equal snakes head might be
+    // Incentivize moving to where a smaller snakes head might be
+    if gs.board.avoids.contains(&you.head) {
+        score.snake_avoids = -5000;
+    } else if gs.board.stomps.contains(&you.head) {
+        score.snake_stomps = 5000;
+    }
+
+    // Maximize our "controlled" squares
+    let territory_info = gs.compute_territory_info();
+    let controlled = territory_info
+        .controlled
+        .get(you.sid.index())
+        .copied()
+        .unwrap_or(0);
+    score.board_control = gs.board.scale_area(controlled as i32 * 10);
+
+    // Going into a dead end is bad, and a stacked tail needs a square more to get out of one.
+    // Small pockets are solved instead, as the bodies around them may leave in time or not.
+    let pocket = territory_info.available <= POCKET_SQUARES + 1;
+    let dead_end = match pocket.then(|| gs.pocket_survivable()).flatten() {
+        Some(survivable) => !survivable,
+        None => territory_info.available < you.length as usize + 1 + you.growing() as usize,
+    };
+    if dead_end {
+        score.board_control = -10000;
+    }
+
+    // Paths to our tail and to food are as long as the health they cost, so going through
+    // hazards counts against them and a path we'd run out of health on doesn't count at all
+    let hazardous = !gs.board.hazards.is_empty() || gs.shrink.is_some();
+    let spent = hazardous.then(|| gs.health_spent_from(&you.head));
+    let spent = spent.as_ref().unwrap_or(&distances);
+
+    // Having a path to our own tail is good
+    if let Some(tail_distance) = gs.tail_distance(you, spent) {
+        score.tail_dist = gs.board.scale_distance(-(tail_distance as i32) * 100);
+    } else {
+        score.tail_dist = -1000;
+    }
+
+    // Standing between hungry, shorter snakes and their food is good
+    score.food_denial = gs.food_denials(&territory_info) * 2000;
+
+    // Prioritize moving towards food
+    if let Some(food_distance) = gs.closest_food_in(spent) {
+        score.food_dist = ((1.0 / food_distance as f32 * 10000.0) as i32).clamp(0, 9999);
+    } else if you.health < 20 {
+        // No food in reach is only starving if none will spawn where we are in time
+        let starving = 1.0 - gs.expected_food(controlled);
+        score.food_dist = (-5000.0 * starving) as i32;
+    }
+
+    // Food that may yet spawn on our squares before we'd starve is worth something too
+    score.food_expectation = gs.food_expectation(controlled);
+
+    // Growing bigger is good
+    score.length = you.length as i32 * 10000;
+
+    // Where hazards are everywhere, outlasting the others' health is how games are won
+    score.attrition = gs.attrition();
+
+    // The longer we survive, the better
+    score.survival = depth * 10000 + you.health * 100;
+
+    score
+}
+
+/// Board control counts for this much more heads-up than with more snakes on the board.
+const DUEL_CONTROL_WEIGHT: i32 = 3;
+
+/// `territory_evaluate` for a game down to us and one opponent, where there's nobody else to take
+/// advantage of a fight: territory counts for more, and so does cutting the opponent off.
+pub(crate) fn duel_evaluate(gs: &GameState, depth: i32) -> Score {
+    let mut score = territory_evaluate(gs, depth);
+    if score.min || score.max {
+        return score;
+    }
+
+    // Squares are split between the two of us, so every one we control is one they don't
+    if score.board_control > 0 {
+        score.board_control *= DUEL_CONTROL_WEIGHT;
+    }
+
+    // Leaving the opponent less room than their body needs all but wins the game
+    let you = gs.you();
+    if let Some(opponent) = gs.board.alive().find(|snake| snake.sid != you.sid) {
+        let room = gs.reachable_squares(&opponent.head);
+        if room < opponent.length as usize + 1 {
+            score.cutoff = 20000;
+        } else if room < opponent.length as usize * 2 {
+            score.cutoff = 5000;
+        }
+        score.partition = chokepoint_threats(gs, you, opponent);
+    }
+
+    score
+}
+
+/// Score for a chokepoint we can take next that walls the opponent into less room than its
+/// body, or the penalty for one it can take that walls us in.
+const CHOKEPOINT_THREAT: i32 = 10000;
+
+/// Weighs up the chokepoints next to either head: one the opponent can take that leaves us less
+/// room than our body needs is a trap, and one we can take that does the same to it a cutoff.
+fn chokepoint_threats(gs: &GameState, you: &Battlesnake, opponent: &Battlesnake) -> i32 {
+    let partition = gs.partition();
+    // The room a snake has left after the chokepoint is taken, on its best side of it
+    let room_after = |snake: &Battlesnake, taken: &Coord| {
+        gs.adjacent_moves(&snake.head)
+            .iter()
+            .map(|(coord, _)| partition.room_after(taken, coord))
+            .max()
+            .unwrap_or(0)
+    };
+    let next_to = |snake: &Battlesnake, square: &Coord| {
+        gs.adjacent_moves(&snake.head)
+            .iter()
+            .any(|(coord, _)| coord == square)
+    };
+    let (mut trapped, mut cut_off) = (false, false);
+    for chokepoint in partition.chokepoints() {
+        let square = &chokepoint.square;
+        trapped |= next_to(opponent, square) && room_after(you, square) < you.length as usize;
+        cut_off |= next_to(you, square) && room_after(opponent, square) < opponent.length as usize;
+    }
+    cut_off as i32 * CHOKEPOINT_THREAT - trapped as i32 * CHOKEPOINT_THREAT
+}
+
+/// Score for each square we control in constrictor, where territory is the whole game.
+const CONSTRICTOR_CONTROL_WEIGHT: i32 = 100;
+/// Score for each opponent walled off from us with less room than we have, or the penalty for
+/// each walled off with more.
+const CONSTRICTOR_PARTITION: i32 = 20000;
+
+/// Scores constrictor positions, where nobody eats or goes hungry and every snake grows a square
+/// a turn, so the game is won by walling the others into less room than we have left.
+///
+/// Food, health, tail and center terms would only be dead weight here, so territory and how the
+/// board is partitioned are all there is.
+pub(crate) fn constrictor_evaluate(gs: &GameState, depth: i32) -> Score {
+    let mut score = Score::new();
+    let you = gs.you();
+    // Elimination is bad
+    if you.eliminated {
+        score.min = true;
+        return score;
+    }
+
+    // Other snakes being eliminated is good
+    if gs.board.alive().count() == 1 {
+        score.max = true;
+        return score;
+    }
+
+    // Penalize moving to where a bigger or equal snakes head might be
+    // Incentivize moving to where a smaller snakes head might be
+    if gs.board.avoids.contains(&you.head) {
+        score.snake_avoids = -5000;
+    } else if gs.board.stomps.contains(&you.head) {
+        score.snake_stomps = 5000;
+    }
+
+    // Every square we control is a turn we may outlast the others by
+    let territory_info = gs.compute_territory_info();
+    if let Some(controlled) = territory_info.controlled.get(you.sid.index()) {
+        score.board_control = gs
+            .board
+            .scale_area(*controlled as i32 * CONSTRICTOR_CONTROL_WEIGHT);
+    }
+
+    // Nothing moves out of the way in constrictor, so the room we can reach is all we'll get
+    let available = territory_info.available;
+    if available < you.length as usize + 1 {
+        score.board_control = -10000;
+    }
+
+    // Once a wall separates us from an opponent, whoever has more room outlasts the other
+    let distances = gs.distances_from(&you.head);
+    for opponent in gs.board.alive().filter(|snake| snake.sid != you.sid) {
+        let separated = gs
+            .adjacent_moves(&opponent.head)
+            .into_iter()
+            .all(|(coord, _)| distances.distance(&coord).is_none());
+        if !separated {
+            continue;
+        }
+        let room = gs.reachable_squares(&opponent.head);
+        if room < available {
+            score.partition += CONSTRICTOR_PARTITION;
+        } else if room > available {
+            score.partition -= CONSTRICTOR_PARTITION;
+        }
+    }
+
+    // The longer we survive, the better
+    score.survival = depth * 10000;
+
+    score
+}
@@ -0,0 +1,153 @@
+//! Solo mazes and challenges, where there's nobody to search against and the way to the food is
+//! a matter of pathfinding rather than of evaluating positions.
+
+use super::*;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Plays solo games on maze maps and official challenges by routing to food.
+///
+/// Food is taken by the shortest route that leaves a way back to our tail once it's eaten. When no
+/// food is safe to take, we stall along the longest way round instead, keeping our tail in reach
+/// and as much of the board open as we can until a route opens up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MazePolicy;
+
+impl MazePolicy {
+    /// Whether a game is one this policy plays rather than the personality's own: a solo game on a
+    /// maze map, or any solo challenge.
+    pub fn suits(gs: &GameState) -> bool {
+        gs.game.ruleset.name == GameMode::Solo
+            && (matches!(gs.game.map, GameMap::SoloMaze | GameMap::ArcadeMaze)
+                || matches!(gs.game.source, Source::Challenge))
+    }
+}
+
+impl MovePolicy for MazePolicy {
+    fn choose(&mut self, gs: &GameState, deadline: Instant) -> (Direction, Report) {
+        let start = Instant::now();
+        let you = gs.you();
+        let mut report = Report::unsearched();
+        let direction = match safe_food_route(gs, deadline) {
+            Some(route) => {
+                report.pv = route.iter().map(|&coord| (you.sid, coord)).collect();
+                gs.direction_to(&you.head, &route[0])
+            }
+            None => stall(gs),
+        }
+        .unwrap_or_else(|| gs.random_valid_move(&you.head).1);
+        report.time = start.elapsed().as_millis();
+        (direction, report)
+    }
+}
+
+/// The shortest route to the food that's safe to eat, closest food first, leaving out the head.
+fn safe_food_route(gs: &GameState, deadline: Instant) -> Option<Vec<Coord>> {
+    let mut routes: Vec<Vec<Coord>> = gs
+        .board
+        .food
+        .iter()
+        .filter_map(|food| route(gs, food))
+        .collect();
+    routes.sort_by_key(Vec::len);
+    routes
+        .into_iter()
+        .take_while(|_| Instant::now() < deadline)
+        .find(|route| {
+            let mut gs = gs.clone();
+            route.iter().all(|&coord| play(&mut gs, coord)) && tail_in_reach(&gs)
+        })
+}
+
+/// The shortest route from our head to `goal`, leaving out the head, going onto body squares only
+/// once their tails will have moved off them and onto hazards only while they won't finish us.
+fn route(gs: &GameState, goal: &Coord) -> Option<Vec<Coord>> {
+    let you = gs.you();
+    // Turns until each body square is free, counting from the tail
+    let mut free_after: Grid<Option<u32>> = Grid::new(gs.board.width, gs.board.height);
+    for snake in gs.board.alive() {
+        let length = snake.body.len() as u32;
+        for (i, coord) in snake.body.iter().enumerate() {
+            let turns = length - i as u32;
+            if free_after.get(coord).copied().flatten().unwrap_or(0) < turns {
+                free_after.set(coord, Some(turns));
+            }
+        }
+    }
+    let mut came_from: Grid<Option<Coord>> = Grid::new(gs.board.width, gs.board.height);
+    let mut nodes: VecDeque<(Coord, u32)> = VecDeque::new();
+    came_from.set(&you.head, Some(you.head));
+    nodes.push_back((you.head, 0));
+    while let Some((coord, distance)) = nodes.pop_front() {
+        if coord == *goal {
+            let mut route = vec![coord];
+            while let Some(Some(previous)) = came_from.get(route.last().unwrap()).copied() {
+                if previous == you.head {
+                    break;
+                }
+                route.push(previous);
+            }
+            route.reverse();
+            return Some(route);
+        }
+        let arrival = distance + 1;
+        for (adj_coord, _) in gs.adjacent_moves(&coord) {
+            if !gs.valid_at(&adj_coord) || came_from.get(&adj_coord).copied().flatten().is_some() {
+                continue;
+            }
+            if free_after.get(&adj_coord).copied().flatten().unwrap_or(0) > arrival {
+                continue;
+            }
+            let health = you.health - arrival as i32;
+            if matches!(gs.board.hazard_at(&adj_coord), Some(damage) if damage >= health) {
+                continue;
+            }
+            came_from.set(&adj_coord, Some(coord));
+            nodes.push_back((adj_coord, arrival));
+        }
+    }
+    None
+}
+
+/// The move that keeps our tail in reach with the most room, and of those the one furthest from
+/// the tail, so we take the longest way round while waiting for food.
+fn stall(gs: &GameState) -> Option<Direction> {
+    let you = gs.you();
+    gs.adjacent_moves(&you.head)
+        .into_iter()
+        .filter(|(coord, _)| gs.viable(coord))
+        .filter_map(|(coord, direction)| {
+            let mut gs = gs.clone();
+            if !play(&mut gs, coord) {
+                return None;
+            }
+            let you = gs.you();
+            let distances = gs.distances_from(&you.head);
+            let rank = (
+                tail_in_reach(&gs),
+                gs.reachable_squares(&you.head),
+                gs.tail_distance(you, &distances).unwrap_or(0),
+            );
+            Some((rank, direction))
+        })
+        .max_by_key(|&(rank, _)| rank)
+        .map(|(_, direction)| direction)
+}
+
+/// Moves our head to `coord` for good, returning whether we survived it.
+fn play(gs: &mut GameState, coord: Coord) -> bool {
+    if gs.advance(&[(gs.you().sid, coord)]).is_err() {
+        return false;
+    }
+    gs.commit();
+    !gs.you().eliminated
+}
+
+/// Whether our tail can be reached from our head, or there's at least room for our whole body.
+fn tail_in_reach(gs: &GameState) -> bool {
+    let you = gs.you();
+    let tail_reached = gs
+        .tail_distance(you, &gs.distances_from(&you.head))
+        .is_some();
+    tail_reached || gs.reachable_squares(&you.head) >= you.body.len()
+}
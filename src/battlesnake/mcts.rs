@@ -0,0 +1,262 @@
+//! Monte Carlo tree search over simultaneous moves, as an engine to compare minimax against.
+
+use super::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Exploration constant of UCB1.
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// Deepest the tree grows, in turns, leaving the rest of the undo buffers to the playouts.
+const MAX_TREE_TURNS: usize = 64;
+/// Turns of random moves a playout runs past the tree before the position is scored.
+const PLAYOUT_TURNS: usize = 8;
+/// Playouts run however late the search starts, enough to try every move at the root.
+const MIN_PLAYOUTS: u32 = 16;
+/// Basic evaluation score that counts as three quarters of a win, for turning scores into
+/// rewards.
+const REWARD_SCALE: f64 = 1000.0;
+
+/// One move of one snake at a node, with the rewards of the playouts that took it.
+#[derive(Debug, Clone)]
+struct Arm {
+    coord: Coord,
+    visits: u32,
+    reward: f64,
+}
+
+impl Arm {
+    fn mean(&self) -> f64 {
+        if self.visits == 0 {
+            0.0
+        } else {
+            self.reward / self.visits as f64
+        }
+    }
+    /// Upper confidence bound of the reward, with untried moves first.
+    fn ucb(&self, log_visits: f64) -> f64 {
+        if self.visits == 0 {
+            return f64::INFINITY;
+        }
+        self.mean() + EXPLORATION * (log_visits / self.visits as f64).sqrt()
+    }
+}
+
+/// A position in the tree. Each snake picks its own move by UCB1, as if the others' moves were
+/// part of the position, which is decoupled UCT.
+#[derive(Debug, Clone)]
+struct Node {
+    visits: u32,
+    /// The moves of every snake alive here.
+    snakes: Vec<(SnakeId, Vec<Arm>)>,
+    /// The node each combination of arm indexes leads to.
+    children: HashMap<Vec<usize>, usize>,
+}
+
+impl Node {
+    fn new(gs: &GameState) -> Self {
+        let snakes = gs
+            .move_choices()
+            .into_iter()
+            .map(|(sid, mut coords)| {
+                // Turning back into the neck is never worth a playout, whatever the tail does
+                let neck = gs
+                    .board
+                    .snake(sid)
+                    .and_then(|snake| gs.neck_at(&snake.head));
+                if coords.len() > 1 {
+                    coords.retain(|coord| Some(*coord) != neck);
+                }
+                let arms = coords
+                    .into_iter()
+                    .map(|coord| Arm {
+                        coord,
+                        visits: 0,
+                        reward: 0.0,
+                    })
+                    .collect();
+                (sid, arms)
+            })
+            .collect();
+        Node {
+            visits: 0,
+            snakes,
+            children: HashMap::new(),
+        }
+    }
+    /// Every snake's arm with the highest upper confidence bound.
+    fn select(&self) -> Vec<usize> {
+        let log_visits = (self.visits.max(1) as f64).ln();
+        self.snakes
+            .iter()
+            .map(|(_, arms)| {
+                (0..arms.len())
+                    .max_by(|&a, &b| arms[a].ucb(log_visits).total_cmp(&arms[b].ucb(log_visits)))
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+    /// Every snake's most played arm.
+    fn most_visited(&self) -> Vec<usize> {
+        self.snakes
+            .iter()
+            .map(|(_, arms)| (0..arms.len()).max_by_key(|&i| arms[i].visits).unwrap_or(0))
+            .collect()
+    }
+    fn moves(&self, arms: &[usize]) -> Vec<(SnakeId, Coord)> {
+        self.snakes
+            .iter()
+            .zip(arms)
+            .map(|((sid, snake_arms), &i)| (*sid, snake_arms[i].coord))
+            .collect()
+    }
+}
+
+/// Our result at the end of a playout, from 0 when we're eliminated to 1 when we've won, with
+/// unfinished games scored by the basic evaluation in between.
+fn reward(gs: &GameState) -> f64 {
+    let score = basic_leaf_evaluate(gs, 0);
+    if score.min {
+        return 0.0;
+    } else if score.max {
+        return 1.0;
+    }
+    let sum = score.sum() as f64;
+    0.5 + 0.5 * sum / (sum.abs() + REWARD_SCALE)
+}
+
+/// Monte Carlo tree search: walks down the tree by decoupled UCT, plays random moves for a few
+/// turns from the first new position, and credits every move on the way with how we fared.
+/// Opponents are credited with the opposite of our reward. Plays the root move tried most.
+#[derive(Debug, Clone)]
+pub struct MctsPolicy {
+    /// Search time per move in milliseconds, on top of the deadline.
+    pub(crate) timeout: u128,
+    /// Playouts after which the search stops, in place of its timeout. Playouts are seeded by
+    /// the position, so a budget makes moves independent of the machine.
+    pub(crate) playouts: Option<u32>,
+}
+
+impl Default for MctsPolicy {
+    fn default() -> Self {
+        MctsPolicy {
+            timeout: SEARCH_TIMEOUT,
+            playouts: None,
+        }
+    }
+}
+
+impl MctsPolicy {
+    /// Runs one playout from the root, leaving `gs` as it was, and returns how many turns of it
+    /// were in the tree.
+    fn playout(
+        gs: &mut GameState,
+        nodes: &mut Vec<Node>,
+        rng: &mut StdRng,
+    ) -> Result<usize, EngineError> {
+        let you = gs.you().sid;
+        let mut path: Vec<(usize, Vec<usize>)> = Vec::new();
+        let mut node = 0;
+        // Down the tree to the first position that's not been played out from
+        while nodes[node].visits > 0 && !gs.game_over() && path.len() < MAX_TREE_TURNS {
+            let arms = nodes[node].select();
+            gs.advance(&nodes[node].moves(&arms))?;
+            let child = match nodes[node].children.get(&arms) {
+                Some(&child) => child,
+                None => {
+                    nodes.push(Node::new(gs));
+                    let child = nodes.len() - 1;
+                    nodes[node].children.insert(arms.clone(), child);
+                    child
+                }
+            };
+            path.push((node, arms));
+            node = child;
+        }
+        let mut turns = path.len();
+        for _ in 0..PLAYOUT_TURNS {
+            if gs.game_over() {
+                break;
+            }
+            let moves: Vec<(SnakeId, Coord)> = gs
+                .move_choices()
+                .into_iter()
+                .map(|(sid, coords)| (sid, coords[rng.gen_range(0..coords.len())]))
+                .collect();
+            gs.advance(&moves)?;
+            turns += 1;
+        }
+        let reward = reward(gs);
+        for _ in 0..turns {
+            gs.undo()?;
+        }
+        nodes[node].visits += 1;
+        let depth = path.len();
+        for (index, arms) in path {
+            let node = &mut nodes[index];
+            node.visits += 1;
+            for ((sid, snake_arms), &i) in node.snakes.iter_mut().zip(&arms) {
+                let arm = &mut snake_arms[i];
+                arm.visits += 1;
+                arm.reward += if *sid == you { reward } else { 1.0 - reward };
+            }
+        }
+        Ok(depth)
+    }
+}
+
+impl MovePolicy for MctsPolicy {
+    fn choose(&mut self, gs: &GameState, deadline: Instant) -> (Direction, Report) {
+        let start = Instant::now();
+        let stop = match self.playouts {
+            Some(_) => None,
+            None => {
+                let timeout = Duration::from_millis(self.timeout.min(u64::MAX as u128) as u64);
+                Some(deadline.min(start + timeout))
+            }
+        };
+        let mut gs = gs.clone();
+        let mut rng = StdRng::seed_from_u64(gs.position_key());
+        let mut nodes = vec![Node::new(&gs)];
+        let mut report = Report::unsearched();
+        let mut playouts = 0;
+        while !gs.game_over()
+            && self.playouts.is_none_or(|budget| playouts < budget)
+            && (playouts < MIN_PLAYOUTS || stop.is_none_or(|stop| Instant::now() < stop))
+        {
+            match MctsPolicy::playout(&mut gs, &mut nodes, &mut rng) {
+                Ok(depth) => report.depth = report.depth.max(depth as u32),
+                // A playout that can't be undone leaves the state unusable for any more
+                Err(e) => {
+                    warn!("stopped the tree search early: {}", e);
+                    break;
+                }
+            }
+            playouts += 1;
+        }
+        report.time = start.elapsed().as_millis();
+        let you = gs.you();
+        let ours = nodes[0].snakes.iter().position(|(sid, _)| *sid == you.sid);
+        let best = match ours {
+            Some(ours) => &nodes[0].snakes[ours].1[nodes[0].most_visited()[ours]],
+            None => return (gs.random_valid_move(&you.head).1, report),
+        };
+        report.score = Score::new();
+        report.score.min = best.visits > 0 && best.mean() == 0.0;
+        // The line played most, for as long as the tree follows it
+        let mut node = 0;
+        while nodes[node].visits > 1 {
+            let arms = nodes[node].most_visited();
+            report.pv.extend(nodes[node].moves(&arms));
+            match nodes[node].children.get(&arms) {
+                Some(&child) => node = child,
+                None => break,
+            }
+        }
+        let direction = gs
+            .direction_to(&you.head, &best.coord)
+            .unwrap_or_else(|| gs.random_valid_move(&you.head).1);
+        (direction, report)
+    }
+}
@@ -0,0 +1,112 @@
+//! The Battlesnake engine, usable without the HTTP server in `main.rs`.
+//!
+//! A `GameState` is deserialized from a move request, readied with `GameState::init`, and then
+//! played forward with `GameState::advance` and back with `GameState::undo`. `Search` picks our
+//! move in it, and `make_move` wraps a search with everything a move request needs.
+//!
+//! - `board`: the game as the API describes it
+//! - `rules`: game states and how they advance
+//! - `ruleset`: what each game mode changes about the rules
+//! - `builder`: game states put together in code
+//! - `error`: input the engine can't play
+//! - `search` and `eval`: choosing a move
+//! - `mcts`: Monte Carlo tree search, another way of choosing one, with the `mcts` feature
+//! - `pocket`: small pockets our snake is sealed in, solved exactly
+//! - `partition`: the chokepoints that wall off parts of the board
+//! - `policy`: the ways of choosing a move, behind one trait
+//! - `maze`: solo mazes and challenges, solved by pathfinding
+//! - `cycle`: other solo games, survived by following a Hamiltonian cycle
+//! - `opening`: the first turns of other games, played by rule
+//! - `render`: drawing boards, with the `render` feature
+//! - `session`: what's learned over a game
+//! - `api`: responses, personalities and the tools built on the search
+//! - `selfplay`: games against itself
+
+mod api;
+mod board;
+mod builder;
+mod cycle;
+mod error;
+mod eval;
+mod maze;
+#[cfg(feature = "mcts")]
+mod mcts;
+mod opening;
+mod partition;
+mod pocket;
+mod policy;
+#[cfg(feature = "render")]
+mod render;
+mod rules;
+mod ruleset;
+mod search;
+mod selfplay;
+mod session;
+
+pub use api::*;
+pub use board::*;
+pub use builder::*;
+pub use cycle::*;
+pub use error::*;
+use eval::*;
+pub use maze::*;
+#[cfg(feature = "mcts")]
+pub use mcts::*;
+pub use opening::*;
+pub use partition::*;
+use pocket::*;
+pub use policy::*;
+#[cfg(feature = "render")]
+pub use render::*;
+pub use rules::*;
+use ruleset::*;
+pub use search::*;
+pub use selfplay::*;
+pub use session::*;
+
+/// Builds a game state from a board in the notation of `boards::parse`, with its metadata computed.
+#[cfg(test)]
+pub(crate) fn new_gamestate_from_text(text: &str) -> GameState {
+    let mut gs = crate::boards::parse(text).unwrap();
+    gs.init();
+    gs
+}
+
+/// Everything advancing and undoing a move may touch.
+#[cfg(any(test, feature = "bench"))]
+fn undoable_fields(gs: &GameState) -> impl PartialEq + std::fmt::Debug {
+    let snake = |snake: &Battlesnake| {
+        (
+            snake.id.clone(),
+            snake.health,
+            snake.body.clone(),
+            snake.head,
+            snake.length,
+            snake.eliminated,
+        )
+    };
+    let metadata = (
+        gs.board.segments.clone(),
+        gs.board.hazard_damage.clone(),
+        gs.board.stomps.clone(),
+        gs.board.avoids.clone(),
+    );
+    (
+        gs.turn,
+        gs.undo_index,
+        gs.board.snakes.iter().map(snake).collect::<Vec<_>>(),
+        snake(&gs.you),
+        // Undo puts eaten food back at the end of the list, so it's the squares that are compared
+        gs.board.food_squares.clone(),
+        gs.board.hazards.clone(),
+        metadata,
+    )
+}
+
+/// Entry points for the benchmarks in `benches/` and the fuzz targets in `fuzz/`, which can't
+/// reach the engine's internals.
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,96 @@
+//! The first turns of a game, while every snake is still uncoiling from the stack it spawned in.
+//! The evaluation reads those positions oddly, with our tail under our head and nothing yet in
+//! the way, so they're played from a few plain rules instead of searched.
+
+use super::*;
+use std::time::Instant;
+
+/// Turns from the start of a game played by `OpeningPolicy`, long enough for a snake spawned
+/// three deep to have uncoiled.
+pub const OPENING_TURNS: u32 = 3;
+
+/// Score for each step closer a square is to the nearest food we get to before anyone else.
+const OPENING_FOOD_WEIGHT: i32 = 100;
+/// Penalty for each point of hazard damage a square does.
+const OPENING_HAZARD_WEIGHT: i32 = 50;
+/// Score for each step closer a square is to the middle of the board.
+const OPENING_CENTER_WEIGHT: i32 = 10;
+
+/// Plays the opening of games against other snakes without searching.
+///
+/// Moves that could meet another head, or that lead into less room than our body needs, are only
+/// taken when there's nothing else. Among the rest, we head for the closest food we get to before
+/// any other snake, keep off the map's hazards, and develop out of the corner or off the edge we
+/// spawned on, toward the middle. Wrapped boards have no middle, so there the food decides.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpeningPolicy;
+
+impl OpeningPolicy {
+    /// Whether a game is one this policy plays rather than the personality's own: one of the
+    /// first `OPENING_TURNS` of a game against other snakes that's played for food. Constrictor
+    /// games have none to go for.
+    pub fn suits(gs: &GameState) -> bool {
+        gs.turn < OPENING_TURNS
+            && gs.board.alive().count() > 1
+            && !matches!(gs.game.ruleset.name, GameMode::Solo | GameMode::Constrictor)
+    }
+}
+
+impl MovePolicy for OpeningPolicy {
+    fn choose(&mut self, gs: &GameState, deadline: Instant) -> (Direction, Report) {
+        let start = Instant::now();
+        let you = gs.you();
+        let food = food_we_reach_first(gs);
+        let center = gs.board.center();
+        let direction = gs
+            .adjacent_moves(&you.head)
+            .into_iter()
+            .filter(|(coord, _)| gs.viable(coord))
+            .max_by_key(|(coord, _)| {
+                let contested = gs.board.avoids.contains(coord);
+                let cramped = gs.reachable_squares(coord) < you.body.len() + 1;
+                let distances = gs.distances_from(coord);
+                let mut score = food
+                    .iter()
+                    .filter_map(|food| distances.distance(food))
+                    .min()
+                    .map_or(0, |distance| -(distance as i32) * OPENING_FOOD_WEIGHT);
+                score -= gs.board.hazard_at(coord).unwrap_or(0) * OPENING_HAZARD_WEIGHT;
+                if !gs.rules().wrapped() {
+                    score -= coord.manhattan_distance(&center) * OPENING_CENTER_WEIGHT;
+                }
+                (!contested, !cramped, score)
+            })
+            .map(|(_, direction)| direction);
+        let direction = match direction {
+            Some(direction) => direction,
+            None => return SurvivalPolicy.choose(gs, deadline),
+        };
+        let mut report = Report::unsearched();
+        report.time = start.elapsed().as_millis();
+        (direction, report)
+    }
+}
+
+/// The food we're strictly closer to than every other snake, so we get there first.
+fn food_we_reach_first(gs: &GameState) -> Vec<Coord> {
+    let you = gs.you();
+    let ours = gs.distances_from(&you.head);
+    let theirs: Vec<Grid<Option<u32>>> = gs
+        .board
+        .alive()
+        .filter(|snake| snake.sid != you.sid && !gs.board.teammate(snake))
+        .map(|snake| gs.distances_from(&snake.head))
+        .collect();
+    gs.board
+        .food
+        .iter()
+        .filter(|food| match ours.distance(food) {
+            Some(distance) => theirs
+                .iter()
+                .all(|theirs| theirs.distance(food).is_none_or(|other| distance < other)),
+            None => false,
+        })
+        .copied()
+        .collect()
+}
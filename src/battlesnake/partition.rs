@@ -0,0 +1,169 @@
+//! Cut vertices of the free squares: the squares that, once a snake takes them, wall off part of
+//! the board from the rest.
+
+use super::*;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A free square that joins parts of the board nothing else joins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Chokepoint {
+    pub square: Coord,
+    /// Sizes of the parts its free squares split into once it's taken, largest first.
+    pub pieces: Vec<usize>,
+    /// Search order of the first square of each part cut off below it, with the part's size.
+    /// The squares not under any of them are the rest of its free squares.
+    #[serde(skip)]
+    below: Vec<(u32, usize)>,
+}
+
+/// How the free squares hang together: which are chokepoints, and how big the parts of the board
+/// they join are.
+#[derive(Debug, Clone)]
+pub struct Partition {
+    /// Order each free square was reached in by the depth-first search, and the index of its
+    /// component of free squares.
+    order: Grid<Option<(u32, usize)>>,
+    /// Size of each component of free squares.
+    components: Vec<usize>,
+    chokepoints: Vec<Chokepoint>,
+    by_square: HashMap<Coord, usize>,
+}
+
+impl Partition {
+    /// Every chokepoint, row by row from the bottom.
+    pub fn chokepoints(&self) -> &[Chokepoint] {
+        &self.chokepoints
+    }
+    /// Free squares that can be reached from `from` once `taken` is taken, counting `from`. Zero
+    /// if `from` isn't free or is the square taken.
+    pub(crate) fn room_after(&self, taken: &Coord, from: &Coord) -> usize {
+        let (order, component) = match self.order.get(from).copied().flatten() {
+            Some(reached) if from != taken => reached,
+            _ => return 0,
+        };
+        let size = self.components[component];
+        match self.order.get(taken).copied().flatten() {
+            Some((_, taken_component)) if taken_component == component => {}
+            _ => return size,
+        }
+        let chokepoint = match self.by_square.get(taken) {
+            Some(&index) => &self.chokepoints[index],
+            None => return size - 1,
+        };
+        let mut rest = size - 1;
+        for &(start, part) in &chokepoint.below {
+            if (start..start + part as u32).contains(&order) {
+                return part;
+            }
+            rest -= part;
+        }
+        rest
+    }
+}
+
+impl GameState {
+    /// Finds the chokepoints of the free squares, with Tarjan's depth-first search for cut
+    /// vertices, walked with a stack of its own so big boards can't overflow the thread's.
+    pub fn partition(&self) -> Partition {
+        let (width, height) = (self.board.width, self.board.height);
+        let mut order: Grid<Option<(u32, usize)>> = Grid::new(width, height);
+        let mut low: Grid<u32> = Grid::new(width, height);
+        let mut components = Vec::new();
+        let mut below: HashMap<Coord, Vec<(u32, usize)>> = HashMap::new();
+        let mut roots = Vec::new();
+        let mut reached = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let root = Coord { x, y };
+                if !self.viable(&root) || order.get(&root).copied().flatten().is_some() {
+                    continue;
+                }
+                let component = components.len();
+                let first = reached;
+                roots.push(root);
+                order.set(&root, Some((reached, component)));
+                low.set(&root, reached);
+                reached += 1;
+                // Each square on the stack, the square it was reached from and the next
+                // neighbor to look at
+                let mut stack: Vec<(Coord, Option<Coord>, usize)> = vec![(root, None, 0)];
+                while let Some(top) = stack.last_mut() {
+                    let (square, parent, next) = *top;
+                    if next < 4 {
+                        top.2 += 1;
+                        let (neighbor, _) = self.adjacent_moves(&square)[next];
+                        if neighbor == square || !self.viable(&neighbor) {
+                            continue;
+                        }
+                        match order.get(&neighbor).copied().flatten() {
+                            None => {
+                                order.set(&neighbor, Some((reached, component)));
+                                low.set(&neighbor, reached);
+                                reached += 1;
+                                stack.push((neighbor, Some(square), 0));
+                            }
+                            Some((neighbor_order, _)) if Some(neighbor) != parent => {
+                                let square_low = low.get(&square).copied().unwrap_or(0);
+                                low.set(&square, square_low.min(neighbor_order));
+                            }
+                            Some(_) => {}
+                        }
+                        continue;
+                    }
+                    stack.pop();
+                    let parent = match parent {
+                        Some(parent) => parent,
+                        None => continue,
+                    };
+                    let (square_order, _) =
+                        order.get(&square).copied().flatten().unwrap_or_default();
+                    let square_low = low.get(&square).copied().unwrap_or(0);
+                    let (parent_order, _) =
+                        order.get(&parent).copied().flatten().unwrap_or_default();
+                    let parent_low = low.get(&parent).copied().unwrap_or(0);
+                    low.set(&parent, parent_low.min(square_low));
+                    // Nothing under the square reaches above the parent, so the parent cuts it off
+                    if square_low >= parent_order {
+                        let part = (reached - square_order) as usize;
+                        below.entry(parent).or_default().push((square_order, part));
+                    }
+                }
+                components.push((reached - first) as usize);
+            }
+        }
+        let mut below: Vec<(Coord, Vec<(u32, usize)>)> = below.into_iter().collect();
+        below.sort_unstable_by_key(|(square, _)| (square.y, square.x));
+        let mut chokepoints = Vec::new();
+        let mut by_square = HashMap::new();
+        for (square, parts) in below {
+            // The root of a search cuts off everything under it, so it's only a chokepoint when
+            // more than one part hangs from it
+            if roots.contains(&square) && parts.len() < 2 {
+                continue;
+            }
+            let component = order.get(&square).copied().flatten().unwrap_or_default().1;
+            let rest =
+                components[component] - 1 - parts.iter().map(|(_, part)| part).sum::<usize>();
+            let mut pieces: Vec<usize> = parts.iter().map(|&(_, part)| part).collect();
+            if rest > 0 {
+                pieces.push(rest);
+            }
+            pieces.sort_unstable_by(|a, b| b.cmp(a));
+            by_square.insert(square, chokepoints.len());
+            chokepoints.push(Chokepoint {
+                square,
+                pieces,
+                below: parts,
+            });
+        }
+        Partition {
+            order,
+            components,
+            chokepoints,
+            by_square,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Exact answers for our snake sealed in a small pocket, where counting the room the flood fill
+//! reaches gets it wrong both ways: the bodies walling the pocket in may move out of the way in
+//! time, and room that looks big enough may run out before they do.
+
+use super::*;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// Largest pocket, in free squares besides our head, solved exactly rather than counted.
+pub(crate) const POCKET_SQUARES: usize = 12;
+
+/// Moves tried on a pocket before giving up on it, which leaves it to the flood fill count.
+const POCKET_NODE_BUDGET: u32 = 5000;
+
+/// Pockets solved on each search thread before the cache starts over.
+const POCKET_CACHE_ENTRIES: usize = 10_000;
+
+thread_local! {
+    /// Whether each pocket solved on this thread is survivable, by region hash.
+    static POCKET_CACHE: RefCell<HashMap<u64, bool>> = RefCell::new(HashMap::new());
+}
+
+/// Everything that decides whether we survive a pocket, so pockets that agree on it all are the
+/// same pocket wherever they are in the game.
+///
+/// Other snakes are assumed not to eat, so each of their segments moves out when the tail reaches
+/// it, and to keep their heads out of the pocket, which only opens where their bodies leave.
+struct Pocket<'a> {
+    gs: &'a GameState,
+    /// The free squares our head can reach.
+    squares: HashSet<Coord>,
+    /// Squares of other snakes' bodies that are left before the horizon, with the number of moves
+    /// before they are.
+    releases: HashMap<Coord, u32>,
+    /// Free squares outside the pocket next to a square that is or may become part of it.
+    /// Reaching one is getting out.
+    exits: HashSet<Coord>,
+    /// Moves after which still being alive in the pocket counts as surviving it, since by then
+    /// we've had the room to follow our own tail round.
+    horizon: u32,
+}
+
+/// The moves before each segment of `snake` leaves its square, by square, for those left within
+/// `horizon` moves. A segment `n` from the tail leaves after `n` moves.
+fn releases(snake: &Battlesnake, horizon: u32) -> impl Iterator<Item = (Coord, u32)> + '_ {
+    let len = snake.body.len();
+    snake
+        .body
+        .iter()
+        .enumerate()
+        .map(move |(i, coord)| (*coord, (len - 1 - i) as u32))
+        .filter(move |&(_, release)| release < horizon)
+}
+
+impl<'a> Pocket<'a> {
+    /// The pocket our head is sealed in, if it has at most `POCKET_SQUARES` free squares.
+    fn new(gs: &'a GameState) -> Option<Self> {
+        let you = gs.you();
+        let squares: HashSet<Coord> = gs
+            .distances_from(&you.head)
+            .iter()
+            .filter(|(coord, distance)| distance.is_some() && *coord != you.head)
+            .map(|(coord, _)| coord)
+            .collect();
+        if squares.len() > POCKET_SQUARES {
+            return None;
+        }
+        let horizon = (squares.len() + you.body.len()) as u32;
+        let mut releases = HashMap::new();
+        for snake in gs.board.alive().filter(|snake| snake.sid != you.sid) {
+            for (coord, release) in self::releases(snake, horizon) {
+                // A stacked tail leaves when its last segment does
+                let entry = releases.entry(coord).or_insert(release);
+                *entry = (*entry).max(release);
+            }
+        }
+        let mut pocket = Pocket {
+            gs,
+            squares,
+            releases,
+            exits: HashSet::new(),
+            horizon,
+        };
+        let region: Vec<Coord> = pocket
+            .squares
+            .iter()
+            .chain(pocket.releases.keys())
+            .chain(you.body.iter())
+            .copied()
+            .collect();
+        for coord in region {
+            for (adjacent, _) in gs.adjacent_moves(&coord) {
+                if gs.viable(&adjacent) && !pocket.inside(&adjacent) {
+                    pocket.exits.insert(adjacent);
+                }
+            }
+        }
+        Some(pocket)
+    }
+    /// Whether `coord` is a square of the pocket, one of ours, or one that opens up in time.
+    fn inside(&self, coord: &Coord) -> bool {
+        self.squares.contains(coord)
+            || self.releases.contains_key(coord)
+            || self.gs.you().body.contains(coord)
+    }
+    /// Hash of the region: everything the answer depends on, which is little enough in a pocket
+    /// this small to hash square by square.
+    fn key(&self) -> u64 {
+        let you = self.gs.you();
+        let sorted = |squares: Vec<(i32, i32, u32)>| {
+            let mut squares = squares;
+            squares.sort_unstable();
+            squares
+        };
+        let squares = sorted(self.squares.iter().map(|c| (c.x, c.y, 0)).collect());
+        let releases = sorted(self.releases.iter().map(|(c, r)| (c.x, c.y, *r)).collect());
+        let exits = sorted(self.exits.iter().map(|c| (c.x, c.y, 0)).collect());
+        // Food and hazards only matter on the squares we can move to
+        let terrain = sorted(
+            squares
+                .iter()
+                .chain(&releases)
+                .map(|&(x, y, _)| Coord { x, y })
+                .map(|coord| {
+                    let food = self.gs.board.food_squares.contains(&coord) as u32;
+                    let damage = self.gs.board.hazard_at(&coord).unwrap_or(0) as u32;
+                    (coord.x, coord.y, damage << 1 | food)
+                })
+                .collect(),
+        );
+        let mut hasher = DefaultHasher::new();
+        self.gs.rules().wrapped().hash(&mut hasher);
+        (self.gs.board.width, self.gs.board.height).hash(&mut hasher);
+        you.body.hash(&mut hasher);
+        you.health.hash(&mut hasher);
+        (squares, releases, exits, terrain).hash(&mut hasher);
+        hasher.finish()
+    }
+    /// Whether some order of moves keeps us alive until we get out or reach the horizon, or None
+    /// if the budget ran out before that was settled.
+    fn solve(&self) -> Option<bool> {
+        let you = self.gs.you();
+        let mut nodes = 0;
+        self.survives(&you.body, you.health, &[], 1, &mut nodes)
+    }
+    /// Whether we survive from move `turn` on with `body` and `health`, having eaten `eaten`.
+    fn survives(
+        &self,
+        body: &VecDeque<Coord>,
+        health: i32,
+        eaten: &[Coord],
+        turn: u32,
+        nodes: &mut u32,
+    ) -> Option<bool> {
+        if turn > self.horizon {
+            return Some(true);
+        }
+        let mut settled = true;
+        for (coord, _) in self.gs.adjacent_moves(&body[0]) {
+            if self.exits.contains(&coord) {
+                return Some(true);
+            }
+            let open = self.squares.contains(&coord)
+                || self.gs.you().body.contains(&coord)
+                || matches!(self.releases.get(&coord), Some(&release) if release < turn);
+            if !open {
+                continue;
+            }
+            *nodes += 1;
+            if *nodes > POCKET_NODE_BUDGET {
+                return None;
+            }
+            let ate = self.gs.board.food_squares.contains(&coord) && !eaten.contains(&coord);
+            let mut body = body.clone();
+            body.push_front(coord);
+            body.pop_back();
+            if ate {
+                body.push_back(body[body.len() - 1]);
+            }
+            if body.iter().skip(1).any(|segment| *segment == coord) {
+                continue;
+            }
+            let health = match (ate, self.gs.board.hazard_at(&coord)) {
+                (true, _) => 100,
+                (false, damage) => health - 1 - damage.unwrap_or(0),
+            };
+            if health <= 0 {
+                continue;
+            }
+            let mut eaten = eaten.to_vec();
+            if ate {
+                eaten.push(coord);
+            }
+            match self.survives(&body, health, &eaten, turn + 1, nodes) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => settled = false,
+            }
+        }
+        settled.then_some(false)
+    }
+}
+
+impl GameState {
+    /// Whether our snake, sealed in a pocket of at most `POCKET_SQUARES` free squares, can stay
+    /// alive until it gets out or has room to follow its tail round, taking the bodies around it
+    /// moving out of the way into account. None when it isn't sealed in a pocket that small, or
+    /// the pocket is too tangled to solve quickly.
+    pub(crate) fn pocket_survivable(&self) -> Option<bool> {
+        let pocket = Pocket::new(self)?;
+        let key = pocket.key();
+        if let Some(survivable) = POCKET_CACHE.with(|cache| cache.borrow().get(&key).copied()) {
+            return Some(survivable);
+        }
+        let survivable = pocket.solve()?;
+        POCKET_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.len() >= POCKET_CACHE_ENTRIES {
+                cache.clear();
+            }
+            cache.insert(key, survivable);
+        });
+        Some(survivable)
+    }
+}
@@ -0,0 +1,193 @@
+//! Ways of choosing a move, so engines can be swapped and compared without touching `make_move`.
+
+use super::*;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// How a move was chosen, for the move response and its telemetry.
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Score of the chosen move, or the lowest score for policies that don't score moves.
+    pub score: Score,
+    /// Deepest fully completed search iteration, or 0 without a search.
+    pub depth: u32,
+    /// Time spent choosing, in milliseconds.
+    pub time: u128,
+    /// Moves expected to follow the chosen one.
+    pub pv: Vec<(SnakeId, Coord)>,
+    /// The tree explored by the last completed search iteration, when the policy records it.
+    pub tree: Option<SearchTree>,
+    /// How well the search ordered its moves, empty without a search.
+    pub ordering: OrderingStats,
+}
+
+impl Report {
+    /// The report of a policy that chose without searching.
+    pub(crate) fn unsearched() -> Self {
+        let mut score = Score::new();
+        score.min = true;
+        Report {
+            score,
+            depth: 0,
+            time: 0,
+            pv: Vec::new(),
+            tree: None,
+            ordering: OrderingStats::default(),
+        }
+    }
+}
+
+/// Chooses our move in a game state readied with `GameState::init`.
+pub trait MovePolicy {
+    /// Our move in `gs`, chosen before `deadline`.
+    fn choose(&mut self, gs: &GameState, deadline: Instant) -> (Direction, Report);
+}
+
+/// Iterative deepening minimax.
+#[derive(Debug, Clone)]
+pub struct MinimaxPolicy {
+    /// Always use the cheaper basic evaluation, however few snakes are left.
+    pub(crate) basic: bool,
+    /// Search time per move in milliseconds, on top of the deadline.
+    pub(crate) timeout: u128,
+    /// Nodes evaluated after which the search stops, in place of its timeout.
+    pub(crate) node_budget: Option<u32>,
+    pub(crate) full_evaluation_depth: Option<i32>,
+    /// What's been learned of each opponent, by snake id.
+    pub(crate) tendencies: HashMap<String, Tendencies>,
+    pub(crate) log_level: log::Level,
+    /// Record the tree each search explores, for `Report::tree`.
+    pub(crate) record_tree: bool,
+}
+
+impl Default for MinimaxPolicy {
+    fn default() -> Self {
+        MinimaxPolicy {
+            basic: false,
+            timeout: SEARCH_TIMEOUT,
+            node_budget: None,
+            full_evaluation_depth: None,
+            tendencies: HashMap::new(),
+            log_level: log::Level::Debug,
+            record_tree: false,
+        }
+    }
+}
+
+impl MovePolicy for MinimaxPolicy {
+    fn choose(&mut self, gs: &GameState, deadline: Instant) -> (Direction, Report) {
+        let mut gs = gs.clone();
+        let mut search = Search::new(&gs);
+        search.timeout = self.timeout;
+        search.deadline = Some(deadline);
+        search.log_level = self.log_level;
+        if self.full_evaluation_depth.is_some() {
+            search.full_evaluation_depth = self.full_evaluation_depth;
+        }
+        search.tendencies = gs
+            .board
+            .snakes
+            .iter()
+            .map(|snake| self.tendencies.get(&snake.id).cloned())
+            .collect();
+        if let Some(budget) = self.node_budget {
+            search.node_budget = Some(budget);
+            search.timeout = u128::MAX;
+        }
+        if self.basic {
+            search.evaluate_fn = basic_leaf_evaluate;
+        }
+        if self.record_tree {
+            search.tree = Some(TreeRecorder::new(SEARCH_TREE_NODES));
+        }
+        search.iterative_deepening(&mut gs, 50);
+        let mut direction = search.best_direction;
+        // Every move loses against perfect play, but opponents often fail to finish us off
+        if search.best_score.sum() == i32::MIN {
+            direction = SurvivalPolicy.choose(&gs, deadline).0;
+        }
+        let report = Report {
+            score: search.best_score.clone(),
+            depth: search.iteration_reached,
+            time: search.search_time,
+            pv: std::mem::take(&mut search.best_pv),
+            tree: search.tree.take().and_then(|tree| tree.completed),
+            ordering: search.ordering,
+        };
+        (direction, report)
+    }
+}
+
+/// Plays for time in a position that's lost, for opponents that don't see the win: moves where
+/// we'd survive the most turns, which is the room we'd have left or the health we'd have left if
+/// that's less, and away from head-to-heads we'd lose.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SurvivalPolicy;
+
+impl MovePolicy for SurvivalPolicy {
+    fn choose(&mut self, gs: &GameState, _deadline: Instant) -> (Direction, Report) {
+        let you = gs.you();
+        let direction = gs
+            .adjacent_moves(&you.head)
+            .into_iter()
+            .filter(|(coord, _)| gs.viable(coord))
+            .max_by_key(|(coord, _)| {
+                let health = if gs.board.food_squares.contains(coord) {
+                    100
+                } else {
+                    you.health - 1 - gs.board.hazard_at(coord).unwrap_or(0)
+                };
+                let mut turns = gs.reachable_squares(coord).min(health.max(0) as usize);
+                // A head-to-head we'd lose only happens if the opponent takes it
+                if gs.board.avoids.contains(coord) {
+                    turns /= 2;
+                }
+                turns
+            })
+            .map(|(_, direction)| direction)
+            .unwrap_or_else(|| gs.random_valid_move(&you.head).1);
+        (direction, Report::unsearched())
+    }
+}
+
+/// Heads for the closest reachable food without searching.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyFoodPolicy;
+
+impl MovePolicy for GreedyFoodPolicy {
+    fn choose(&mut self, gs: &GameState, _deadline: Instant) -> (Direction, Report) {
+        (gs.greedy_move(), Report::unsearched())
+    }
+}
+
+/// Moves to a random square that's safe this turn, as a baseline for the others.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomPolicy;
+
+impl MovePolicy for RandomPolicy {
+    fn choose(&mut self, gs: &GameState, _deadline: Instant) -> (Direction, Report) {
+        let direction = gs.random_valid_move(&gs.you().head).1;
+        (direction, Report::unsearched())
+    }
+}
+
+impl Strategy {
+    /// The policy that plays this strategy, searching as `minimax` is set up to if it searches.
+    /// Tree search keeps to the same timeout, with the node budget as its playout budget.
+    pub fn policy(self, mut minimax: MinimaxPolicy) -> Box<dyn MovePolicy> {
+        match self {
+            Strategy::Minimax => Box::new(minimax),
+            Strategy::Basic => {
+                minimax.basic = true;
+                Box::new(minimax)
+            }
+            Strategy::Greedy => Box::new(GreedyFoodPolicy),
+            Strategy::Random => Box::new(RandomPolicy),
+            #[cfg(feature = "mcts")]
+            Strategy::Mcts => Box::new(MctsPolicy {
+                timeout: minimax.timeout,
+                playouts: minimax.node_budget,
+            }),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Drawing boards as SVG, ASCII or board text, built with the `render` feature.
+
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// Letters used to label snakes in ASCII renders; `Y` is reserved for us and `F`, `H`, `Z` mark squares.
+const RENDER_LETTERS: &str = "ABCDEGIJKLMNOPQRSTUVWX";
+
+/// Size of a square in SVG renders, in pixels.
+const RENDER_CELL: i32 = 20;
+
+/// Color used for snakes without a valid hex color.
+const RENDER_DEFAULT_COLOR: &str = "#888888";
+
+/// Draws the board, optionally shading the squares each snake controls.
+pub fn render(mut gs: GameState, format: RenderFormat, territory: bool) -> String {
+    gs.init();
+    let territory = if territory {
+        gs.compute_territory_info().controlled_squares(&gs)
+    } else {
+        HashMap::new()
+    };
+    match format {
+        RenderFormat::Svg => render_svg(&gs, &territory),
+        RenderFormat::Ascii => render_ascii(&gs, &territory),
+        RenderFormat::Text => crate::boards::to_text(&gs),
+    }
+}
+
+/// The board after every complete turn of a line of the search from `gs`, which has to have been
+/// initialized, in board text.
+pub fn pv_boards(gs: &GameState, line: &[(SnakeId, Coord)]) -> Vec<String> {
+    gs.line_positions(line, gs.board.alive().count())
+        .iter()
+        .skip(1)
+        .map(crate::boards::to_text)
+        .collect()
+}
+
+/// Analyzes the position like `analyze`, also drawing the board after every turn of the principal
+/// variation.
+pub fn analyze_with_boards(mut gs: GameState) -> Analysis {
+    let mut analysis = analyze(gs.clone());
+    gs.init();
+    analysis.pv_boards = Some(pv_boards(&gs, &analysis.best_pv));
+    analysis
+}
+
+fn render_letter(gs: &GameState, index: usize) -> char {
+    if gs.board.snakes[index].id == gs.you.id {
+        return 'Y';
+    }
+    let others = gs.board.snakes[..index]
+        .iter()
+        .filter(|snake| snake.id != gs.you.id)
+        .count();
+    RENDER_LETTERS.chars().nth(others).unwrap_or('?')
+}
+
+fn render_ascii(gs: &GameState, territory: &HashMap<String, HashSet<Coord>>) -> String {
+    let mut cells = vec![vec![[' ', ' ']; gs.board.width as usize]; gs.board.height as usize];
+    let cell = |coord: &Coord| {
+        let in_bounds =
+            coord.x >= 0 && coord.y >= 0 && coord.x < gs.board.width && coord.y < gs.board.height;
+        in_bounds.then_some((coord.y as usize, coord.x as usize))
+    };
+    for hazard in &gs.board.hazards {
+        if let Some((y, x)) = cell(hazard) {
+            cells[y][x][0] = 'H';
+        }
+    }
+    for food in &gs.board.food {
+        if let Some((y, x)) = cell(food) {
+            cells[y][x][0] = if cells[y][x][0] == 'H' { 'Z' } else { 'F' };
+        }
+    }
+    let mut legend = String::new();
+    for (i, snake) in gs.board.snakes.iter().enumerate() {
+        let letter = render_letter(gs, i);
+        legend.push_str(&format!(
+            "{}: {} ({})\n",
+            letter, snake.details.name, snake.id
+        ));
+        for coord in snake.body.iter().rev() {
+            if let Some((y, x)) = cell(coord) {
+                cells[y][x][0] = letter.to_ascii_lowercase();
+            }
+        }
+        if let Some((y, x)) = cell(&snake.head) {
+            cells[y][x][0] = letter;
+        }
+        for coord in territory.get(&snake.id).into_iter().flatten() {
+            if let Some((y, x)) = cell(coord) {
+                cells[y][x][1] = letter.to_ascii_lowercase();
+            }
+        }
+    }
+    let mut result = String::new();
+    for row in cells.iter().rev() {
+        result.push('|');
+        for [content, owner] in row {
+            result.push(*content);
+            result.push(*owner);
+            result.push('|');
+        }
+        result.push('\n');
+    }
+    result + &legend
+}
+
+fn render_color(color: &str) -> &str {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if valid {
+        color
+    } else {
+        RENDER_DEFAULT_COLOR
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_svg(gs: &GameState, territory: &HashMap<String, HashSet<Coord>>) -> String {
+    let width = gs.board.width * RENDER_CELL;
+    let height = gs.board.height * RENDER_CELL;
+    // Board rows are numbered from the bottom, SVG rows from the top
+    let square = |coord: &Coord, inset: i32, attributes: &str| {
+        format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            coord.x * RENDER_CELL + inset,
+            (gs.board.height - 1 - coord.y) * RENDER_CELL + inset,
+            RENDER_CELL - inset * 2,
+            RENDER_CELL - inset * 2,
+            attributes
+        )
+    };
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"#f4f4f4\"/>\n",
+        width, height
+    ));
+    for y in 0..gs.board.height {
+        for x in 0..gs.board.width {
+            svg.push_str(&square(
+                &Coord { x, y },
+                0,
+                "fill=\"none\" stroke=\"#dddddd\"",
+            ));
+            svg.push('\n');
+        }
+    }
+    for snake in &gs.board.snakes {
+        let color = render_color(&snake.details.customizations.color);
+        for coord in territory.get(&snake.id).into_iter().flatten() {
+            svg.push_str(&square(
+                coord,
+                0,
+                &format!("fill=\"{}\" fill-opacity=\"0.2\"", color),
+            ));
+            svg.push('\n');
+        }
+    }
+    for hazard in &gs.board.hazards {
+        svg.push_str(&square(hazard, 0, "fill=\"#555555\" fill-opacity=\"0.4\""));
+        svg.push('\n');
+    }
+    for food in &gs.board.food {
+        svg.push_str(&format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#e0301e\"/>\n",
+            food.x * RENDER_CELL + RENDER_CELL / 2,
+            (gs.board.height - 1 - food.y) * RENDER_CELL + RENDER_CELL / 2,
+            RENDER_CELL / 3
+        ));
+    }
+    for snake in &gs.board.snakes {
+        let color = render_color(&snake.details.customizations.color);
+        svg.push_str(&format!(
+            "<g><title>{}</title>\n",
+            escape_xml(&snake.details.name)
+        ));
+        for coord in snake.body.iter().skip(1) {
+            svg.push_str(&square(coord, 2, &format!("fill=\"{}\"", color)));
+            svg.push('\n');
+        }
+        svg.push_str(&square(
+            &snake.head,
+            1,
+            &format!("fill=\"{}\" stroke=\"#000000\" stroke-width=\"2\"", color),
+        ));
+        svg.push_str("\n</g>\n");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,1401 @@
+//! A game state and the rules that advance it a turn at a time, and undo it again.
+
+use super::*;
+use crate::profiling::{self, Phase};
+use rand::seq::SliceRandom;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, Default)]
+pub struct UndoInfo {
+    previous_tails: Vec<Vec<(SnakeId, Coord)>>,
+    previous_health: Vec<Vec<(SnakeId, i32)>>,
+    eaten_food: Vec<Vec<Coord>>,
+    eliminated_snakes: Vec<Vec<SnakeId>>,
+}
+
+impl UndoInfo {
+    fn new() -> Self {
+        UndoInfo {
+            previous_tails: vec![Vec::new(); 100],
+            previous_health: vec![Vec::new(); 100],
+            eaten_food: vec![Vec::new(); 100],
+            eliminated_snakes: vec![Vec::new(); 100],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct GameState {
+    /// Game Object describing the game being played. Shared between every copy of the state.
+    pub(crate) game: Arc<Game>,
+    /// Turn number of the game being played (0 for new games).
+    pub(crate) turn: u32,
+    /// Board Object describing the initial state of the game board.
+    pub(crate) board: Board,
+    /// Battlesnake Object describing your Battlesnake. Only up to date at the API boundary, the
+    /// simulation reads our snake from the board with `you()`.
+    pub(crate) you: Battlesnake,
+    #[serde(skip)]
+    /// Info for undoing to a previous state
+    pub(crate) undo: UndoInfo,
+    #[serde(skip)]
+    pub(crate) undo_index: usize,
+    /// The royale shrink expected within the search, if it can be predicted.
+    #[serde(skip)]
+    pub(crate) shrink: Option<HazardShrink>,
+    /// Where food has tended to spawn this game, once it's spawned often enough to tell.
+    #[serde(skip)]
+    pub(crate) food_hotspot: Option<Coord>,
+    /// Where we have to be by the time the royale shrinks ahead have happened, if predictable.
+    #[serde(skip)]
+    pub(crate) safe_zone: Option<SafeZone>,
+}
+
+/// Hazards a royale game is expected to add at the end of a turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HazardShrink {
+    pub(crate) turn: u32,
+    /// Squares the hazards will cover that they don't yet.
+    pub(crate) squares: Arc<Vec<Coord>>,
+}
+
+/// The squares a royale game is expected to leave outside the hazards by a turn, a rectangle as
+/// hazards always grow in from a side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SafeZone {
+    /// Turn of the shrink that leaves only these squares safe.
+    pub(crate) turn: u32,
+    /// Corner with the smallest x and y.
+    pub(crate) min: Coord,
+    /// Corner with the largest x and y.
+    pub(crate) max: Coord,
+}
+
+impl SafeZone {
+    /// Moves from `coord` into the zone, ignoring anything in the way.
+    pub(crate) fn distance(&self, coord: &Coord) -> i32 {
+        let dx = (self.min.x - coord.x).max(coord.x - self.max.x).max(0);
+        let dy = (self.min.y - coord.y).max(coord.y - self.max.y).max(0);
+        dx + dy
+    }
+}
+
+/// Penalty for each square the head is outside the safe zone when its shrink happens.
+pub(crate) const SAFE_ZONE_PENALTY: i32 = 20000;
+/// Score for each point of health we have over the average opponent in hazard-heavy games.
+const ATTRITION_WEIGHT: i32 = 50;
+/// Score for each opponent with its head in a hazard while ours isn't, or the penalty for each
+/// out of one while ours is in it.
+const SAUCE_PRESSURE: i32 = 1000;
+/// Penalty for each escape square the head loses to a wall.
+pub(crate) const EDGE_PENALTY: i32 = 250;
+/// Score for food being sure to spawn on a square we control before we'd starve, scaled down by
+/// how likely it is.
+const FOOD_EXPECTATION_WEIGHT: f32 = 1000.0;
+/// Food this many steps or fewer apart counts as one cluster.
+const FOOD_CLUSTER_RADIUS: i32 = 3;
+/// Health at which we position for the food supply rather than the nearest food.
+pub(crate) const COMFORTABLE_HEALTH: i32 = 50;
+/// Our minimum health before we'll spend effort denying food to others.
+const FOOD_DENIAL_MIN_HEALTH: i32 = 50;
+/// Opponents at or below this health are considered hungry.
+const FOOD_DENIAL_HUNGRY_HEALTH: i32 = 30;
+
+/// Largest board width or height we'll search, well above any official map.
+const MAX_BOARD_SIZE: i32 = 50;
+/// Most snakes a board may have, so every snake and our own gets a `SnakeId`.
+const MAX_SNAKES: usize = u8::MAX as usize;
+
+pub(crate) fn in_bounds(coord: &Coord, width: i32, height: i32) -> bool {
+    coord.x >= 0 && coord.y >= 0 && coord.x < width && coord.y < height
+}
+
+impl GameState {
+    pub fn game_id(&self) -> &str {
+        &self.game.id
+    }
+    /// The game for changing, copied first if another state shares it.
+    #[cfg(test)]
+    pub(crate) fn game_mut(&mut self) -> &mut Game {
+        Arc::make_mut(&mut self.game)
+    }
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+    /// Time snakes have to respond to each request, in milliseconds.
+    pub fn timeout(&self) -> u32 {
+        self.game.timeout
+    }
+    /// Checks the invariants the engine relies on, which any request could break: a board of a
+    /// sensible size, snakes with unique ids and non-empty bodies on the board, and us among them.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        let (width, height) = (self.board.width, self.board.height);
+        if !(1..=MAX_BOARD_SIZE).contains(&width) || !(1..=MAX_BOARD_SIZE).contains(&height) {
+            return Err(EngineError::InvalidState(format!(
+                "board is {}x{}, but must be between 1x1 and {}x{}",
+                width, height, MAX_BOARD_SIZE, MAX_BOARD_SIZE
+            )));
+        }
+        if self.board.snakes.len() > MAX_SNAKES {
+            return Err(EngineError::InvalidState(format!(
+                "board has {} snakes, but can have at most {}",
+                self.board.snakes.len(),
+                MAX_SNAKES
+            )));
+        }
+        let mut ids: HashSet<&str> = HashSet::new();
+        for snake in &self.board.snakes {
+            if !ids.insert(&snake.id) {
+                return Err(EngineError::InvalidState(format!(
+                    "more than one snake has id {:?}",
+                    snake.id
+                )));
+            }
+            if snake.body.front() != Some(&snake.head) {
+                return Err(EngineError::InvalidState(format!(
+                    "snake {:?} has a head off its body",
+                    snake.id
+                )));
+            }
+            if !snake
+                .body
+                .iter()
+                .all(|coord| in_bounds(coord, width, height))
+            {
+                return Err(EngineError::InvalidState(format!(
+                    "snake {:?} is off the board",
+                    snake.id
+                )));
+            }
+        }
+        if !ids.contains(self.you.id.as_str()) {
+            return Err(EngineError::InvalidState(format!(
+                "snake {:?} isn't on the board",
+                self.you.id
+            )));
+        }
+        Ok(())
+    }
+    /// Checks the board is no more than `max` squares a side, for servers that won't search
+    /// boards bigger than any they expect to play.
+    pub fn validate_board_size(&self, max: i32) -> Result<(), EngineError> {
+        let (width, height) = (self.board.width, self.board.height);
+        if width > max || height > max {
+            return Err(EngineError::InvalidState(format!(
+                "board is {}x{}, but this server plays boards up to {}x{}",
+                width, height, max, max
+            )));
+        }
+        Ok(())
+    }
+    /// Plays a turn: moves each snake in `moves` to its new head, then feeds, damages and
+    /// eliminates snakes by the rules. Turns can be nested up to the depth of the undo buffers,
+    /// which `init` allocates.
+    pub fn advance(&mut self, moves: &[(SnakeId, Coord)]) -> Result<(), EngineError> {
+        if self.undo_index >= self.undo.previous_tails.len() {
+            return Err(EngineError::TooDeep(self.undo_index));
+        }
+        let _timer = profiling::start(Phase::Advance);
+        let rules = self.rules();
+        // The buffers of this ply are reused from the last time the search reached it
+        self.undo.previous_tails[self.undo_index].clear();
+        self.undo.previous_health[self.undo_index].clear();
+        self.undo.eaten_food[self.undo_index].clear();
+        self.undo.eliminated_snakes[self.undo_index].clear();
+        // Apply snake moves
+        for (owner, new_head) in moves {
+            if self.board.snake(*owner).is_none() {
+                continue;
+            }
+            let board = &mut self.board;
+            let snake = &mut board.snakes[owner.index()];
+            snake.head = *new_head;
+            push_head(&mut snake.body, &mut board.segments, *new_head);
+            // The new head was just pushed, so there's always a tail to pop
+            let tail = pop_tail(&mut snake.body, &mut board.segments).unwrap_or(*new_head);
+            self.undo.previous_tails[self.undo_index].push((snake.sid, tail));
+            self.undo.previous_health[self.undo_index].push((snake.sid, snake.health));
+            let last = snake.body.back().copied().unwrap_or(*new_head);
+            rules.post_move(snake, &mut board.segments);
+            // Consume food, which stays on the board until every snake has moved
+            if board.food_squares.contains(&snake.head) {
+                snake.health = 100;
+                push_tail(&mut snake.body, &mut board.segments, last);
+                let eaten_food = &mut self.undo.eaten_food[self.undo_index];
+                if !eaten_food.contains(&snake.head) {
+                    eaten_food.push(snake.head);
+                }
+            } else if let Some(damage) = board.hazard_damage.get(&snake.head).copied().flatten() {
+                rules.hazard_phase(snake, damage);
+            }
+            snake.length = snake.body.len() as u32;
+        }
+        // Remove Eaten Food
+        for food in &self.undo.eaten_food[self.undo_index] {
+            self.board.remove_food(food);
+        }
+
+        // TODO: Add new food?
+
+        // Find every collision before eliminating anyone, as they all happen at once
+        for snake in self.board.alive() {
+            if rules.eliminated(self, snake) {
+                self.undo.eliminated_snakes[self.undo_index].push(snake.sid);
+            }
+        }
+        // Eliminate snakes, leaving them in place so they can be brought back on undo
+        for sid in &self.undo.eliminated_snakes[self.undo_index] {
+            let snake = &mut self.board.snakes[sid.index()];
+            snake.eliminated = true;
+            for coord in snake.body.range(..snake.body.len().saturating_sub(1)) {
+                self.board.segments.remove(coord);
+            }
+        }
+        self.apply_shrink(true);
+        self.compute_threats();
+        self.undo_index += 1;
+        self.check_metadata();
+        Ok(())
+    }
+    /// Adds the hazards of the expected royale shrink when the move being advanced ends its turn,
+    /// or takes them back off when it's being undone.
+    fn apply_shrink(&mut self, grow: bool) {
+        let turn = self.turn + self.undo_index as u32 + 1;
+        let shrink = match &self.shrink {
+            Some(shrink) if shrink.turn == turn => shrink,
+            _ => return,
+        };
+        let damage = self.game.ruleset.settings.hazard_damage_per_turn;
+        for coord in shrink.squares.iter() {
+            if let Some(cell) = self.board.hazard_damage.get_mut(coord) {
+                *cell = grow.then_some(damage);
+            }
+        }
+    }
+    /// Whether a snake that just moved runs out of health, leaves the board, runs into a body or
+    /// loses a head-to-head.
+    pub(crate) fn collided(&self, snake: &Battlesnake) -> bool {
+        if snake.health <= 0 || !in_bounds(&snake.head, self.board.width, self.board.height) {
+            return true;
+        }
+        // Segments leave out tails and count heads, while collisions are with everything but heads
+        let mut bodies = self.board.segments.get(&snake.head).copied().unwrap_or(0) as i32;
+        for other in self.board.alive() {
+            if other.body.len() < 2 {
+                continue;
+            }
+            if other.head == snake.head {
+                bodies -= 1;
+            }
+            if other.body.back() == Some(&snake.head) {
+                bodies += 1;
+            }
+        }
+        if bodies > 0 {
+            return true;
+        }
+        // Snakes can't head-to-head with themselves
+        self.board.alive().any(|other| {
+            other.sid != snake.sid && other.head == snake.head && snake.length <= other.length
+        })
+    }
+    /// Takes back the last turn played with `advance`.
+    pub fn undo(&mut self) -> Result<(), EngineError> {
+        if self.undo_index == 0 {
+            return Err(EngineError::NothingToUndo);
+        }
+        let _timer = profiling::start(Phase::Undo);
+        let rules = self.rules();
+        self.undo_index -= 1;
+        self.apply_shrink(false);
+        // Bring back any snakes eliminated by the move
+        for sid in self.undo.eliminated_snakes[self.undo_index].drain(..) {
+            let snake = &mut self.board.snakes[sid.index()];
+            snake.eliminated = false;
+            for coord in snake.body.range(..snake.body.len().saturating_sub(1)) {
+                self.board.segments.add(coord);
+            }
+        }
+        // Add back any eaten food
+        for food in &self.undo.eaten_food[self.undo_index] {
+            self.board.add_food(*food);
+        }
+        // Undo the moves of the snakes that moved
+        for &(sid, tail) in &self.undo.previous_tails[self.undo_index] {
+            let board = &mut self.board;
+            let snake = &mut board.snakes[sid.index()];
+            let head = pop_head(&mut snake.body, &mut board.segments);
+            // Snake ate in the previous turn and needs to shrink an additional body part
+            if matches!(head, Some(head) if board.food_squares.contains(&head)) {
+                pop_tail(&mut snake.body, &mut board.segments);
+            }
+            rules.undo_post_move(snake, &mut board.segments);
+            push_tail(&mut snake.body, &mut board.segments, tail);
+            // One part snakes are left empty until their tail is back
+            snake.head = snake.body[0];
+            snake.length = snake.body.len() as u32;
+        }
+        for &(sid, health) in &self.undo.previous_health[self.undo_index] {
+            self.board.snakes[sid.index()].health = health;
+        }
+        self.compute_threats();
+        self.check_metadata();
+        Ok(())
+    }
+    /// Makes the moves so far permanent, as the API would send the next turn: eliminated snakes
+    /// leave the board and `you` catches up with our snake. Nothing before it can be undone.
+    pub(crate) fn commit(&mut self) {
+        self.you = self.you().clone();
+        self.board.snakes.retain(|snake| !snake.eliminated);
+        self.undo_index = 0;
+        self.intern_snakes();
+        self.compute_metadata();
+    }
+    /// Has the search grow the hazards as `schedule` expects this royale game to shrink next.
+    ///
+    /// Also plans where we have to be a few shrinks ahead, so the evaluation can head there in
+    /// time rather than stepping out of each new hazard as it appears.
+    pub fn predict_shrink(&mut self, schedule: &HazardSchedule) {
+        self.shrink = schedule.next_shrink(self);
+        self.safe_zone = schedule.safe_zone(self, SAFE_ZONE_SHRINKS);
+    }
+    /// Has the evaluation head for where `spawns` says food turns up, rather than the center.
+    pub fn predict_food(&mut self, spawns: &FoodSpawns) {
+        self.food_hotspot = spawns.hotspot();
+    }
+    /// Our snake, read from the board, where it stays when eliminated until the turn is committed.
+    /// Falls back to `you` as sent when our snake isn't on the board.
+    pub(crate) fn you(&self) -> &Battlesnake {
+        self.board
+            .snakes
+            .get(self.you.sid.index())
+            .filter(|snake| snake.sid == self.you.sid)
+            .unwrap_or(&self.you)
+    }
+    pub(crate) fn adjacent_coord(&self, coord: &Coord, dir: &Direction) -> Coord {
+        match self.board.neighbors.coords.get(coord) {
+            Some(neighbors) => neighbors[*dir as usize],
+            None => self.step(coord, dir),
+        }
+    }
+    /// The square one step away, worked out rather than looked up in `Board::neighbors`.
+    pub(crate) fn step(&self, coord: &Coord, dir: &Direction) -> Coord {
+        self.rules()
+            .step(coord, *dir, self.board.width, self.board.height)
+    }
+    /// The hooks for the rules of this game's mode.
+    pub(crate) fn rules(&self) -> &'static dyn RulesetHooks {
+        ruleset_hooks(&self.game.ruleset.name)
+    }
+    /// Steps between two squares ignoring snakes and hazards, across the edges in wrapped games.
+    pub fn grid_distance(&self, from: &Coord, to: &Coord) -> i32 {
+        if self.rules().wrapped() {
+            from.wrapped_distance(to, self.board.width, self.board.height)
+        } else {
+            from.manhattan_distance(to)
+        }
+    }
+    /// Direction of the step from one square to the one next to it, across the edges in wrapped
+    /// games, or `None` if they aren't next to each other.
+    pub fn direction_to(&self, from: &Coord, to: &Coord) -> Option<Direction> {
+        Direction::iter().find(|direction| self.adjacent_coord(from, direction) == *to)
+    }
+    /// Direction the snake moved last turn, or up if its body is still stacked.
+    pub(crate) fn current_direction(&self, snake: &Battlesnake) -> Direction {
+        if snake.body.len() < 2 {
+            return Direction::Up;
+        }
+        self.direction_to(&snake.body[1], &snake.body[0])
+            .unwrap_or(Direction::Up)
+    }
+    pub(crate) fn adjacent_moves(&self, coord: &Coord) -> [(Coord, Direction); 4] {
+        match self.board.neighbors.coords.get(coord) {
+            Some(neighbors) => DIRECTIONS.map(|dir| (neighbors[dir as usize], dir)),
+            None => DIRECTIONS.map(|dir| (self.step(coord, &dir), dir)),
+        }
+    }
+    pub(crate) fn valid_at(&self, coord: &Coord) -> bool {
+        in_bounds(coord, self.board.width, self.board.height)
+    }
+    fn safe_at(&self, coord: &Coord) -> bool {
+        if self.board.segments.occupied(coord) {
+            return false;
+        }
+        // Hazards that would finish us off are as bad as a body
+        match self.board.hazard_at(coord) {
+            Some(damage) => damage < self.you().health,
+            None => true,
+        }
+    }
+    pub(crate) fn viable(&self, coord: &Coord) -> bool {
+        self.valid_at(coord) && self.safe_at(coord)
+    }
+    /// Readies a state that was deserialized or built by hand to be simulated, by numbering its
+    /// snakes and computing its metadata. Called once, before the first `advance`.
+    pub fn init(&mut self) {
+        self.undo = UndoInfo::new();
+        self.intern_snakes();
+        self.compute_metadata();
+    }
+    /// Assigns every snake its `SnakeId` in board order. Our snake gets one past the last when
+    /// it isn't on the board, so it never shares one.
+    fn intern_snakes(&mut self) {
+        self.you.sid = SnakeId(self.board.snakes.len().min(MAX_SNAKES) as u8);
+        for (i, snake) in self.board.snakes.iter_mut().enumerate() {
+            snake.sid = SnakeId(i as u8);
+            if snake.id == self.you.id {
+                self.you.sid = snake.sid;
+            }
+        }
+    }
+    /// Recomputes the metadata from scratch, for a state that didn't come from `advance` or `undo`.
+    pub(crate) fn compute_metadata(&mut self) {
+        let _timer = profiling::start(Phase::Metadata);
+        let (width, height) = (self.board.width, self.board.height);
+        self.board.segments = self.count_segments();
+        // Food listed twice is only there to eat once
+        let mut listed: HashSet<Coord> = HashSet::new();
+        self.board.food.retain(|food| listed.insert(*food));
+        self.board.food_squares = self.mark_food();
+        let mut hazard_damage = std::mem::take(&mut self.board.hazard_damage);
+        hazard_damage.reset(width, height);
+        for hazard in &self.board.hazards {
+            let damage = self.game.ruleset.settings.hazard_damage_per_turn;
+            match hazard_damage.get_mut(hazard) {
+                Some(Some(total)) => *total += damage,
+                Some(cell) => *cell = Some(damage),
+                None => continue,
+            }
+        }
+        self.board.hazard_damage = hazard_damage;
+        self.board.sides = self.sides();
+        let wrapped = self.rules().wrapped();
+        let neighbors = &self.board.neighbors;
+        if neighbors.wrapped != wrapped
+            || neighbors.coords.width != width
+            || neighbors.coords.height != height
+        {
+            let mut coords: Grid<[Coord; 4]> = Grid::new(width, height);
+            for y in 0..height {
+                for x in 0..width {
+                    let coord = Coord { x, y };
+                    coords.set(&coord, DIRECTIONS.map(|dir| self.step(&coord, &dir)));
+                }
+            }
+            self.board.neighbors = Arc::new(Neighbors { wrapped, coords });
+        }
+        self.compute_threats();
+    }
+    fn count_segments(&self) -> Grid<u8> {
+        let mut segments: Grid<u8> = Grid::new(self.board.width, self.board.height);
+        for snake in self.board.alive() {
+            for coord in snake.body.range(..snake.body.len().saturating_sub(1)) {
+                segments.add(coord);
+            }
+        }
+        segments
+    }
+    fn mark_food(&self) -> Grid<bool> {
+        let mut food_squares: Grid<bool> = Grid::new(self.board.width, self.board.height);
+        for food in &self.board.food {
+            food_squares.set(food, true);
+        }
+        food_squares
+    }
+    /// Which side each snake plays for: in squad games, those that share our squad are
+    /// teammates.
+    fn sides(&self) -> Vec<Side> {
+        let squad = &self.you.details.squad;
+        let squads = self.game.ruleset.name == GameMode::Squad && !squad.is_empty();
+        self.board
+            .snakes
+            .iter()
+            .map(|snake| {
+                if snake.id == self.you.id {
+                    Side::Us
+                } else if squads && &snake.details.squad == squad {
+                    Side::Teammate
+                } else {
+                    Side::Enemy
+                }
+            })
+            .collect()
+    }
+    /// Finds the squares next to other snakes' heads. Only necks are looked at, so this is cheap
+    /// enough to redo every ply. Teammates are never to be killed, so their heads are only
+    /// avoided.
+    fn compute_threats(&mut self) {
+        let _timer = profiling::start(Phase::Metadata);
+        let mut stomps = std::mem::take(&mut self.board.stomps);
+        let mut avoids = std::mem::take(&mut self.board.avoids);
+        stomps.clear();
+        avoids.clear();
+        let you = self.you();
+        for snake in self.board.alive() {
+            if snake.sid == you.sid {
+                continue;
+            }
+            let neck = match snake.body.get(1) {
+                Some(neck) => neck,
+                None => continue,
+            };
+            for (adj_coord, _) in self.adjacent_moves(neck) {
+                if you.length <= snake.length || self.board.teammate(snake) {
+                    avoids.push(adj_coord);
+                } else {
+                    stomps.push(adj_coord);
+                }
+            }
+        }
+        self.board.stomps = stomps;
+        self.board.avoids = avoids;
+    }
+    /// Checks in debug builds that the segments kept up to date by `advance` and `undo` match a
+    /// recount, and that the neighbors were built for this mode. With the `invariants` feature,
+    /// checks the rest of `invariant_violations` too.
+    fn check_metadata(&self) {
+        debug_assert_eq!(self.board.segments, self.count_segments());
+        debug_assert_eq!(self.board.food_squares, self.mark_food());
+        debug_assert_eq!(self.board.neighbors.wrapped, self.rules().wrapped());
+        #[cfg(feature = "invariants")]
+        {
+            let violations = self.invariant_violations();
+            debug_assert!(
+                violations.is_empty(),
+                "simulator invariants broken {} turns into the search: {}",
+                self.undo_index,
+                violations.join("; ")
+            );
+        }
+    }
+    /// What's wrong with the state that `advance` and `undo` should have kept right, so corruption
+    /// shows where it happens rather than as a bizarre move turns later. Empty when consistent.
+    #[cfg_attr(not(any(test, feature = "invariants")), allow(dead_code))]
+    pub(crate) fn invariant_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        let mut ids: HashSet<&str> = HashSet::new();
+        for (i, snake) in self.board.snakes.iter().enumerate() {
+            if !ids.insert(&snake.id) {
+                violations.push(format!("more than one snake has id {:?}", snake.id));
+            }
+            if snake.sid.index() != i {
+                violations.push(format!(
+                    "snake {:?} at index {} is numbered {}",
+                    snake.id,
+                    i,
+                    snake.sid.index()
+                ));
+            }
+            if snake.body.front() != Some(&snake.head) {
+                violations.push(format!("snake {:?} has a head off its body", snake.id));
+            }
+            if snake.length as usize != snake.body.len() {
+                violations.push(format!(
+                    "snake {:?} has length {} but {} body parts",
+                    snake.id,
+                    snake.length,
+                    snake.body.len()
+                ));
+            }
+            if !snake.eliminated && !(1..=100).contains(&snake.health) {
+                violations.push(format!(
+                    "snake {:?} is alive with health {}",
+                    snake.id, snake.health
+                ));
+            }
+        }
+        if self.board.segments != self.count_segments() {
+            violations.push("obstacles don't match the bodies".to_owned());
+        }
+        let depth = self.undo.eliminated_snakes.len();
+        if self.undo_index > depth {
+            violations.push(format!(
+                "{} turns advanced with undo history for {}",
+                self.undo_index, depth
+            ));
+        }
+        // Eliminated snakes leave the board when a turn is committed, so any left were
+        // eliminated by a turn that can still be undone
+        let undoable: HashSet<SnakeId> = self.undo.eliminated_snakes[..self.undo_index.min(depth)]
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        for snake in self.board.snakes.iter().filter(|snake| snake.eliminated) {
+            if !undoable.contains(&snake.sid) {
+                violations.push(format!(
+                    "snake {:?} is eliminated by no turn that can be undone",
+                    snake.id
+                ));
+            }
+        }
+        violations
+    }
+    /// A random viable move from `coord`, preferring food. With nothing viable, a move that at
+    /// least stays on the board. Never back into the neck of the snake whose head is at `coord`.
+    pub(crate) fn random_valid_move(&self, coord: &Coord) -> (Coord, Direction) {
+        let mut valid_moves: SmallVec<[(Coord, Direction); 4]> = SmallVec::new();
+        let mut food_moves: SmallVec<[(Coord, Direction); 4]> = SmallVec::new();
+        let neck = self.neck_at(coord);
+
+        for direction in Direction::iter() {
+            let adjacent_coord = self.adjacent_coord(coord, &direction);
+            if Some(adjacent_coord) == neck || !self.viable(&adjacent_coord) {
+                continue;
+            }
+            valid_moves.push((adjacent_coord, direction));
+            if self.board.food_squares.contains(&adjacent_coord) {
+                food_moves.push((adjacent_coord, direction));
+            }
+        }
+
+        if !food_moves.is_empty() {
+            *food_moves.choose(&mut rand::thread_rng()).unwrap()
+        } else if !valid_moves.is_empty() {
+            *valid_moves.choose(&mut rand::thread_rng()).unwrap()
+        } else {
+            // Every move is fatal, but going off the board or into the neck is fatal for certain
+            let moves = self.adjacent_moves(coord);
+            moves
+                .into_iter()
+                .filter(|(adjacent_coord, _)| Some(*adjacent_coord) != neck)
+                .max_by_key(|(adjacent_coord, _)| self.valid_at(adjacent_coord))
+                .unwrap_or(moves[0])
+        }
+    }
+    /// The square behind the head of the snake whose head is at `coord`, or `None` if there's no
+    /// snake there or its body is stacked.
+    pub(crate) fn neck_at(&self, coord: &Coord) -> Option<Coord> {
+        let snake = self.board.alive().find(|snake| snake.head == *coord)?;
+        snake.body.get(1).copied().filter(|neck| neck != coord)
+    }
+    /// `direction`, unless it takes our snake back into its neck, which is never worth it, in which
+    /// case the move `random_valid_move` makes instead. Applied to every move we send.
+    pub fn legal_direction(&self, direction: Direction) -> Direction {
+        let head = self.you().head;
+        if self.neck_at(&head) == Some(self.adjacent_coord(&head, &direction)) {
+            let legal = self.random_valid_move(&head).1;
+            warn!(
+                "{:?} reverses into our neck, moving {:?} instead",
+                direction, legal
+            );
+            return legal;
+        }
+        direction
+    }
+    /// Kept for the tests and benchmarks, as the evaluation searches once from the head instead.
+    #[cfg_attr(not(any(test, feature = "bench")), allow(dead_code))]
+    pub(crate) fn shortest_distance(&self, start: &Coord, end: &Coord) -> Option<u32> {
+        let mut nodes: BinaryHeap<PriorityCoord> = BinaryHeap::new();
+        // Squares are visited once they have a distance
+        let mut distances: Grid<Option<u32>> = Grid::new(self.board.width, self.board.height);
+        nodes.push(PriorityCoord {
+            coord: *start,
+            priority: 0,
+        });
+        distances.set(start, Some(0));
+        while let Some(PriorityCoord { coord, priority: _ }) = nodes.pop() {
+            let distance = distances.get(&coord).copied().flatten().unwrap_or(0);
+            if coord == *end {
+                return Some(distance);
+            }
+            for (adj_coord, _) in self.adjacent_moves(&coord) {
+                if !self.viable(&adj_coord) {
+                    continue;
+                }
+                if distances.get(&adj_coord).copied().flatten().is_some() {
+                    continue;
+                }
+                distances.set(&adj_coord, Some(distance + 1));
+                nodes.push(PriorityCoord {
+                    coord: adj_coord,
+                    priority: distance + adj_coord.manhattan_distance(end) as u32,
+                })
+            }
+        }
+        None
+    }
+    /// Distances over viable squares from `start` to every square it can reach, found with a
+    /// single breadth-first search so an evaluation can answer all its path queries from it.
+    pub(crate) fn distances_from(&self, start: &Coord) -> Grid<Option<u32>> {
+        let mut nodes: VecDeque<Coord> = VecDeque::new();
+        let mut distances: Grid<Option<u32>> = Grid::new(self.board.width, self.board.height);
+        nodes.push_back(*start);
+        distances.set(start, Some(0));
+        while let Some(coord) = nodes.pop_front() {
+            let distance = distances.distance(&coord).unwrap_or(0);
+            for (adj_coord, _) in self.adjacent_moves(&coord) {
+                if !self.viable(&adj_coord) || distances.distance(&adj_coord).is_some() {
+                    continue;
+                }
+                distances.set(&adj_coord, Some(distance + 1));
+                nodes.push_back(adj_coord);
+            }
+        }
+        distances
+    }
+    /// Health our snake spends reaching every square over viable squares from `start`, along the
+    /// paths that spend the least: a point a move plus the damage of each hazard moved onto,
+    /// counting the squares the predicted royale shrink will have covered by the time they're
+    /// reached. Squares we'd run out of health on the way to are left out, and moving onto food
+    /// ends a path before its hazard can do damage.
+    ///
+    /// Off the hazards this is the move count, so it answers the same queries as `distances_from`
+    /// in health rather than moves.
+    pub(crate) fn health_spent_from(&self, start: &Coord) -> Grid<Option<u32>> {
+        let health = self.you().health.max(0) as u32;
+        let now = self.turn + self.undo_index as u32;
+        let damage_per_turn = self.game.ruleset.settings.hazard_damage_per_turn;
+        let pending = self.shrink.as_ref().filter(|shrink| shrink.turn > now);
+        let mut spent: Grid<Option<u32>> = Grid::new(self.board.width, self.board.height);
+        let mut nodes: BinaryHeap<Reverse<(u32, u32, i32, i32)>> = BinaryHeap::new();
+        spent.set(start, Some(0));
+        nodes.push(Reverse((0, 0, start.x, start.y)));
+        while let Some(Reverse((cost, moves, x, y))) = nodes.pop() {
+            let coord = Coord { x, y };
+            if spent.distance(&coord).is_some_and(|best| best < cost) {
+                continue;
+            }
+            for (adj_coord, _) in self.adjacent_moves(&coord) {
+                if !self.viable(&adj_coord) {
+                    continue;
+                }
+                // The shrink lands at the end of its turn, so only moves after it take its damage
+                let damage = if self.board.food_squares.contains(&adj_coord) {
+                    0
+                } else {
+                    match self.board.hazard_at(&adj_coord) {
+                        Some(damage) => damage,
+                        None => match pending {
+                            Some(shrink)
+                                if shrink.turn < now + moves + 1
+                                    && shrink.squares.contains(&adj_coord) =>
+                            {
+                                damage_per_turn
+                            }
+                            _ => 0,
+                        },
+                    }
+                };
+                let next = cost + 1 + damage.max(0) as u32;
+                if next >= health {
+                    continue;
+                }
+                if spent.distance(&adj_coord).is_none_or(|best| next < best) {
+                    spent.set(&adj_coord, Some(next));
+                    nodes.push(Reverse((next, moves + 1, adj_coord.x, adj_coord.y)));
+                }
+            }
+        }
+        spent
+    }
+    pub(crate) fn closest_food_distance(&self, coord: &Coord) -> Option<u32> {
+        self.closest_food_in(&self.distances_from(coord))
+    }
+    /// The food grouped into clusters, each food within `FOOD_CLUSTER_RADIUS` steps of another in
+    /// its cluster.
+    pub(crate) fn food_clusters(&self) -> Vec<Vec<Coord>> {
+        let mut food = self.board.food.clone();
+        // Undo puts eaten food back at the end of the list, so sort it for the same clusters
+        // every time
+        food.sort_by_key(|coord| (coord.x, coord.y));
+        let mut clusters: Vec<Vec<Coord>> = Vec::new();
+        while let Some(seed) = food.pop() {
+            let mut cluster = vec![seed];
+            let mut i = 0;
+            while i < cluster.len() {
+                let coord = cluster[i];
+                let (near, far): (Vec<Coord>, Vec<Coord>) = food
+                    .into_iter()
+                    .partition(|other| self.grid_distance(&coord, other) <= FOOD_CLUSTER_RADIUS);
+                cluster.extend(near);
+                food = far;
+                i += 1;
+            }
+            clusters.push(cluster);
+        }
+        clusters
+    }
+    /// The middle of the cluster with the most food we can reach, nearest first on a tie, given our
+    /// distances from `distances_from`. Only clusters of more than one food count, as a single
+    /// food is just the closest food.
+    pub(crate) fn richest_food_region(&self, distances: &Grid<Option<u32>>) -> Option<Coord> {
+        if self.board.food.len() < 2 {
+            return None;
+        }
+        self.food_clusters()
+            .into_iter()
+            .filter_map(|cluster| {
+                let reachable: Vec<(Coord, u32)> = cluster
+                    .into_iter()
+                    .filter_map(|food| Some((food, distances.distance(&food)?)))
+                    .collect();
+                let closest = reachable.iter().map(|&(_, distance)| distance).min()?;
+                Some((reachable, closest))
+            })
+            .filter(|(reachable, _)| reachable.len() > 1)
+            .max_by_key(|(reachable, closest)| (reachable.len(), Reverse(*closest)))
+            .map(|(reachable, _)| {
+                let count = reachable.len() as i32;
+                Coord {
+                    x: reachable.iter().map(|(food, _)| food.x).sum::<i32>() / count,
+                    y: reachable.iter().map(|(food, _)| food.y).sum::<i32>() / count,
+                }
+            })
+    }
+    /// Moves to `snake`'s tail, given distances from `distances_from`. A stacked tail is still
+    /// taken when it's reached, so it's chased through its neighbors and reached a move later.
+    pub(crate) fn tail_distance(
+        &self,
+        snake: &Battlesnake,
+        distances: &Grid<Option<u32>>,
+    ) -> Option<u32> {
+        let tail = snake.body.back()?;
+        if !snake.growing() {
+            return distances.distance(tail);
+        }
+        self.adjacent_moves(tail)
+            .iter()
+            .filter_map(|(coord, _)| distances.distance(coord))
+            .min()
+            .map(|distance| distance + 1)
+    }
+    pub(crate) fn closest_food_in(&self, distances: &Grid<Option<u32>>) -> Option<u32> {
+        self.board
+            .food
+            .iter()
+            .filter_map(|food| distances.distance(food))
+            .min()
+    }
+    /// Scores the head position by how far it is outside the planned royale safe zone, more
+    /// harshly as the zone's turn gets closer, and fully once there's no getting there in time.
+    pub(crate) fn safe_zone_penalty(&self) -> i32 {
+        let zone = match &self.safe_zone {
+            Some(zone) => zone,
+            None => return 0,
+        };
+        let distance = zone.distance(&self.you().head);
+        if distance == 0 {
+            return 0;
+        }
+        let turns_left = zone.turn.saturating_sub(self.turn + self.undo_index as u32) as i32;
+        if distance > turns_left {
+            return -distance * SAFE_ZONE_PENALTY;
+        }
+        -distance * SAFE_ZONE_PENALTY / (turns_left + 1)
+    }
+    /// Scores how much better we're managing health than the opponents when hazards cover at
+    /// least a quarter of the board, where attrition often decides the game.
+    ///
+    /// Health at a leaf is what's left after every hazard along the line searched, so comparing it
+    /// compares the trajectories, and heads in hazards show who's still paying for them.
+    pub(crate) fn attrition(&self) -> i32 {
+        let squares = (self.board.width * self.board.height) as usize;
+        if self.board.hazards.len() * 4 < squares {
+            return 0;
+        }
+        let you = self.you();
+        let opponents: Vec<&Battlesnake> = self
+            .board
+            .alive()
+            .filter(|snake| snake.sid != you.sid)
+            .collect();
+        if opponents.is_empty() {
+            return 0;
+        }
+        let health_gap = opponents
+            .iter()
+            .map(|opponent| you.health - opponent.health)
+            .sum::<i32>()
+            / opponents.len() as i32;
+        let in_sauce = self.board.hazard_at(&you.head).is_some();
+        let sauce: i32 = opponents
+            .iter()
+            .map(
+                |opponent| match (self.board.hazard_at(&opponent.head).is_some(), in_sauce) {
+                    (true, false) => SAUCE_PRESSURE,
+                    (false, true) => -SAUCE_PRESSURE,
+                    _ => 0,
+                },
+            )
+            .sum();
+        health_gap * ATTRITION_WEIGHT + sauce
+    }
+    /// Scores the head position by how many escape squares walls take away from it.
+    ///
+    /// Walls don't exist in wrapped mode, so the term is disabled there. In royale the
+    /// hazard boundary acts as a wall too: outside the hazard each adjacent hazard square
+    /// is penalized, while inside it each adjacent safe square is rewarded. Squares we
+    /// can't move to, such as bodies, are no way in or out of the hazard and don't count.
+    pub(crate) fn edge_safety(&self) -> i32 {
+        let head = &self.you().head;
+        match self.game.ruleset.name {
+            _ if self.rules().wrapped() => 0,
+            GameMode::Royale if !self.board.hazards.is_empty() => {
+                let in_hazard = self.board.hazard_at(head).is_some();
+                let mut result = 0;
+                for (adj_coord, _) in self.adjacent_moves(head) {
+                    if !self.valid_at(&adj_coord) {
+                        result -= EDGE_PENALTY;
+                        continue;
+                    } else if !self.viable(&adj_coord) {
+                        continue;
+                    }
+                    let adj_hazard = self.board.hazard_at(&adj_coord).is_some();
+                    if in_hazard && !adj_hazard {
+                        result += EDGE_PENALTY;
+                    } else if !in_hazard && adj_hazard {
+                        result -= EDGE_PENALTY;
+                    }
+                }
+                result
+            }
+            _ => {
+                let walls = self
+                    .adjacent_moves(head)
+                    .iter()
+                    .filter(|(coord, _)| !self.valid_at(coord))
+                    .count();
+                -(walls as i32) * EDGE_PENALTY
+            }
+        }
+    }
+    /// What kind of move stepping to `coord` would be for `snake`.
+    pub(crate) fn move_traits(&self, snake: &Battlesnake, coord: &Coord) -> MoveTraits {
+        let nearest_food = |from: &Coord| {
+            self.board
+                .food
+                .iter()
+                .map(|food| from.manhattan_distance(food))
+                .min()
+        };
+        let toward_food = match (nearest_food(coord), nearest_food(&snake.head)) {
+            (Some(after), Some(before)) => after < before,
+            _ => false,
+        };
+        let contested = self.adjacent_moves(coord).iter().any(|(adj_coord, _)| {
+            self.board
+                .alive()
+                .any(|other| other.sid != snake.sid && other.head == *adj_coord)
+        });
+        let edge = !self.rules().wrapped()
+            && (coord.x == 0
+                || coord.y == 0
+                || coord.x == self.board.width - 1
+                || coord.y == self.board.height - 1);
+        MoveTraits {
+            toward_food,
+            contested,
+            edge,
+        }
+    }
+    /// Chance that food spawns on a given empty square within `turns` turns, from the ruleset's
+    /// settings: a board short of `minimum_food` gets the shortfall straight away, and otherwise
+    /// food spawns on a random empty square with a `food_spawn_chance` percent chance each turn.
+    /// The search doesn't simulate spawns, so this is what a line searched on a board without
+    /// food can still count on.
+    pub(crate) fn food_chance(&self, turns: u32) -> f32 {
+        let settings = &self.game.ruleset.settings;
+        let squares = (self.board.width * self.board.height) as usize;
+        let occupied: usize = self.board.alive().map(|snake| snake.body.len()).sum();
+        let empty = squares
+            .saturating_sub(occupied + self.board.food.len())
+            .max(1) as f32;
+        let shortfall = settings
+            .minimum_food
+            .saturating_sub(self.board.food.len() as u32) as f32;
+        let per_turn = settings.food_spawn_chance.min(100) as f32 / 100.0 / empty;
+        let missed = (1.0 - (shortfall / empty).min(1.0)) * (1.0 - per_turn).powi(turns as i32);
+        1.0 - missed
+    }
+    /// Chance that food spawns on at least one of the `controlled` squares we control before we'd
+    /// starve, counting the turns searched to get here, in which no food spawned.
+    pub(crate) fn expected_food(&self, controlled: usize) -> f32 {
+        let you = self.you();
+        let turns = self.undo_index as u32 + you.health.max(0) as u32;
+        let chance = self.food_chance(turns);
+        1.0 - (1.0 - chance).powi(controlled as i32)
+    }
+    /// Scores the food that may yet spawn on the `controlled` squares we control.
+    pub(crate) fn food_expectation(&self, controlled: usize) -> i32 {
+        (self.expected_food(controlled) * FOOD_EXPECTATION_WEIGHT) as i32
+    }
+    /// Counts hungry, shorter opponents that can reach food, but only food we get to strictly
+    /// first by the first arrivals of `territory`. An opponent walled off from every food isn't
+    /// being denied anything by us.
+    pub(crate) fn food_denials(&self, territory: &TerritoryInfo) -> i32 {
+        let you = self.you();
+        if you.health < FOOD_DENIAL_MIN_HEALTH || self.board.food.is_empty() {
+            return 0;
+        }
+        let ours = |food: &&Coord| match territory.arrivals.get(food).copied().flatten() {
+            Some(arrival) => arrival.snake == you.sid.index() && !arrival.tied,
+            None => false,
+        };
+        self.board
+            .alive()
+            .filter(|snake| {
+                snake.sid != you.sid
+                    && snake.length < you.length
+                    && snake.health <= FOOD_DENIAL_HUNGRY_HEALTH
+            })
+            .filter(|snake| {
+                let distances = self.distances_from(&snake.head);
+                let mut reachable = self
+                    .board
+                    .food
+                    .iter()
+                    .filter(|food| distances.distance(food).is_some())
+                    .peekable();
+                reachable.peek().is_some() && reachable.all(|food| ours(&food))
+            })
+            .count() as i32
+    }
+    /// Classifies how our snake was eliminated, from the state sent with the end request.
+    pub fn death_cause(&self) -> DeathCause {
+        if self
+            .board
+            .snakes
+            .iter()
+            .any(|snake| snake.id == self.you.id)
+        {
+            return DeathCause::Survived;
+        }
+        let head = &self.you.head;
+        if !self.valid_at(head) {
+            return DeathCause::Wall;
+        }
+        if self.you.health <= 0 {
+            if self.board.hazard_at(head).is_some() {
+                return DeathCause::Hazard;
+            }
+            return DeathCause::Starvation;
+        }
+        if self.you.body.range(1..).any(|coord| coord == head) {
+            return DeathCause::SelfCollision;
+        }
+        for snake in &self.board.snakes {
+            if snake.head == *head {
+                return DeathCause::HeadToHead;
+            }
+            if snake.body.range(1..).any(|coord| coord == head) {
+                return DeathCause::BodyCollision;
+            }
+        }
+        DeathCause::Unknown
+    }
+    /// Picks the viable move closest to food, falling back to a random valid move.
+    pub(crate) fn greedy_move(&self) -> Direction {
+        let head = self.you().head;
+        let mut best: Option<(u32, Direction)> = None;
+        for (coord, direction) in self.adjacent_moves(&head) {
+            if !self.viable(&coord) {
+                continue;
+            }
+            if let Some(distance) = self.closest_food_distance(&coord) {
+                if best.is_none() || distance < best.unwrap().0 {
+                    best = Some((distance, direction));
+                }
+            }
+        }
+        match best {
+            Some((_, direction)) => direction,
+            None => self.random_valid_move(&head).1,
+        }
+    }
+    pub(crate) fn compute_territory_info(&self) -> TerritoryInfo {
+        let _timer = profiling::start(Phase::Territory);
+        let (width, height) = (self.board.width, self.board.height);
+        let mut controlled: Vec<usize> = vec![0; self.board.snakes.len()];
+        let mut nodes: VecDeque<(usize, u32, Coord)> = VecDeque::new();
+        let mut owners: Grid<Option<(usize, u32)>> = Grid::new(width, height);
+        let mut arrivals: Grid<Option<Arrival>> = Grid::new(width, height);
+        let you = self.you().sid.index();
+        for (i, snake) in self.board.snakes.iter().enumerate() {
+            if snake.eliminated {
+                continue;
+            }
+            nodes.push_back((i, 0, snake.head));
+            owners.set(&snake.head, Some((i, 0)));
+            arrivals.set(
+                &snake.head,
+                Some(Arrival {
+                    snake: i,
+                    distance: 0,
+                    tied: false,
+                }),
+            );
+            controlled[i] += 1;
+        }
+        while let Some((owner, distance, current_coord)) = nodes.pop_front() {
+            'outer: for (adj_coord, _dir) in self.adjacent_moves(&current_coord) {
+                if !self.viable(&adj_coord) {
+                    continue;
+                }
+                match arrivals.get_mut(&adj_coord) {
+                    Some(slot @ None) => {
+                        *slot = Some(Arrival {
+                            snake: owner,
+                            distance: distance + 1,
+                            tied: false,
+                        })
+                    }
+                    Some(Some(arrival))
+                        if arrival.distance == distance + 1
+                            && !self.board.allied(arrival.snake, owner) =>
+                    {
+                        arrival.tied = true;
+                        if owner == you {
+                            arrival.snake = you;
+                        }
+                    }
+                    _ => {}
+                }
+                if owners.get(&adj_coord).copied().flatten().is_some() {
+                    continue;
+                }
+                // Squares an enemy reaches just as soon are contested, so nobody controls them.
+                // Teammates don't fight over squares.
+                for (potential_controlled_coord, _potential_dir) in self.adjacent_moves(&adj_coord)
+                {
+                    if let Some(Some((potential_owner, visited_distance))) =
+                        owners.get(&potential_controlled_coord)
+                    {
+                        if !self.board.allied(*potential_owner, owner)
+                            && *visited_distance == distance
+                        {
+                            continue 'outer;
+                        }
+                    }
+                }
+                let new_distance = distance + 1;
+                nodes.push_back((owner, new_distance, adj_coord));
+                owners.set(&adj_coord, Some((owner, new_distance)));
+                controlled[owner] += 1;
+            }
+        }
+        TerritoryInfo {
+            owners,
+            arrivals,
+            controlled,
+            available: self.reachable_squares(&self.you().head),
+        }
+    }
+    /// Number of squares that can be reached from `start` over viable squares, counting `start`.
+    pub(crate) fn reachable_squares(&self, start: &Coord) -> usize {
+        // The narrowest rows that fit the board, as fills go a whole row at a time
+        let (width, height) = (self.board.width, self.board.height);
+        if let Some(empty) = SmallBitGrid::new(width, height) {
+            return self.flood_fill_count(empty, start);
+        }
+        if let Some(empty) = LargeBitGrid::new(width, height) {
+            return self.flood_fill_count(empty, start);
+        }
+        if let Some(empty) = HugeBitGrid::new(width, height) {
+            return self.flood_fill_count(empty, start);
+        }
+        self.distances_from(start)
+            .iter()
+            .filter(|(_, distance)| distance.is_some())
+            .count()
+    }
+    /// `reachable_squares` by flood filling `empty`, a grid the size of the board.
+    fn flood_fill_count<W: BitRow, const N: usize>(
+        &self,
+        empty: BitGrid<W, N>,
+        start: &Coord,
+    ) -> usize {
+        let mut free = empty;
+        for y in 0..self.board.height {
+            for x in 0..self.board.width {
+                let coord = Coord { x, y };
+                if self.safe_at(&coord) {
+                    free.insert(&coord);
+                }
+            }
+        }
+        let mut reached = empty;
+        reached.insert(start);
+        let wrapped = self.rules().wrapped();
+        reached.flood_fill(&free, wrapped).len()
+    }
+}
+
+/// Counts from enumerating every joint move to a fixed depth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Perft {
+    /// Paths through the tree that reach the depth, one per sequence of joint moves.
+    pub leaves: u64,
+    /// Distinct positions among those leaves, as different joint moves may lead to the same one.
+    pub states: u64,
+}
+
+/// Every combination of one move per snake, made one at a time as it's asked for rather than all
+/// at once. The last snake's move changes fastest. Owns its moves, so the state can be advanced
+/// while going through them.
+pub struct JointMoves {
+    choices: Vec<(SnakeId, SmallVec<[Coord; 4]>)>,
+    /// Which move of each snake comes next, or `None` once every combination has been made.
+    indexes: Option<Vec<usize>>,
+}
+
+impl Iterator for JointMoves {
+    type Item = Vec<(SnakeId, Coord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indexes = self.indexes.as_mut()?;
+        let moves = self
+            .choices
+            .iter()
+            .zip(indexes.iter())
+            .map(|((sid, coords), &i)| (*sid, coords[i]))
+            .collect();
+        // Step the last snake's move, carrying over to the snakes before it
+        let mut exhausted = true;
+        for (index, (_, coords)) in indexes.iter_mut().zip(&self.choices).rev() {
+            *index += 1;
+            if *index < coords.len() {
+                exhausted = false;
+                break;
+            }
+            *index = 0;
+        }
+        if exhausted {
+            self.indexes = None;
+        }
+        Some(moves)
+    }
+}
+
+impl GameState {
+    /// Every combination of one move per snake, where each snake moves to a square the search
+    /// would consider, or straight up to its death when it has none.
+    pub fn joint_moves(&self) -> JointMoves {
+        let _timer = profiling::start(Phase::MoveGeneration);
+        let choices = self.move_choices();
+        JointMoves {
+            indexes: Some(vec![0; choices.len()]),
+            choices,
+        }
+    }
+    /// The moves of each snake that `joint_moves` combines.
+    pub(crate) fn move_choices(&self) -> Vec<(SnakeId, SmallVec<[Coord; 4]>)> {
+        self.board
+            .alive()
+            .map(|snake| {
+                let mut coords: SmallVec<[Coord; 4]> = self
+                    .adjacent_moves(&snake.head)
+                    .into_iter()
+                    .map(|(coord, _)| coord)
+                    .filter(|coord| self.viable(coord))
+                    .collect();
+                if coords.is_empty() {
+                    coords.push(self.adjacent_coord(&snake.head, &Direction::Up));
+                }
+                (snake.sid, coords)
+            })
+            .collect()
+    }
+    /// Whether the game has ended: with no snakes left in solo, or one at most otherwise.
+    pub fn game_over(&self) -> bool {
+        let alive = self.board.alive().count();
+        if self.rules().last_snake_wins() {
+            alive <= 1
+        } else {
+            alive == 0
+        }
+    }
+    /// Zobrist key of the position: the snakes, their health, the food and the hazards, with our
+    /// snake first and the others in board order. The same position gets the same key in any game,
+    /// on any build, so keys can be compared across recordings.
+    pub fn position_key(&self) -> u64 {
+        let mut key = 0;
+        // Eliminated snakes stay where they died, which doesn't make the position any different
+        let ours = self.board.alive().filter(|snake| snake.id == self.you.id);
+        let others = self.board.alive().filter(|snake| snake.id != self.you.id);
+        for (slot, snake) in ours.chain(others).enumerate() {
+            for (i, part) in snake.body.iter().enumerate() {
+                key ^= zobrist(ZobristFeature::Body, slot, i, part);
+            }
+            key ^= zobrist(
+                ZobristFeature::Health,
+                slot,
+                snake.health as usize,
+                &Coord::default(),
+            );
+        }
+        for food in &self.board.food {
+            key ^= zobrist(ZobristFeature::Food, 0, 0, food);
+        }
+        // Hazards stack, so each gets a key for how many times over it's been listed
+        let mut stacks: HashMap<Coord, usize> = HashMap::new();
+        for hazard in &self.board.hazards {
+            let stack = stacks.entry(*hazard).or_insert(0);
+            key ^= zobrist(ZobristFeature::Hazard, 0, *stack, hazard);
+            *stack += 1;
+        }
+        key
+    }
+    fn perft_walk(&mut self, depth: u32, leaves: &mut u64, states: &mut HashSet<u64>) {
+        if depth == 0 {
+            *leaves += 1;
+            states.insert(self.position_key());
+            return;
+        }
+        if self.game_over() {
+            return;
+        }
+        for moves in self.joint_moves() {
+            if self.advance(&moves).is_err() {
+                return;
+            }
+            self.perft_walk(depth - 1, leaves, states);
+            let _ = self.undo();
+        }
+    }
+}
+
+/// What a Zobrist key stands for, alongside a square.
+#[derive(Debug, Clone, Copy)]
+enum ZobristFeature {
+    Body = 1,
+    Health = 2,
+    Food = 3,
+    Hazard = 4,
+}
+
+/// Key of one feature of a position: a snake's `index`th body part or its health of `index`, or the
+/// food or `index`th stacked hazard on `coord`. Keys are mixed from the feature with SplitMix64
+/// rather than drawn from a random table, so they're the same on every build.
+fn zobrist(feature: ZobristFeature, slot: usize, index: usize, coord: &Coord) -> u64 {
+    let mut z = (feature as u64) << 56
+        | (slot as u64 & 0xff) << 48
+        | (index as u64 & 0xffff) << 32
+        | (coord.x as u64 & 0xffff) << 16
+        | (coord.y as u64 & 0xffff);
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Enumerates every joint move to `depth` turns, stopping at finished games, and counts the
+/// leaves and distinct positions reached. Depths beyond the undo buffers are cut to fit.
+pub fn perft(mut gs: GameState, depth: u32) -> Perft {
+    gs.init();
+    let depth = depth.min(gs.undo.previous_tails.len() as u32);
+    let mut leaves = 0;
+    let mut states: HashSet<u64> = HashSet::new();
+    gs.perft_walk(depth, &mut leaves, &mut states);
+    Perft {
+        leaves,
+        states: states.len() as u64,
+    }
+}
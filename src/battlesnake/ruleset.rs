@@ -0,0 +1,93 @@
+//! What each game mode changes about the rules, as hooks the simulator calls at each phase of a
+//! turn, so a new mode is a new set of hooks here rather than checks across `rules`.
+
+use super::*;
+
+/// The phases of a turn a game mode can change, with the standard rules as the defaults.
+///
+/// A turn runs the phases in order: every snake's head moves to where `step` lands and its tail
+/// follows, `post_move` charges the snake for the move, snakes that didn't eat take
+/// `hazard_phase` damage, and then every snake `eliminated` says is out is eliminated at once.
+/// Undoing a turn runs `undo_post_move` in place of `post_move`.
+pub(crate) trait RulesetHooks: Sync {
+    /// Pre-move: the square a step from `coord` lands on, which may be off the board.
+    fn step(&self, coord: &Coord, direction: Direction, _width: i32, _height: i32) -> Coord {
+        coord.step(direction)
+    }
+    /// Whether `step` crosses the edges of the board, so there are no walls to run into.
+    fn wrapped(&self) -> bool {
+        false
+    }
+    /// Post-move: what moving costs a snake whose head has moved and tail has followed, before
+    /// it eats.
+    fn post_move(&self, snake: &mut Battlesnake, _segments: &mut Grid<u8>) {
+        snake.health -= 1;
+    }
+    /// Takes back what `post_move` did to the body of a snake whose head has been taken back,
+    /// before its tail is put back. Health is restored separately.
+    fn undo_post_move(&self, _snake: &mut Battlesnake, _segments: &mut Grid<u8>) {}
+    /// Hazard phase: what the hazards on its head do to a snake that didn't eat.
+    fn hazard_phase(&self, snake: &mut Battlesnake, damage: i32) {
+        snake.health -= damage;
+    }
+    /// Elimination phase: whether a snake that just moved is out of the game.
+    fn eliminated(&self, gs: &GameState, snake: &Battlesnake) -> bool {
+        gs.collided(snake)
+    }
+    /// Whether the game is won by the last snake left, rather than played until nobody is.
+    fn last_snake_wins(&self) -> bool {
+        true
+    }
+}
+
+/// The standard rules, which royale and squad games play by too: hazard shrinks are predicted by
+/// `HazardSchedule` rather than simulated, and squad teammates collide as anyone else does.
+struct StandardRules;
+
+impl RulesetHooks for StandardRules {}
+
+/// Solo games go on until the one snake is out.
+struct SoloRules;
+
+impl RulesetHooks for SoloRules {
+    fn last_snake_wins(&self) -> bool {
+        false
+    }
+}
+
+/// Snakes grow every turn in constrictor and never go hungry.
+struct ConstrictorRules;
+
+impl RulesetHooks for ConstrictorRules {
+    fn post_move(&self, snake: &mut Battlesnake, segments: &mut Grid<u8>) {
+        if let Some(&tail) = snake.body.back() {
+            push_tail(&mut snake.body, segments, tail);
+        }
+    }
+    fn undo_post_move(&self, snake: &mut Battlesnake, segments: &mut Grid<u8>) {
+        pop_tail(&mut snake.body, segments);
+    }
+}
+
+/// Moves off one edge of a wrapped board come back on at the opposite edge.
+struct WrappedRules;
+
+impl RulesetHooks for WrappedRules {
+    fn step(&self, coord: &Coord, direction: Direction, width: i32, height: i32) -> Coord {
+        coord.step_wrapped(direction, width, height)
+    }
+    fn wrapped(&self) -> bool {
+        true
+    }
+}
+
+/// The hooks of a game mode. Maps only change where snakes, food and hazards start and spawn,
+/// which the state already holds, so they don't need hooks of their own.
+pub(crate) fn ruleset_hooks(mode: &GameMode) -> &'static dyn RulesetHooks {
+    match mode {
+        GameMode::Standard | GameMode::Royale | GameMode::Squad => &StandardRules,
+        GameMode::Solo => &SoloRules,
+        GameMode::Constrictor => &ConstrictorRules,
+        GameMode::Wrapped => &WrappedRules,
+    }
+}
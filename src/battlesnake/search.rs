@@ -0,0 +1,768 @@
+//! Iterative deepening minimax over the joint moves of every snake.
+
+use super::*;
+use crate::profiling::{self, Phase};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::Serialize;
+use smallvec::SmallVec;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Score {
+    pub(crate) min: bool,
+    pub(crate) max: bool,
+    pub(crate) center_dist: i32,
+    pub(crate) edge_safety: i32,
+    pub(crate) safe_zone: i32,
+    pub(crate) tail_dist: i32,
+    pub(crate) food_dist: i32,
+    pub(crate) food_expectation: i32,
+    pub(crate) length: i32,
+    pub(crate) snake_stomps: i32,
+    pub(crate) snake_avoids: i32,
+    pub(crate) board_control: i32,
+    pub(crate) food_denial: i32,
+    pub(crate) cutoff: i32,
+    pub(crate) partition: i32,
+    pub(crate) squeeze: i32,
+    pub(crate) attrition: i32,
+    pub(crate) survival: i32,
+}
+
+impl Score {
+    pub(crate) fn new() -> Self {
+        Score {
+            min: false,
+            max: false,
+            center_dist: 0,
+            edge_safety: 0,
+            safe_zone: 0,
+            tail_dist: 0,
+            food_dist: 0,
+            food_expectation: 0,
+            length: 0,
+            snake_stomps: 0,
+            snake_avoids: 0,
+            board_control: 0,
+            food_denial: 0,
+            cutoff: 0,
+            partition: 0,
+            squeeze: 0,
+            attrition: 0,
+            survival: 0,
+        }
+    }
+    pub(crate) fn sum(&self) -> i32 {
+        if self.min {
+            return i32::MIN;
+        } else if self.max {
+            return i32::MAX;
+        }
+        let mut result: i32 = 0;
+        result += self.center_dist;
+        result += self.edge_safety;
+        result += self.safe_zone;
+        result += self.tail_dist;
+        result += self.food_dist;
+        result += self.food_expectation;
+        result += self.length;
+        result += self.snake_stomps;
+        result += self.snake_avoids;
+        result += self.board_control;
+        result += self.food_denial;
+        result += self.cutoff;
+        result += self.partition;
+        result += self.squeeze;
+        result += self.attrition;
+        result += self.survival;
+        result
+    }
+}
+
+/// Why the search stopped trying moves at a node before it had tried them all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cutoff {
+    /// Time or the node budget ran out.
+    Timeout,
+    /// We already had a move at least as good elsewhere.
+    Alpha,
+    /// An opponent already had a reply at least as bad for us elsewhere.
+    Beta,
+}
+
+/// How well the search ordered moves, across every iteration, for tuning the ordering: the
+/// sooner the move that cuts a node off is tried, the less is searched. Only nodes with more than
+/// one move to try are counted, as the order of one move can't matter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct OrderingStats {
+    /// Nodes cut off by alpha-beta, whether or not moves were left to try.
+    pub cutoffs: u32,
+    /// Cutoffs caused by the first move tried.
+    pub first_move_cutoffs: u32,
+    /// Sum over every cutoff of the position of the move that caused it, the first being 0.
+    pub cutoff_index_sum: u32,
+    /// Nodes whose moves were ordered by a hint: the last iteration's root scores heads-up, an
+    /// opponent's tendencies, or closeness to the other snakes in constrictor.
+    pub hinted: u32,
+    /// Hinted nodes where the move the hint put first turned out best.
+    pub hint_hits: u32,
+}
+
+impl OrderingStats {
+    /// Share of cutoffs caused by the first move tried.
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        self.first_move_cutoffs as f64 / self.cutoffs.max(1) as f64
+    }
+    /// Average position of the move that caused a cutoff, the first being 0.
+    pub fn average_cutoff_index(&self) -> f64 {
+        self.cutoff_index_sum as f64 / self.cutoffs.max(1) as f64
+    }
+    /// Share of hinted nodes where the hint put the best move first.
+    pub fn hint_hit_rate(&self) -> f64 {
+        self.hint_hits as f64 / self.hinted.max(1) as f64
+    }
+    /// Counts a node with `moves` to try, cut off by the one at `cutoff` if it was cut off, and
+    /// whose best move was at `best`.
+    fn record(&mut self, moves: usize, cutoff: Option<usize>, hinted: bool, best: Option<usize>) {
+        if moves < 2 {
+            return;
+        }
+        if let Some(index) = cutoff {
+            self.cutoffs += 1;
+            self.cutoff_index_sum += index as u32;
+            if index == 0 {
+                self.first_move_cutoffs += 1;
+            }
+        }
+        if hinted {
+            self.hinted += 1;
+            if best == Some(0) {
+                self.hint_hits += 1;
+            }
+        }
+    }
+}
+
+/// A node of the search tree, reached by one snake's move.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TreeNode {
+    /// Id of the snake that moved, or null at the root.
+    pub snake: Option<String>,
+    /// Its move. Example: "up"
+    #[serde(rename = "move")]
+    pub direction: Option<Direction>,
+    /// Score sum the node was given.
+    pub score: i32,
+    /// Leaf positions evaluated below the node, itself included.
+    pub nodes: u32,
+    /// Why the moves after the last child weren't searched, if they weren't.
+    pub cutoff: Option<Cutoff>,
+    pub children: Vec<TreeNode>,
+    /// Leaves evaluated before the node was entered.
+    #[serde(skip)]
+    terminals: u32,
+}
+
+/// The tree explored by the last completed iteration of a search.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SearchTree {
+    /// Depth of the iteration, in plies.
+    pub depth: u32,
+    /// Whether nodes were left out past the limit.
+    pub truncated: bool,
+    pub root: TreeNode,
+}
+
+/// Most nodes of a search tree kept, which is a few megabytes of JSON.
+pub(crate) const SEARCH_TREE_NODES: usize = 20_000;
+
+/// Builds the tree of each iteration as the search walks it, up to a number of nodes.
+#[derive(Debug, Clone)]
+pub(crate) struct TreeRecorder {
+    limit: usize,
+    recorded: usize,
+    truncated: bool,
+    /// Nodes from the root down to the current one, `None` for those past the limit.
+    stack: Vec<Option<TreeNode>>,
+    root: Option<TreeNode>,
+    pub(crate) completed: Option<SearchTree>,
+}
+
+impl TreeRecorder {
+    pub(crate) fn new(limit: usize) -> Self {
+        TreeRecorder {
+            limit,
+            recorded: 0,
+            truncated: false,
+            stack: Vec::new(),
+            root: None,
+            completed: None,
+        }
+    }
+    fn enter(&mut self, terminals: u32) {
+        if self.recorded >= self.limit {
+            self.truncated = true;
+            self.stack.push(None);
+            return;
+        }
+        self.recorded += 1;
+        self.stack.push(Some(TreeNode {
+            snake: None,
+            direction: None,
+            score: 0,
+            nodes: 0,
+            cutoff: None,
+            children: Vec::new(),
+            terminals,
+        }));
+    }
+    fn cut(&mut self, cutoff: Cutoff) {
+        if let Some(Some(node)) = self.stack.last_mut() {
+            node.cutoff = Some(cutoff);
+        }
+    }
+    fn leave(&mut self, mover: Option<(String, Direction)>, score: i32, terminals: u32) {
+        let mut node = match self.stack.pop() {
+            Some(Some(node)) => node,
+            _ => return,
+        };
+        if let Some((snake, direction)) = mover {
+            node.snake = Some(snake);
+            node.direction = Some(direction);
+        }
+        node.score = score;
+        node.nodes = terminals - node.terminals;
+        match self.stack.last_mut() {
+            Some(Some(parent)) => parent.children.push(node),
+            Some(None) => {}
+            None => self.root = Some(node),
+        }
+    }
+    /// Keeps the tree of the iteration just completed, and starts over for the next.
+    fn complete(&mut self, depth: u32) {
+        if let Some(root) = self.root.take() {
+            self.completed = Some(SearchTree {
+                depth,
+                truncated: self.truncated,
+                root,
+            });
+        }
+        self.recorded = 0;
+        self.truncated = false;
+    }
+    fn discard(&mut self) {
+        self.root = None;
+        self.recorded = 0;
+        self.truncated = false;
+    }
+}
+
+/// A root move with its score and the line the search expects to follow it.
+pub(crate) type RootLine = (Direction, Score, Vec<(SnakeId, Coord)>);
+
+pub struct Search {
+    tree_depth: u32,
+    pub(crate) move_depth: i32,
+    pub(crate) iteration_reached: u32,
+    advances: u32,
+    undos: u32,
+    terminals: u32,
+    pub(crate) best_direction: Direction,
+    pub(crate) best_score: Score,
+    /// Moves of every snake along the best line, in search order.
+    pub(crate) best_pv: Vec<(SnakeId, Coord)>,
+    /// Score and expected line of each root move in the last completed iteration.
+    pub(crate) root_scores: Vec<RootLine>,
+    iteration_root_scores: Vec<RootLine>,
+    /// Moves made from the root down to the current node, the last of which are applied on
+    /// every full round.
+    path: Vec<(SnakeId, Coord)>,
+    /// Best line found below each tree depth, reused from node to node.
+    pv_table: Vec<Vec<(SnakeId, Coord)>>,
+    pub(crate) nodes: u32,
+    pub(crate) search_time: u128,
+    pub(crate) timeout: u128,
+    /// Hard stop measured from request arrival, on top of the per-search timeout.
+    pub(crate) deadline: Option<Instant>,
+    /// Nodes evaluated, across iterations, after which the search stops whatever the time.
+    pub(crate) node_budget: Option<u32>,
+    pub(crate) snake_order: Vec<SnakeId>,
+    pub(crate) evaluate_fn: fn(&GameState, i32) -> Score,
+    /// Deepest leaf, in turns, scored with `evaluate_fn`. Deeper leaves are scored with
+    /// `basic_evaluate`, which skips the territory computation, so searches can go deeper. Set
+    /// for the board size unless configured.
+    pub(crate) full_evaluation_depth: Option<i32>,
+    /// Tendencies of the opponents seen so far in the game, by `SnakeId`, which order their moves
+    /// so the likely ones are searched first.
+    pub(crate) tendencies: Vec<Option<Tendencies>>,
+    /// Level of the per-iteration search logs.
+    pub(crate) log_level: log::Level,
+    /// Records the tree each iteration explores, when set.
+    pub(crate) tree: Option<TreeRecorder>,
+    /// The only root move searched, when set, for scoring root moves on their own.
+    pub(crate) root_move: Option<Direction>,
+    /// The opponent we're squeezing and the room it had at the root, in duels and endgames.
+    pub(crate) squeeze: Option<(SnakeId, usize)>,
+    pub(crate) ordering: OrderingStats,
+}
+
+/// Score for each square of room the squeezed opponent loses along the line searched.
+const SQUEEZE_WEIGHT: i32 = 100;
+
+/// The opponent closest to our head and the room it has, once squeezing it is what wins the game:
+/// when it's the only one left, or when bodies take up half the board.
+fn squeeze_target(gs: &GameState) -> Option<(SnakeId, usize)> {
+    let you = gs.you();
+    let duel = gs.board.alive().count() == 2;
+    let occupied: usize = gs.board.alive().map(|snake| snake.body.len()).sum();
+    let endgame = occupied * 2 >= (gs.board.width * gs.board.height) as usize;
+    if !duel && !endgame {
+        return None;
+    }
+    let opponent = gs
+        .board
+        .alive()
+        .filter(|snake| snake.sid != you.sid)
+        .min_by_key(|snake| snake.head.manhattan_distance(&you.head))?;
+    Some((opponent.sid, gs.reachable_squares(&opponent.head)))
+}
+
+thread_local! {
+    /// Moves from the root to where the last search on this thread panicked.
+    static PANIC_LINE: RefCell<Vec<(SnakeId, Coord)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The moves from the root to where the last search on this thread panicked, if one has since
+/// it was last taken.
+pub(crate) fn take_panic_line() -> Vec<(SnakeId, Coord)> {
+    PANIC_LINE.with(|line| std::mem::take(&mut *line.borrow_mut()))
+}
+
+impl Drop for Search {
+    fn drop(&mut self) {
+        // Unwinding from a panic, the line being searched is what a crash dump needs to replay it
+        if std::thread::panicking() {
+            let path = std::mem::take(&mut self.path);
+            PANIC_LINE.with(|line| *line.borrow_mut() = path);
+        }
+    }
+}
+
+/// Search time of a move request when nothing cuts it shorter, in milliseconds.
+pub(crate) const SEARCH_TIMEOUT: u128 = 425;
+
+impl Search {
+    /// A search for our move in `gs`, which has to have been initialized with `GameState::init`.
+    pub fn new(gs: &GameState) -> Self {
+        let mut best_score = Score::new();
+        best_score.min = true;
+        let you = gs.you();
+        let mut move_order: Vec<SnakeId> = Vec::new();
+        move_order.push(you.sid);
+        for snake in gs.board.alive() {
+            if snake.sid == you.sid {
+                continue;
+            }
+            move_order.push(snake.sid);
+        }
+        let mut evaluate_fn: fn(&GameState, i32) -> Score = territory_evaluate;
+        if gs.game.ruleset.name == GameMode::Constrictor {
+            evaluate_fn = constrictor_evaluate;
+        } else if move_order.len() > 4 {
+            evaluate_fn = basic_leaf_evaluate;
+        } else if move_order.len() == 2 {
+            // Heads-up, the opponent minimizing our score is no longer an assumption
+            evaluate_fn = duel_evaluate;
+        }
+        Search {
+            tree_depth: 0,
+            move_depth: 0,
+            iteration_reached: 1,
+            advances: 0,
+            undos: 0,
+            terminals: 0,
+            best_direction: gs.random_valid_move(&you.head).1,
+            best_score,
+            best_pv: Vec::new(),
+            root_scores: Vec::new(),
+            iteration_root_scores: Vec::new(),
+            path: Vec::new(),
+            pv_table: Vec::new(),
+            nodes: 0,
+            search_time: 0,
+            timeout: SEARCH_TIMEOUT,
+            deadline: None,
+            node_budget: None,
+            snake_order: move_order,
+            evaluate_fn,
+            full_evaluation_depth: gs.board.size().full_evaluation_depth(),
+            tendencies: Vec::new(),
+            log_level: log::Level::Debug,
+            tree: None,
+            root_move: None,
+            squeeze: squeeze_target(gs),
+            ordering: OrderingStats::default(),
+        }
+    }
+    /// Stops searching at `deadline`, as well as when the search's own timeout runs out.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+    /// Searches a ply deeper at a time, up to `max_depth` or until time runs out, keeping the best
+    /// move of the last depth completed. Leaves `gs` as it found it.
+    pub fn iterative_deepening(&mut self, gs: &mut GameState, max_depth: u32) {
+        let start = Instant::now();
+        for i in 1..=max_depth {
+            let _iteration = tracing::info_span!("iteration", depth = i).entered();
+            self.iteration_root_scores.clear();
+            self.tree_enter();
+            let score =
+                self.minimax_alphabeta(gs, gs.you.sid, gs.you.sid, start, i, i32::MIN, i32::MAX);
+            self.tree_leave(gs, None, &score);
+            self.nodes += self.terminals;
+            let debug_header = format!("{} Depth {:?} {}", "#".repeat(75), i, "#".repeat(25));
+            if i <= 20 {
+                log::log!(self.log_level, "\n{}", debug_header);
+                log::log!(
+                    self.log_level,
+                "Advances: {:?} | Undos: {:?} | Terminals: {:?} | Best Direction: {:?} | Best Score Sum: {:?}",
+                self.advances,
+                self.undos,
+                self.terminals,
+                self.best_direction,
+                self.best_score.sum()
+            );
+                log::log!(self.log_level, "Sum: {:?}\n{:?}", score.sum(), score);
+                log::log!(
+                    self.log_level,
+                    "Best Sum: {:?}\n{:?}",
+                    self.best_score.sum(),
+                    self.best_score
+                );
+                log::log!(
+                    self.log_level,
+                    "PV: {:?}\n{}",
+                    self.pv_table[0],
+                    "#".repeat(debug_header.len())
+                );
+            }
+            if self.time_check(start) {
+                if let Some(tree) = &mut self.tree {
+                    tree.discard();
+                }
+                break;
+            }
+            if let Some(tree) = &mut self.tree {
+                tree.complete(i);
+            }
+            if score.sum() > self.best_score.sum() && self.advances > 0 {
+                self.best_score = score;
+            }
+            self.root_scores = self.iteration_root_scores.clone();
+            self.advances = 0;
+            self.undos = 0;
+            self.terminals = 0;
+            self.tree_depth = 0;
+            self.move_depth = 0;
+            self.iteration_reached = i;
+        }
+        self.search_time = start.elapsed().as_millis();
+        if self.best_score.sum() == i32::MIN {
+            warn!("unable to find a move!");
+        }
+    }
+    fn time_check(&self, start: Instant) -> bool {
+        let now = Instant::now();
+        now.duration_since(start).as_millis() > self.timeout
+            || matches!(self.deadline, Some(deadline) if now >= deadline)
+            || matches!(self.node_budget, Some(budget) if self.nodes + self.terminals >= budget)
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn minimax_alphabeta(
+        &mut self,
+        gs: &mut GameState,
+        maximizer: SnakeId,
+        current_id: SnakeId,
+        start: Instant,
+        depth: u32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> Score {
+        let mut score = Score::new();
+        let ply = self.tree_depth as usize;
+        if self.pv_table.len() <= ply {
+            self.pv_table.resize_with(ply + 1, Vec::new);
+        }
+        self.pv_table[ply].clear();
+
+        if maximizer == current_id {
+            score.min = true;
+        } else {
+            score.max = true;
+        }
+
+        if self.time_check(start) {
+            self.tree_cut(Cutoff::Timeout);
+            score.min = true;
+            return score;
+        }
+
+        if depth == 0 {
+            self.terminals += 1;
+            return self.evaluate(gs);
+        }
+
+        let mut hinted = false;
+        let viable_moves = {
+            let _timer = profiling::start(Phase::MoveGeneration);
+            let mut viable_moves: SmallVec<[(Coord, Direction); 4]> = SmallVec::new();
+
+            if let Some(snake) = gs.board.snake(current_id) {
+                viable_moves.extend(
+                    gs.adjacent_moves(&snake.head)
+                        .into_iter()
+                        .filter(|(coord, _)| gs.viable(coord)),
+                );
+                trace!(
+                "Current Depth {:?} | Tree Depth {:?} | Current ID: {:?} | Viable Moves: {:?} | Pending Moves: {:?}",
+                self.tree_depth,
+                depth,
+                current_id,
+                viable_moves,
+                self.path,
+            );
+                // If a snake has no viable moves, we make a random move
+                if viable_moves.is_empty() {
+                    viable_moves.push(gs.random_valid_move(&snake.head));
+                }
+                if let (0, Some(root_move)) = (self.tree_depth, self.root_move) {
+                    viable_moves.retain(|(_, direction)| *direction == root_move);
+                }
+                // In constrictor, moves toward the other snakes cut off their room, so try them first
+                if gs.game.ruleset.name == GameMode::Constrictor {
+                    hinted = true;
+                    viable_moves.sort_by_key(|(coord, _)| {
+                        gs.board
+                            .alive()
+                            .filter(|other| other.sid != current_id)
+                            .map(|other| coord.manhattan_distance(&other.head))
+                            .min()
+                    });
+                }
+                // Heads-up, try our moves best first as the last iteration scored them, so the
+                // rest are cut off sooner and the search gets deeper
+                if self.tree_depth == 0 && self.snake_order.len() == 2 {
+                    hinted |= !self.root_scores.is_empty();
+                    let root_scores = &self.root_scores;
+                    viable_moves.sort_by_key(|(_, direction)| {
+                        Reverse(
+                            root_scores
+                                .iter()
+                                .find(|(root_direction, _, _)| root_direction == direction)
+                                .map_or(i32::MIN, |(_, score, _)| score.sum()),
+                        )
+                    });
+                }
+                // Search the moves the opponent tends to make first, for earlier cutoffs
+                if let Some(Some(tendencies)) = self.tendencies.get(current_id.index()) {
+                    if current_id != maximizer {
+                        let straight = gs.current_direction(snake);
+                        if tendencies.timing_out() {
+                            // The engine repeats the last move of a snake that times out
+                            viable_moves.clear();
+                            viable_moves
+                                .push((gs.adjacent_coord(&snake.head, &straight), straight));
+                        } else if viable_moves.len() > 1 {
+                            hinted = true;
+                            viable_moves.sort_by_key(|(coord, _)| {
+                                Reverse(tendencies.likelihood(&gs.move_traits(snake, coord)))
+                            });
+                            // A slow snake may time out, so its last move is likelier still
+                            if tendencies.slow() {
+                                if let Some(i) =
+                                    viable_moves.iter().position(|(_, dir)| *dir == straight)
+                                {
+                                    viable_moves[..=i].rotate_right(1);
+                                }
+                            }
+                        }
+                    }
+                }
+            } else {
+                // Push a placeholder move to keep exploring the tree when a snake's been eliminated
+                viable_moves.push((Coord { x: -1, y: -1 }, Direction::Down));
+            }
+            viable_moves
+        };
+
+        let snake_order_index = (self.tree_depth as usize + 1) % self.snake_order.len();
+        let next_id = self.snake_order[snake_order_index];
+
+        let moves = viable_moves.len();
+        let (mut best, mut cutoff) = (None, None);
+        for (index, (coord, direction)) in viable_moves.into_iter().enumerate() {
+            self.path.push((current_id, coord));
+            let mut advanced = false;
+
+            // All snakes have made moves, so we advance the gamestate
+            trace!(
+                "PATH: {:?} | Current ID: {:?} | Next ID: {:?} | Next Snake Index: {:?} | Snakes: {:?}",
+                self.path,
+                current_id,
+                next_id,
+                snake_order_index,
+                gs.board.snakes.len()
+            );
+            if self.path.len().is_multiple_of(self.snake_order.len()) {
+                trace!(
+                    "Advanced > Tree Depth {:?} | Recursive Depth {:?}",
+                    self.tree_depth,
+                    depth,
+                );
+                // Past the depth the undo history holds, the rest of the line is scored from here
+                advanced = gs
+                    .advance(&self.path[self.path.len() - self.snake_order.len()..])
+                    .is_ok();
+                if advanced {
+                    self.advances += 1;
+                    self.move_depth += 1;
+                }
+            }
+            trace!(
+                    "DOWN > Current Depth {:?} | Tree Depth {:?} | Score: {:?} | A: {:?} | B: {:?} | Current ID: {:?} | Coord: {:?} | Move: {:?}",
+                    self.tree_depth, depth, score, alpha, beta, current_id, coord, direction
+                );
+            self.tree_depth += 1;
+            self.tree_enter();
+            let node_score =
+                self.minimax_alphabeta(gs, maximizer, next_id, start, depth - 1, alpha, beta);
+            self.tree_leave(gs, Some((current_id, direction)), &node_score);
+            self.tree_depth -= 1;
+            if maximizer == current_id {
+                if self.tree_depth == 0 {
+                    let mut line = vec![(current_id, coord)];
+                    line.extend_from_slice(&self.pv_table[ply + 1]);
+                    self.iteration_root_scores
+                        .push((direction, node_score.clone(), line));
+                }
+                if node_score.sum() > score.sum() {
+                    score = node_score;
+                    best = Some(index);
+                    if self.tree_depth == 0
+                        && self.advances > 0
+                        && score.sum() > self.best_score.sum()
+                    {
+                        trace!(
+                    "New Best Score: {:?} {:?} | A: {:?} | B: {:?} | Current ID: {:?} | Coord: {:?} | Move: {:?}",
+                    score.sum(), score, alpha, beta, current_id, coord, direction
+                );
+                        self.best_direction = direction;
+                        self.best_pv.clear();
+                        self.best_pv.push((current_id, coord));
+                        self.best_pv.extend_from_slice(&self.pv_table[ply + 1]);
+                    }
+                }
+                if score.sum() > alpha {
+                    self.update_pv(ply, (current_id, coord));
+                    alpha = score.sum();
+                }
+            } else {
+                if node_score.sum() < score.sum() {
+                    score = node_score;
+                    best = Some(index);
+                }
+                if score.sum() < beta {
+                    self.update_pv(ply, (current_id, coord));
+                    beta = score.sum();
+                }
+            }
+            trace!(
+                    "UP   > Current Depth {:?} | Tree Depth {:?} | Score: {:?} | A: {:?} | B: {:?} | Current ID: {:?} | Coord: {:?} | Move: {:?}",
+                    self.tree_depth, depth, score, alpha, beta, current_id, coord, direction
+                );
+            // Pop off the last move to make room for the next viable move for this snake
+            self.path.pop();
+            if advanced {
+                // Can't fail, as the turn was just advanced
+                let _ = gs.undo();
+                self.undos += 1;
+                self.move_depth -= 1;
+            }
+            if maximizer == current_id && alpha >= beta {
+                trace!("alpha cutoff");
+                self.tree_cut(Cutoff::Alpha);
+                cutoff = Some(index);
+                break;
+            } else if beta <= alpha {
+                trace!("beta cutoff");
+                self.tree_cut(Cutoff::Beta);
+                cutoff = Some(index);
+                break;
+            }
+        }
+        self.ordering.record(moves, cutoff, hinted, best);
+        score
+    }
+    fn tree_enter(&mut self) {
+        if let Some(tree) = &mut self.tree {
+            tree.enter(self.terminals);
+        }
+    }
+    fn tree_cut(&mut self, cutoff: Cutoff) {
+        if let Some(tree) = &mut self.tree {
+            tree.cut(cutoff);
+        }
+    }
+    fn tree_leave(&mut self, gs: &GameState, mover: Option<(SnakeId, Direction)>, score: &Score) {
+        if let Some(tree) = &mut self.tree {
+            let mover = mover.map(|(sid, direction)| {
+                let id = gs
+                    .board
+                    .snakes
+                    .get(sid.index())
+                    .map(|snake| snake.id.clone());
+                (id.unwrap_or_default(), direction)
+            });
+            tree.leave(mover, score.sum(), self.terminals);
+        }
+    }
+    /// Makes the line at `ply` the given move followed by the line just found below it.
+    fn update_pv(&mut self, ply: usize, mv: (SnakeId, Coord)) {
+        let (above, below) = self.pv_table.split_at_mut(ply + 1);
+        let line = &mut above[ply];
+        line.clear();
+        line.push(mv);
+        line.extend_from_slice(&below[0]);
+    }
+    /// The best move of the last depth completed, named as in move responses. Example: "up"
+    pub fn best_move(&self) -> String {
+        format!("{:?}", self.best_direction).to_lowercase()
+    }
+    /// How deep and how much the search has searched so far.
+    pub fn stats(&self) -> SearchStats {
+        SearchStats {
+            depth: self.iteration_reached,
+            nodes: self.nodes,
+            time: self.search_time as u64,
+        }
+    }
+    pub(crate) fn evaluate(&self, gs: &GameState) -> Score {
+        let _timer = profiling::start(Phase::Evaluation);
+        let mut score = match self.full_evaluation_depth {
+            Some(depth) if self.move_depth > depth => basic_leaf_evaluate(gs, self.move_depth),
+            _ => (self.evaluate_fn)(gs, self.move_depth),
+        };
+        // The evaluations only see the leaf, so whether a squeeze is closing in is measured here
+        if let Some((sid, root_room)) = self.squeeze {
+            if let Some(opponent) = gs.board.snake(sid).filter(|_| !score.min && !score.max) {
+                let room = gs.reachable_squares(&opponent.head);
+                score.squeeze = (root_room as i32 - room as i32) * SQUEEZE_WEIGHT;
+            }
+        }
+        score
+    }
+}
@@ -0,0 +1,341 @@
+//! Games the engine plays against itself, for training data and matches between strategies.
+
+use super::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Number of planes produced by `GameState::encode`.
+pub const ENCODED_CHANNELS: usize = 6;
+
+/// Board planes for training, taken from the perspective of a single snake.
+///
+/// `planes` is a flat, row-major `[channel][y][x]` array of `0`/`1` bytes with
+/// `ENCODED_CHANNELS` channels, in order: own head, own body, enemy heads,
+/// enemy bodies, food and hazards. Health and length are raw game values.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EncodedState {
+    width: i32,
+    height: i32,
+    health: i32,
+    length: u32,
+    enemy_health: Vec<i32>,
+    pub(crate) enemy_length: Vec<u32>,
+    pub(crate) planes: Vec<u8>,
+}
+
+/// One training sample: a position, the move chosen there, and how the game ended.
+///
+/// Records are written one JSON object per line. `outcome` is `1` if the snake
+/// won, `0` for a draw and `-1` for a loss.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SelfPlayRecord {
+    game: u32,
+    pub(crate) turn: u32,
+    snake_id: String,
+    state: EncodedState,
+    #[serde(rename = "move")]
+    direction: Direction,
+    pub(crate) outcome: i8,
+}
+
+#[derive(Debug, Clone)]
+pub struct SelfPlayConfig {
+    /// Width and height of the square board.
+    pub board_size: i32,
+    /// Number of snakes placed on the board.
+    pub snakes: usize,
+    /// Search time per snake per turn for players that don't set their own, in milliseconds.
+    pub timeout: u128,
+    /// Games are adjudicated as draws for the survivors after this many turns.
+    pub max_turns: u32,
+    /// Players dealt out to the snakes in turn, rotating by one seat every game.
+    pub players: Vec<Player>,
+}
+
+/// A strategy entered into self-play, with its own search time if it differs from the game's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Player {
+    pub strategy: Strategy,
+    /// Search time per turn, in milliseconds.
+    pub timeout: Option<u128>,
+}
+
+impl From<Strategy> for Player {
+    fn from(strategy: Strategy) -> Self {
+        Player {
+            strategy,
+            timeout: None,
+        }
+    }
+}
+
+impl Default for SelfPlayConfig {
+    fn default() -> Self {
+        SelfPlayConfig {
+            board_size: 11,
+            snakes: 2,
+            timeout: 50,
+            max_turns: 500,
+            players: vec![Strategy::Minimax.into()],
+        }
+    }
+}
+
+impl SelfPlayConfig {
+    /// Index of the player taking the given seat of the given game.
+    pub fn seat(&self, game: u32, seat: usize) -> usize {
+        (seat + game as usize) % self.players.len().max(1)
+    }
+    fn player(&self, index: usize) -> Player {
+        self.players
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| Strategy::Minimax.into())
+    }
+}
+
+/// How a self-play game ended.
+#[derive(Debug, Clone)]
+pub struct SelfPlayResult {
+    /// Number of turns played.
+    pub turns: u32,
+    /// Index of the player each snake was, by snake id.
+    pub players: HashMap<String, usize>,
+    /// Ids of the snakes that survived longest.
+    pub winners: Vec<String>,
+    /// Whether the game ended without a single winner, which solo games always do.
+    pub draw: bool,
+}
+
+impl SelfPlayResult {
+    /// Index of the winning player, unless the game was a draw.
+    pub fn winner(&self) -> Option<usize> {
+        if self.draw {
+            return None;
+        }
+        self.winners
+            .first()
+            .and_then(|id| self.players.get(id))
+            .copied()
+    }
+}
+
+impl GameState {
+    fn new_self_play(config: &SelfPlayConfig) -> Self {
+        let size = config.board_size;
+        let (low, mid, high) = (1, (size - 1) / 2, size - 2);
+        let mut spawns = [
+            Coord { x: low, y: low },
+            Coord { x: low, y: high },
+            Coord { x: high, y: low },
+            Coord { x: high, y: high },
+            Coord { x: low, y: mid },
+            Coord { x: mid, y: low },
+            Coord { x: mid, y: high },
+            Coord { x: high, y: mid },
+        ];
+        spawns.shuffle(&mut rand::thread_rng());
+        let mode = if config.snakes == 1 {
+            GameMode::Solo
+        } else {
+            GameMode::Standard
+        };
+        let mut builder = GameStateBuilder::new(size, size)
+            .game_id("self-play")
+            .mode(mode)
+            .food([Coord { x: mid, y: mid }]);
+        for (i, spawn) in spawns.iter().take(config.snakes).enumerate() {
+            builder = builder.snake(&format!("snake-{}", i), [*spawn; 3]);
+        }
+        builder.build().expect("self-play board is valid")
+    }
+    /// Two snakes on a 7x7 board, with ours cornered so that right is its only safe move.
+    pub(crate) fn new_probe() -> Self {
+        GameStateBuilder::new(7, 7)
+            .game_id("probe")
+            .snake("snake-0", [(0, 0), (0, 1), (0, 2)])
+            .snake("snake-1", [(6, 6), (6, 5), (6, 4)])
+            .food([(3, 3)])
+            .build()
+            .expect("probe board is valid")
+    }
+    /// Returns a copy of this game state as seen by the snake with the given id.
+    fn perspective(&self, id: &str) -> Option<GameState> {
+        let snake = self.board.get_snake(id)?;
+        let mut gs = self.clone();
+        gs.you = snake.clone();
+        gs.init();
+        Some(gs)
+    }
+    fn spawn_food(&mut self) {
+        let mut rng = rand::thread_rng();
+        let settings = &self.game.ruleset.settings;
+        let spawn = (self.board.food.len() as u32) < settings.minimum_food
+            || rng.gen_range(0..100) < settings.food_spawn_chance;
+        if !spawn {
+            return;
+        }
+        let mut empty: Vec<Coord> = Vec::new();
+        for x in 0..self.board.width {
+            for y in 0..self.board.height {
+                let coord = Coord { x, y };
+                if self.board.food_squares.contains(&coord) {
+                    continue;
+                }
+                if self.board.snakes.iter().any(|s| s.body.contains(&coord)) {
+                    continue;
+                }
+                empty.push(coord);
+            }
+        }
+        if let Some(coord) = empty.choose(&mut rng) {
+            self.board.add_food(*coord);
+        }
+    }
+    pub fn encode(&self) -> EncodedState {
+        let area = (self.board.width * self.board.height) as usize;
+        let mut planes = vec![0; ENCODED_CHANNELS * area];
+        let mut set = |channel: usize, coord: &Coord| {
+            if in_bounds(coord, self.board.width, self.board.height) {
+                let index = coord.y * self.board.width + coord.x;
+                planes[channel * area + index as usize] = 1;
+            }
+        };
+        let mut enemy_health: Vec<i32> = Vec::new();
+        let mut enemy_length: Vec<u32> = Vec::new();
+        for snake in &self.board.snakes {
+            let offset = if snake.id == self.you.id { 0 } else { 2 };
+            set(offset, &snake.head);
+            for body in snake.body.range(1..) {
+                set(offset + 1, body);
+            }
+            if offset != 0 {
+                enemy_health.push(snake.health);
+                enemy_length.push(snake.length);
+            }
+        }
+        for food in &self.board.food {
+            set(4, food);
+        }
+        for hazard in &self.board.hazards {
+            set(5, hazard);
+        }
+        EncodedState {
+            width: self.board.width,
+            height: self.board.height,
+            health: self.you.health,
+            length: self.you.length,
+            enemy_health,
+            enemy_length,
+            planes,
+        }
+    }
+}
+
+/// Plays a complete game of the engine against itself and returns one record per snake per turn.
+pub fn self_play(game: u32, config: &SelfPlayConfig) -> Vec<SelfPlayRecord> {
+    let mut records: Vec<SelfPlayRecord> = Vec::new();
+    let result = play_self_play(game, config, |turn, snake_gs, direction| {
+        records.push(SelfPlayRecord {
+            game,
+            turn,
+            snake_id: snake_gs.you.id.clone(),
+            state: snake_gs.encode(),
+            direction,
+            outcome: 0,
+        });
+    });
+    for record in records.iter_mut() {
+        record.outcome = if !result.winners.contains(&record.snake_id) {
+            -1
+        } else if result.draw {
+            0
+        } else {
+            1
+        };
+    }
+    records
+}
+
+/// Chooses a move for the snake whose perspective `gs` is taken from.
+fn self_play_move(gs: &GameState, player: Player, timeout: u128) -> Direction {
+    let timeout = player.timeout.unwrap_or(timeout);
+    let mut policy = player.strategy.policy(MinimaxPolicy {
+        timeout,
+        ..MinimaxPolicy::default()
+    });
+    let deadline = Instant::now() + Duration::from_millis(timeout as u64);
+    policy.choose(gs, deadline).0
+}
+
+/// Chooses a move for a position outside of a game, named as in move responses. The search runs
+/// for the player's timeout, or as long as a move request's would without one.
+pub fn solve(mut gs: GameState, player: Player) -> String {
+    gs.init();
+    let direction = self_play_move(&gs, player, SEARCH_TIMEOUT);
+    format!("{:?}", direction).to_lowercase()
+}
+
+/// Plays a complete game of the configured players against each other, without recording it.
+pub fn play(game: u32, config: &SelfPlayConfig) -> SelfPlayResult {
+    play_self_play(game, config, |_, _, _| {})
+}
+
+/// Plays a complete game, calling `on_move` with the turn, the game from the moving snake's
+/// perspective and its move, for every move played.
+fn play_self_play(
+    game: u32,
+    config: &SelfPlayConfig,
+    mut on_move: impl FnMut(u32, &GameState, Direction),
+) -> SelfPlayResult {
+    let mut gs = GameState::new_self_play(config);
+    let players: HashMap<String, usize> = gs
+        .board
+        .snakes
+        .iter()
+        .enumerate()
+        .map(|(seat, snake)| (snake.id.clone(), config.seat(game, seat)))
+        .collect();
+    let mut survivors: Vec<String> = Vec::new();
+    while gs.turn < config.max_turns {
+        let mut moves: Vec<(SnakeId, Coord)> = Vec::new();
+        for snake in &gs.board.snakes {
+            let snake_gs = match gs.perspective(&snake.id) {
+                Some(snake_gs) => snake_gs,
+                None => continue,
+            };
+            let player = config.player(players[&snake.id]);
+            let direction = self_play_move(&snake_gs, player, config.timeout);
+            on_move(gs.turn, &snake_gs, direction);
+            let coord = gs.adjacent_coord(&snake.head, &direction);
+            moves.push((snake.sid, coord));
+        }
+        survivors = gs.board.snakes.iter().map(|s| s.id.clone()).collect();
+        if let Err(e) = gs.advance(&moves) {
+            warn!("self-play game {} stopped: {}", game, e);
+            break;
+        }
+        gs.commit();
+        gs.turn += 1;
+        gs.spawn_food();
+        gs.compute_metadata();
+        if gs.game_over() {
+            break;
+        }
+    }
+    let winners: Vec<String> = if gs.board.snakes.is_empty() {
+        survivors
+    } else {
+        gs.board.snakes.iter().map(|s| s.id.clone()).collect()
+    };
+    let draw = winners.len() != 1 || !gs.rules().last_snake_wins();
+    SelfPlayResult {
+        turns: gs.turn,
+        players,
+        winners,
+        draw,
+    }
+}
@@ -0,0 +1,877 @@
+//! What's learned about a game between its requests: timings, opponents, hazards and food.
+
+use super::*;
+use dashmap::DashMap;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Opponent moves observed before a tendency is trusted to order the search.
+const MIN_TENDENCY_SAMPLES: u32 = 5;
+/// Turns in a row an opponent has to answer close to the timeout before it's expected to time out.
+pub(crate) const SLOW_TURNS: u32 = 3;
+/// How long after answering a move a request for the same position gets the same answer again,
+/// rather than a search of its own.
+const CACHED_RESPONSE_TTL: Duration = Duration::from_secs(2);
+
+/// What kind of move stepping to a square is, for the tendencies tracked per opponent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MoveTraits {
+    /// Gets closer to the nearest food.
+    pub(crate) toward_food: bool,
+    /// Lands next to another snake's head, where a head-to-head could happen.
+    pub(crate) contested: bool,
+    /// Lands on the edge of a board that doesn't wrap.
+    pub(crate) edge: bool,
+}
+
+/// How often an opponent made each kind of move, counted over the turns where its choices
+/// differed in that respect.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Tendencies {
+    pub(crate) food_chances: u32,
+    pub(crate) food_seeking: u32,
+    pub(crate) head_to_head_chances: u32,
+    pub(crate) aggressive: u32,
+    pub(crate) edge_chances: u32,
+    pub(crate) edge_hugging: u32,
+    /// Turns in a row the opponent answered within a tenth of the timeout.
+    pub(crate) slow_turns: u32,
+    /// Whether the opponent timed out last turn.
+    pub(crate) timed_out: bool,
+}
+
+/// Share of `chances` taken, once there have been enough of them to go on.
+fn tendency_rate(taken: u32, chances: u32) -> Option<f32> {
+    (chances >= MIN_TENDENCY_SAMPLES).then(|| taken as f32 / chances as f32)
+}
+
+impl Tendencies {
+    /// Counts a move that picked `chosen` among the traits of every move it had.
+    fn observe(&mut self, choices: &[MoveTraits], chosen: MoveTraits) {
+        let tally = |trait_of: fn(&MoveTraits) -> bool, chances: &mut u32, taken: &mut u32| {
+            if choices.iter().any(trait_of) && !choices.iter().all(trait_of) {
+                *chances += 1;
+                if trait_of(&chosen) {
+                    *taken += 1;
+                }
+            }
+        };
+        tally(
+            |traits| traits.toward_food,
+            &mut self.food_chances,
+            &mut self.food_seeking,
+        );
+        tally(
+            |traits| traits.contested,
+            &mut self.head_to_head_chances,
+            &mut self.aggressive,
+        );
+        tally(
+            |traits| traits.edge,
+            &mut self.edge_chances,
+            &mut self.edge_hugging,
+        );
+    }
+    /// Keeps track of how close the opponent cut it to `timeout` with the latency it was given.
+    pub(crate) fn observe_latency(&mut self, latency: &str, timeout: u32) {
+        let latency: u32 = match latency.parse() {
+            Ok(latency) if latency > 0 => latency,
+            // Nothing to go on before its first move
+            _ => return,
+        };
+        self.timed_out = latency >= timeout;
+        if latency.saturating_mul(10) >= timeout.saturating_mul(9) {
+            self.slow_turns += 1;
+        } else {
+            self.slow_turns = 0;
+        }
+    }
+    /// Whether the opponent has been answering close to the timeout.
+    pub(crate) fn slow(&self) -> bool {
+        self.slow_turns >= SLOW_TURNS
+    }
+    /// Whether the opponent is expected to time out again, and so repeat its last move.
+    pub(crate) fn timing_out(&self) -> bool {
+        self.timed_out
+    }
+    /// How likely the opponent is to make a move like this, in millionths, from the tendencies
+    /// seen often enough. Moves are only compared with each other, to search the likely ones first.
+    pub(crate) fn likelihood(&self, traits: &MoveTraits) -> i32 {
+        let rates = [
+            (
+                tendency_rate(self.food_seeking, self.food_chances),
+                traits.toward_food,
+            ),
+            (
+                tendency_rate(self.aggressive, self.head_to_head_chances),
+                traits.contested,
+            ),
+            (
+                tendency_rate(self.edge_hugging, self.edge_chances),
+                traits.edge,
+            ),
+        ];
+        let likelihood = rates
+            .iter()
+            .filter_map(|&(rate, has_trait)| Some(if has_trait { rate? } else { 1.0 - rate? }))
+            .product::<f32>();
+        (likelihood * 1_000_000.0) as i32
+    }
+}
+
+/// The squares outside the hazards, as the smallest and largest x and then y, if there are any.
+fn safe_bounds(board: &Board) -> Option<(i32, i32, i32, i32)> {
+    let hazards: HashSet<&Coord> = board.hazards.iter().collect();
+    let mut bounds: Option<(i32, i32, i32, i32)> = None;
+    for y in 0..board.height {
+        for x in 0..board.width {
+            if hazards.contains(&Coord { x, y }) {
+                continue;
+            }
+            bounds = Some(match bounds {
+                Some((min_x, max_x, min_y, max_y)) => {
+                    (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+                }
+                None => (x, x, y, y),
+            });
+        }
+    }
+    bounds
+}
+
+/// When and from which side the hazards of a royale game have been seen to grow, which predicts
+/// the next shrink better than the ruleset's interval alone.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HazardSchedule {
+    /// Turns the hazards grew on, in order.
+    pub(crate) shrinks: Vec<u32>,
+    /// Side of the board each of those shrinks came from.
+    pub(crate) sides: Vec<Direction>,
+}
+
+impl HazardSchedule {
+    /// Records a shrink if the hazards grew from `previous` to `current`.
+    pub(crate) fn observe(&mut self, previous: &GameState, current: &GameState) {
+        if current.game.ruleset.name != GameMode::Royale
+            || current.board.hazards.len() <= previous.board.hazards.len()
+        {
+            return;
+        }
+        let (before, after) = match (safe_bounds(&previous.board), safe_bounds(&current.board)) {
+            (Some(before), Some(after)) => (before, after),
+            _ => return,
+        };
+        let side = if after.0 > before.0 {
+            Direction::Left
+        } else if after.1 < before.1 {
+            Direction::Right
+        } else if after.2 > before.2 {
+            Direction::Down
+        } else if after.3 < before.3 {
+            Direction::Up
+        } else {
+            return;
+        };
+        self.shrinks.push(current.turn);
+        self.sides.push(side);
+    }
+    /// Turns between shrinks: the last gap seen, or the ruleset's until there's been one.
+    fn interval(&self, gs: &GameState) -> u32 {
+        match self.shrinks.as_slice() {
+            [.., before, last] => last - before,
+            _ => gs.game.ruleset.settings.royale.shrink_every_n_turns,
+        }
+    }
+    /// Length of the shortest pattern the sides so far repeat, if they repeat one.
+    fn side_period(&self) -> Option<usize> {
+        let sides = &self.sides;
+        (1..=sides.len() / 2)
+            .find(|&period| (period..sides.len()).all(|i| sides[i] == sides[i - period]))
+    }
+    /// The side the `n`th shrink from now comes from, counting from 0, when the sides so far
+    /// repeat a shorter pattern.
+    fn upcoming_side(&self, n: usize) -> Option<Direction> {
+        let period = self.side_period()?;
+        Some(self.sides[self.sides.len() - period + n % period])
+    }
+    /// Turn of the next shrink after the turn of `gs`, in royale games.
+    fn next_turn(&self, gs: &GameState) -> Option<u32> {
+        if gs.game.ruleset.name != GameMode::Royale {
+            return None;
+        }
+        let interval = self.interval(gs);
+        if interval == 0 {
+            return None;
+        }
+        let last = self.shrinks.last().copied().unwrap_or(0);
+        Some(last + ((gs.turn.saturating_sub(last)) / interval + 1) * interval)
+    }
+    /// The next shrink after the turn of `gs`, if both its turn and side can be predicted.
+    pub(crate) fn next_shrink(&self, gs: &GameState) -> Option<HazardShrink> {
+        let turn = self.next_turn(gs)?;
+        let side = self.upcoming_side(0)?;
+        let (min_x, max_x, min_y, max_y) = safe_bounds(&gs.board)?;
+        let hazards: HashSet<&Coord> = gs.board.hazards.iter().collect();
+        let squares: Vec<Coord> = match side {
+            Direction::Left | Direction::Right => {
+                let x = if side == Direction::Left {
+                    min_x
+                } else {
+                    max_x
+                };
+                (0..gs.board.height).map(|y| Coord { x, y }).collect()
+            }
+            Direction::Down | Direction::Up => {
+                let y = if side == Direction::Down {
+                    min_y
+                } else {
+                    max_y
+                };
+                (0..gs.board.width).map(|x| Coord { x, y }).collect()
+            }
+        };
+        Some(HazardShrink {
+            turn,
+            squares: Arc::new(
+                squares
+                    .into_iter()
+                    .filter(|coord| !hazards.contains(coord))
+                    .collect(),
+            ),
+        })
+    }
+    /// The squares left safe by the next `shrinks` shrinks, or by as many as leave any, and the
+    /// turn of the last of them, if both their turns and sides can be predicted.
+    pub(crate) fn safe_zone(&self, gs: &GameState, shrinks: usize) -> Option<SafeZone> {
+        let first = self.next_turn(gs)?;
+        let interval = self.interval(gs);
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = safe_bounds(&gs.board)?;
+        let mut zone = None;
+        for n in 0..shrinks {
+            match self.upcoming_side(n)? {
+                Direction::Left => min_x += 1,
+                Direction::Right => max_x -= 1,
+                Direction::Down => min_y += 1,
+                Direction::Up => max_y -= 1,
+            }
+            if min_x > max_x || min_y > max_y {
+                break;
+            }
+            zone = Some(SafeZone {
+                turn: first + n as u32 * interval,
+                min: Coord { x: min_x, y: min_y },
+                max: Coord { x: max_x, y: max_y },
+            });
+        }
+        zone
+    }
+}
+
+/// Shrinks ahead the royale safe zone is planned for.
+pub(crate) const SAFE_ZONE_SHRINKS: usize = 3;
+
+/// Food spawns seen before their pattern is trusted over the center of the board.
+pub(crate) const MIN_FOOD_SPAWNS: usize = 5;
+
+/// Where food has spawned so far in a game, as some maps spawn it in patterns rather than
+/// uniformly.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoodSpawns {
+    /// Every square food appeared on, in order.
+    spawns: Vec<Coord>,
+    /// Turns watched for spawns.
+    turns: u32,
+}
+
+impl FoodSpawns {
+    /// Records the food that appeared from `previous` to `current`.
+    pub(crate) fn observe(&mut self, previous: &GameState, current: &GameState) {
+        self.turns += 1;
+        let before: HashSet<&Coord> = previous.board.food.iter().collect();
+        self.spawns.extend(
+            current
+                .board
+                .food
+                .iter()
+                .filter(|food| !before.contains(food)),
+        );
+    }
+    /// Food spawned per turn watched.
+    pub fn rate(&self) -> f32 {
+        if self.turns == 0 {
+            return 0.0;
+        }
+        self.spawns.len() as f32 / self.turns as f32
+    }
+    /// The square closest to the spawns seen so far, taking the median of each axis, once
+    /// there have been enough of them.
+    pub(crate) fn hotspot(&self) -> Option<Coord> {
+        if self.spawns.len() < MIN_FOOD_SPAWNS {
+            return None;
+        }
+        let median = |mut values: Vec<i32>| {
+            values.sort_unstable();
+            values[values.len() / 2]
+        };
+        Some(Coord {
+            x: median(self.spawns.iter().map(|food| food.x).collect()),
+            y: median(self.spawns.iter().map(|food| food.y).collect()),
+        })
+    }
+}
+
+/// A move we made, kept in the session for looking back over how sure the engine was as the
+/// game went on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Decision {
+    pub turn: u32,
+    /// The move played. Example: "up"
+    #[serde(rename = "move")]
+    pub direction: Direction,
+    /// Sum of the best score found, or null when the search failed and the fallback move was played.
+    pub score: Option<i32>,
+    /// Deepest fully completed search iteration, or 0 for the fallback move.
+    pub depth: u32,
+    /// Time from the request arriving to responding, in milliseconds.
+    pub elapsed: u32,
+    /// The move the shadow strategy would have played instead, when one is configured and
+    /// finished in time. Example: "left"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow: Option<Direction>,
+}
+
+/// The answer to the most recent move request, for answering the engine's retries of it.
+#[derive(Debug, Clone)]
+struct Answered {
+    turn: u32,
+    /// `GameState::position_key` of the request, which leaves out latencies and shouts.
+    position: u64,
+    response: MoveResponse,
+    at: Instant,
+}
+
+/// Games that have ended whose decision histories are kept, most recent last.
+const FINISHED_HISTORIES: usize = 100;
+/// Most recent turns whose decisions and scores a session keeps. Nothing learned about the
+/// opponents or hazards needs more than the previous turn, so only the post-mortem and the
+/// decision history look back, and games rarely last this long.
+pub(crate) const MAX_HISTORY_TURNS: usize = 500;
+/// How long a session goes without a request before it's taken for a game whose end request
+/// was missed, and dropped.
+pub(crate) const SESSION_IDLE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Drops the oldest entries of `entries` beyond the last `MAX_HISTORY_TURNS`.
+fn truncate_history<T>(entries: &mut Vec<T>) {
+    if entries.len() > MAX_HISTORY_TURNS {
+        entries.drain(..entries.len() - MAX_HISTORY_TURNS);
+    }
+}
+
+/// Data kept between requests for a single game.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSession {
+    /// Turn of the most recent move request.
+    pub(crate) turn: u32,
+    /// Latency the engine reported for our snake on the previous turn, in milliseconds.
+    pub(crate) latency: Option<u32>,
+    /// Time we took to respond to the previous move request, from its arrival, in milliseconds.
+    response_time: Option<u32>,
+    /// Estimated network and engine overhead on top of our own response time, in milliseconds.
+    overhead: Option<u32>,
+    /// Hazards present at the start of the game, which hazard maps use as fixed walls.
+    pub(crate) map_hazards: HashSet<Coord>,
+    /// Names of the other snakes at the start of the game.
+    pub(crate) opponents: Vec<String>,
+    /// Number of snakes on the board, including us, on the most recent move request.
+    pub(crate) snakes_alive: usize,
+    /// Strategy to play instead of the personality's own, set through the admin API.
+    strategy: Option<Strategy>,
+    /// Name of the experiment the game takes part in.
+    pub(crate) experiment: Option<String>,
+    /// Strategy the experiment assigned to the game.
+    pub(crate) variant: Option<Strategy>,
+    profile: Profile,
+    /// Turn and best score found for every move we searched, for the post-mortem, over the last
+    /// `MAX_HISTORY_TURNS` turns.
+    pub(crate) scores: Vec<(u32, i32)>,
+    /// Every move we made over the last `MAX_HISTORY_TURNS` turns, searched or not, in order.
+    history: Vec<Decision>,
+    /// How each opponent has moved so far, keyed by snake id.
+    tendencies: HashMap<String, Tendencies>,
+    /// How the hazards have grown so far, in royale.
+    hazard_schedule: HazardSchedule,
+    /// Where food has spawned so far.
+    food_spawns: FoodSpawns,
+    /// The state of the previous move request, to see how the opponents moved from it.
+    #[serde(skip)]
+    previous: Option<GameState>,
+    #[serde(skip)]
+    answered: Option<Answered>,
+    /// When the session was last asked for by a request.
+    #[serde(skip)]
+    seen: Option<Instant>,
+}
+
+impl GameSession {
+    pub(crate) fn new(gs: &GameState) -> Self {
+        GameSession {
+            turn: gs.turn,
+            latency: None,
+            response_time: None,
+            overhead: None,
+            map_hazards: gs.board.hazards.iter().cloned().collect(),
+            opponents: gs
+                .board
+                .snakes
+                .iter()
+                .filter(|snake| snake.id != gs.you.id)
+                .map(|snake| snake.details.name.clone())
+                .collect(),
+            snakes_alive: gs.board.snakes.len(),
+            strategy: None,
+            experiment: None,
+            variant: None,
+            profile: Profile::for_source(&gs.game.source),
+            scores: Vec::new(),
+            history: Vec::new(),
+            tendencies: HashMap::new(),
+            hazard_schedule: HazardSchedule::default(),
+            food_spawns: FoodSpawns::default(),
+            previous: None,
+            answered: None,
+            seen: None,
+        }
+    }
+    /// Updates the session from a new move request.
+    pub fn observe(&mut self, gs: &GameState) {
+        self.turn = gs.turn;
+        // The engine reports 0 before our first response
+        self.latency = gs
+            .you
+            .details
+            .latency
+            .parse()
+            .ok()
+            .filter(|&latency| latency > 0);
+        if self.snakes_alive > 2 && gs.board.snakes.len() == 2 {
+            info!("game {} is down to a duel on turn {}", gs.game.id, gs.turn);
+        }
+        self.snakes_alive = gs.board.snakes.len();
+        if let (Some(latency), Some(response_time)) = (self.latency, self.response_time.take()) {
+            let sample = latency.saturating_sub(response_time);
+            // Trust a slow turn immediately, but only recover slowly from one
+            self.overhead = Some(match self.overhead {
+                Some(overhead) if overhead > sample => (overhead * 7 + sample) / 8,
+                _ => sample,
+            });
+        }
+        for snake in gs.board.snakes.iter().filter(|snake| snake.id != gs.you.id) {
+            self.tendencies
+                .entry(snake.id.clone())
+                .or_default()
+                .observe_latency(&snake.details.latency, gs.game.timeout);
+        }
+        let mut current = gs.clone();
+        current.init();
+        if let Some(previous) = self.previous.take() {
+            if previous.turn + 1 == current.turn {
+                self.observe_opponents(&previous, &current);
+                self.hazard_schedule.observe(&previous, &current);
+                self.food_spawns.observe(&previous, &current);
+            }
+        }
+        self.previous = Some(current);
+    }
+    /// Tallies the tendencies of every opponent that moved from `previous` to `current`.
+    fn observe_opponents(&mut self, previous: &GameState, current: &GameState) {
+        let you = previous.you();
+        for snake in previous.board.alive() {
+            if snake.sid == you.sid {
+                continue;
+            }
+            let moved_to = match current.board.get_snake(&snake.id) {
+                Some(moved) => moved.head,
+                None => continue,
+            };
+            let mut choices: SmallVec<[MoveTraits; 4]> = SmallVec::new();
+            let mut chosen = None;
+            for (coord, _) in previous.adjacent_moves(&snake.head) {
+                if !previous.viable(&coord) {
+                    continue;
+                }
+                let traits = previous.move_traits(snake, &coord);
+                if coord == moved_to {
+                    chosen = Some(traits);
+                }
+                choices.push(traits);
+            }
+            if let Some(chosen) = chosen {
+                self.tendencies
+                    .entry(snake.id.clone())
+                    .or_default()
+                    .observe(&choices, chosen);
+            }
+        }
+    }
+    /// Records how long we took to respond, to compare against the latency the engine reports next turn.
+    pub fn responded(&mut self, response_time: Duration) {
+        self.response_time = Some(response_time.as_millis() as u32);
+    }
+    pub fn scored(&mut self, turn: u32, score: i32) {
+        self.scores.push((turn, score));
+        truncate_history(&mut self.scores);
+    }
+    /// Records a move we made, keeping its score for the post-mortem if it was searched.
+    pub fn decided(&mut self, decision: Decision) {
+        if let Some(score) = decision.score {
+            self.scored(decision.turn, score);
+        }
+        self.history.push(decision);
+        truncate_history(&mut self.history);
+    }
+    /// Keeps the response to the request for `position` on `turn`, answered at `now`, for
+    /// `cached_response`.
+    pub fn answered(&mut self, turn: u32, position: u64, response: MoveResponse, now: Instant) {
+        self.answered = Some(Answered {
+            turn,
+            position,
+            response,
+            at: now,
+        });
+    }
+    /// The response already sent for `position` on `turn`, if it was sent shortly before `now`.
+    /// The engine retries a request whose response got lost on the way, and searching it again
+    /// would answer later, and maybe differently.
+    pub fn cached_response(&self, turn: u32, position: u64, now: Instant) -> Option<MoveResponse> {
+        self.answered
+            .as_ref()
+            .filter(|answered| answered.turn == turn && answered.position == position)
+            .filter(|answered| now.saturating_duration_since(answered.at) < CACHED_RESPONSE_TTL)
+            .map(|answered| answered.response.clone())
+    }
+    /// Every move we made over the last `MAX_HISTORY_TURNS` turns, in order.
+    pub fn history(&self) -> &[Decision] {
+        &self.history
+    }
+    /// Time to leave under the game timeout for our response to reach the engine, in milliseconds.
+    pub fn move_margin(&self) -> u32 {
+        let margin = match self.overhead {
+            Some(overhead) => (overhead + LATENCY_SAFETY_MARGIN)
+                .clamp(MIN_MOVE_DEADLINE_MARGIN, MAX_MOVE_DEADLINE_MARGIN),
+            None => MOVE_DEADLINE_MARGIN,
+        };
+        match self.profile {
+            Profile::Custom => margin + CUSTOM_GAME_MARGIN,
+            _ => margin,
+        }
+    }
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+    pub fn latency(&self) -> Option<u32> {
+        self.latency
+    }
+    pub fn map_hazards(&self) -> &HashSet<Coord> {
+        &self.map_hazards
+    }
+    pub fn strategy(&self) -> Option<Strategy> {
+        self.strategy
+    }
+    pub fn variant(&self) -> Option<Strategy> {
+        self.variant
+    }
+    /// How each opponent has moved so far, keyed by snake id.
+    pub fn tendencies(&self) -> &HashMap<String, Tendencies> {
+        &self.tendencies
+    }
+    /// How the hazards have grown so far, in royale.
+    pub fn hazard_schedule(&self) -> &HazardSchedule {
+        &self.hazard_schedule
+    }
+    /// Where food has spawned so far.
+    pub fn food_spawns(&self) -> &FoodSpawns {
+        &self.food_spawns
+    }
+}
+
+/// Splits games between strategies to compare their win rates, read from the `experiment` config table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Experiment {
+    /// Recorded with every outcome, so results of different experiments aren't mixed.
+    pub(crate) name: String,
+    pub(crate) variants: Vec<Strategy>,
+}
+
+impl Experiment {
+    /// Picks a variant from the game id alone, so every snake and replica agrees on it.
+    pub(crate) fn assign(&self, game_id: &str) -> Option<Strategy> {
+        if self.variants.is_empty() {
+            return None;
+        }
+        // FNV-1a, which unlike the std hasher gives the same result across builds
+        let hash = game_id.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        Some(self.variants[(hash % self.variants.len() as u64) as usize])
+    }
+}
+
+/// Search time the shadow strategy gets each turn, unless configured otherwise.
+pub const SHADOW_BUDGET: u64 = 50;
+
+/// A second strategy searched alongside every real move, read from the `shadow` config table.
+/// Its move is only logged and kept in the decision history, never played, to compare
+/// strategies on real games without risking them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Shadow {
+    pub(crate) strategy: Strategy,
+    /// Search time it gets each turn, in milliseconds, cut short by the real move's deadline.
+    #[serde(default = "shadow_budget")]
+    pub(crate) budget: u64,
+}
+
+fn shadow_budget() -> u64 {
+    SHADOW_BUDGET
+}
+
+impl Shadow {
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+}
+
+/// Sessions for all games in progress, keyed by game and snake id.
+#[derive(Debug, Default)]
+pub struct GameSessions {
+    sessions: DashMap<String, Arc<Mutex<GameSession>>>,
+    /// Number of move searches that haven't responded yet.
+    searches: AtomicUsize,
+    experiment: Option<Experiment>,
+    shadow: Option<Shadow>,
+    /// Time spent searching the start position of every new game, if any.
+    warm_up: Duration,
+    /// Decision histories of the games that ended most recently, keyed as sessions are.
+    finished: Mutex<VecDeque<(String, Vec<Decision>)>>,
+}
+
+/// Marks a move search as in progress until dropped.
+pub struct SearchGuard<'a> {
+    searches: &'a AtomicUsize,
+}
+
+impl Drop for SearchGuard<'_> {
+    fn drop(&mut self) {
+        self.searches.fetch_sub(1, AtomicOrdering::SeqCst);
+    }
+}
+
+/// Sessions are per snake, since several of our personalities may play in the same game.
+pub fn session_key(gs: &GameState) -> String {
+    format!("{}/{}", gs.game.id, gs.you.id)
+}
+
+impl GameSessions {
+    /// Assigns every new game to a variant of the experiment.
+    pub fn with_experiment(mut self, experiment: Option<Experiment>) -> Self {
+        self.experiment = experiment;
+        self
+    }
+    /// Name of the experiment new ladder games are assigned to.
+    pub fn experiment(&self) -> Option<&str> {
+        self.experiment
+            .as_ref()
+            .map(|experiment| experiment.name.as_str())
+    }
+    /// Searches every move with a shadow strategy too.
+    pub fn with_shadow(mut self, shadow: Option<Shadow>) -> Self {
+        self.shadow = shadow;
+        self
+    }
+    pub fn shadow(&self) -> Option<&Shadow> {
+        self.shadow.as_ref()
+    }
+    pub fn with_warm_up(mut self, warm_up: Duration) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+    pub fn warm_up(&self) -> Duration {
+        self.warm_up
+    }
+    fn new_session(&self, gs: &GameState) -> Arc<Mutex<GameSession>> {
+        let mut session = GameSession::new(gs);
+        // Only ladder games are worth risking on an experiment
+        if let (Some(experiment), Profile::Ladder) = (&self.experiment, session.profile) {
+            session.experiment = Some(experiment.name.clone());
+            session.variant = experiment.assign(&gs.game.id);
+        }
+        Arc::new(Mutex::new(session))
+    }
+    /// Starts a session for a new game.
+    pub fn start(&self, gs: &GameState) -> Arc<Mutex<GameSession>> {
+        let now = Instant::now();
+        let session = self.new_session(gs);
+        session.lock().unwrap_or_else(PoisonError::into_inner).seen = Some(now);
+        self.sessions.insert(session_key(gs), session.clone());
+        session
+    }
+    /// Returns the session for the game, creating one if the start request was missed.
+    pub fn get(&self, gs: &GameState) -> Arc<Mutex<GameSession>> {
+        let session = self
+            .sessions
+            .entry(session_key(gs))
+            .or_insert_with(|| self.new_session(gs))
+            .clone();
+        session.lock().unwrap_or_else(PoisonError::into_inner).seen = Some(Instant::now());
+        session
+    }
+    /// Keeps the decision history of a session that's been removed for a while.
+    fn finish(&self, key: String, session: &Mutex<GameSession>) {
+        let history = session
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .history
+            .clone();
+        let mut finished = self.finished.lock().unwrap_or_else(PoisonError::into_inner);
+        if finished.len() >= FINISHED_HISTORIES {
+            finished.pop_front();
+        }
+        finished.push_back((key, history));
+    }
+    /// Removes the session of a game that's ended, keeping its decision history for a while.
+    pub fn end(&self, gs: &GameState) -> Option<Arc<Mutex<GameSession>>> {
+        let (key, session) = self.sessions.remove(&session_key(gs))?;
+        self.finish(key, &session);
+        Some(session)
+    }
+    /// Removes the sessions that haven't been asked for in `SESSION_IDLE_TTL` as of `now`, left
+    /// by games that ended without an end request, keeping their decision histories like those
+    /// of ended games. Returns their keys.
+    pub fn evict_idle(&self, now: Instant) -> Vec<String> {
+        let idle = |session: &Arc<Mutex<GameSession>>| {
+            let seen = session.lock().unwrap_or_else(PoisonError::into_inner).seen;
+            seen.is_some_and(|seen| now.saturating_duration_since(seen) > SESSION_IDLE_TTL)
+        };
+        let keys: Vec<String> = self
+            .sessions
+            .iter()
+            .filter(|entry| idle(entry.value()))
+            .map(|entry| entry.key().clone())
+            .collect();
+        let mut evicted = Vec::new();
+        for key in keys {
+            // Asked for again since, in which case it's no longer idle
+            if let Some((key, session)) = self.sessions.remove_if(&key, |_, session| idle(session))
+            {
+                info!("dropped session {} after it went idle", key);
+                self.finish(key.clone(), &session);
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+    /// Decision histories of every one of our snakes in a game, in progress or among the last
+    /// to have ended, keyed by snake id. Empty when the game isn't known.
+    pub fn history(&self, game_id: &str) -> HashMap<String, Vec<Decision>> {
+        let prefix = format!("{}/", game_id);
+        let snake_id = |key: &str| key.strip_prefix(&prefix).map(str::to_owned);
+        let mut histories: HashMap<String, Vec<Decision>> = self
+            .finished
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(|(key, history)| Some((snake_id(key)?, history.clone())))
+            .collect();
+        for entry in self.sessions.iter() {
+            if let Some(id) = snake_id(entry.key()) {
+                let session = entry.value().lock().unwrap_or_else(PoisonError::into_inner);
+                histories.insert(id, session.history.clone());
+            }
+        }
+        histories
+    }
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+    /// Overrides the strategy of every one of our snakes in a game, or restores their own with `None`.
+    ///
+    /// Returns the number of sessions changed, which is zero when the game isn't in progress.
+    pub fn set_strategy(&self, game_id: &str, strategy: Option<Strategy>) -> usize {
+        let prefix = format!("{}/", game_id);
+        let mut changed = 0;
+        for entry in self.sessions.iter() {
+            if entry.key().starts_with(&prefix) {
+                entry
+                    .value()
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .strategy = strategy;
+                changed += 1;
+            }
+        }
+        changed
+    }
+    pub fn begin_search(&self) -> SearchGuard<'_> {
+        self.searches.fetch_add(1, AtomicOrdering::SeqCst);
+        SearchGuard {
+            searches: &self.searches,
+        }
+    }
+    /// Number of move searches currently in progress.
+    pub fn searches(&self) -> usize {
+        self.searches.load(AtomicOrdering::SeqCst)
+    }
+    /// Loads sessions written by `save`, so games in progress survive a restart.
+    pub fn load(path: &Path) -> Self {
+        let saved: HashMap<String, GameSession> = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!("unable to read sessions from {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                warn!("unable to read sessions from {:?}: {}", path, e);
+                HashMap::new()
+            }
+        };
+        let sessions = GameSessions::default();
+        let now = Instant::now();
+        for (key, mut session) in saved {
+            session.seen = Some(now);
+            sessions.sessions.insert(key, Arc::new(Mutex::new(session)));
+        }
+        sessions
+    }
+    /// Writes every session to `path`, replacing the file only once it's fully written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let saved: HashMap<String, GameSession> = self
+            .sessions
+            .iter()
+            .map(|entry| {
+                let session = entry.value().lock().unwrap_or_else(PoisonError::into_inner);
+                (entry.key().clone(), session.clone())
+            })
+            .collect();
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_vec(&saved)?)?;
+        fs::rename(partial, path)
+    }
+}
@@ -0,0 +1,101 @@
+//! Shows what the engine would play on a board, with its principal variation and territory.
+//!
+//! Usage: `cargo run --release --bin analyze -- [--server <url>] [file]`
+//!
+//! Reads board text or a JSON game state from the file, or standard input when none is given.
+//! The position is analyzed in-process, or by `POST /analyze` and `POST /render` on the server
+//! at `url` when one is given, so a deployment can be checked against a local build.
+
+use ndsquared_rustapi::battlesnake::{self, GameState, RenderFormat};
+use ndsquared_rustapi::boards;
+use serde_json::Value;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+
+/// Asks the server for the analysis and a render of the position with territory.
+fn analyze_remote(url: &str, gs: &GameState) -> Result<(Value, String), Box<dyn Error>> {
+    let url = url.trim_end_matches('/');
+    let analysis: Value = ureq::post(&format!("{}/analyze", url))
+        .send_json(gs)?
+        .into_json()?;
+    let board = ureq::post(&format!("{}/render?format=ascii&territory=true", url))
+        .send_json(gs)?
+        .into_string()?;
+    Ok((analysis, board))
+}
+
+fn analyze_local(gs: GameState) -> (Value, String) {
+    let board = battlesnake::render(gs.clone(), RenderFormat::Ascii, true);
+    let analysis =
+        serde_json::to_value(battlesnake::analyze(gs)).expect("unable to serialize analysis");
+    (analysis, board)
+}
+
+fn main() {
+    ndsquared_rustapi::trace::init("warn");
+    let mut args = env::args().skip(1);
+    let mut server: Option<String> = None;
+    let mut path: Option<String> = None;
+    while let Some(arg) = args.next() {
+        if arg == "--server" {
+            server = args.next();
+            if server.is_none() {
+                eprintln!("usage: analyze [--server <url>] [file]");
+                std::process::exit(2);
+            }
+            continue;
+        }
+        path = Some(arg);
+    }
+    let input = match path {
+        Some(path) => fs::read_to_string(path).expect("unable to read board"),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .expect("unable to read board");
+            input
+        }
+    };
+    let gs: GameState = if input.trim_start().starts_with('{') {
+        serde_json::from_str(&input).expect("invalid game state")
+    } else {
+        boards::parse(&input).unwrap_or_else(|e| {
+            eprintln!("invalid board: {}", e);
+            std::process::exit(1);
+        })
+    };
+    if let Err(e) = gs.validate() {
+        eprintln!("invalid game state: {}", e);
+        std::process::exit(1);
+    }
+    let (analysis, board) = match server {
+        Some(url) => analyze_remote(&url, &gs).unwrap_or_else(|e| {
+            eprintln!("unable to analyze on {}: {}", url, e);
+            std::process::exit(1);
+        }),
+        None => analyze_local(gs),
+    };
+    let pv: Vec<&str> = analysis["pv"]
+        .as_array()
+        .map(|pv| pv.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    print!("{}", board);
+    println!("move: {}", analysis["move"].as_str().unwrap_or("?"));
+    println!("score: {}", analysis["score"]);
+    println!("pv: {}", pv.join(" "));
+    for root in analysis["root_scores"].as_array().into_iter().flatten() {
+        println!(
+            "  {}: {}",
+            root["move"].as_str().unwrap_or("?"),
+            root["score"]
+        );
+    }
+    let stats = &analysis["stats"];
+    println!(
+        "depth {} | nodes {} | {}ms",
+        stats["depth"], stats["nodes"], stats["time"]
+    );
+}
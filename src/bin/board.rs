@@ -0,0 +1,53 @@
+//! Converts between game states and the board text notation, or analyzes a board.
+//!
+//! Usage: `cargo run --release --bin board -- [--analyze] [file]`
+//!
+//! Reads the file, or standard input when none is given. A JSON game state is printed as board
+//! text and board text as a JSON game state; with `--analyze`, either is analyzed like
+//! `POST /analyze` instead.
+
+use ndsquared_rustapi::battlesnake::{self, GameState};
+use ndsquared_rustapi::boards;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+
+fn main() {
+    ndsquared_rustapi::trace::init("warn");
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let analyze = args.iter().any(|arg| arg == "--analyze");
+    args.retain(|arg| arg != "--analyze");
+    let input = match args.first() {
+        Some(path) => fs::read_to_string(path).expect("unable to read board"),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .expect("unable to read board");
+            input
+        }
+    };
+    let json = input.trim_start().starts_with('{');
+    let gs: GameState = if json {
+        serde_json::from_str(&input).expect("invalid game state")
+    } else {
+        boards::parse(&input).unwrap_or_else(|e| {
+            eprintln!("invalid board: {}", e);
+            std::process::exit(1);
+        })
+    };
+    if let Err(e) = gs.validate() {
+        eprintln!("invalid game state: {}", e);
+        std::process::exit(1);
+    }
+    if analyze {
+        let analysis = battlesnake::analyze(gs);
+        let json = serde_json::to_string_pretty(&analysis).expect("unable to serialize analysis");
+        println!("{}", json);
+    } else if json {
+        print!("{}", boards::to_text(&gs));
+    } else {
+        let json = serde_json::to_string_pretty(&gs).expect("unable to serialize game state");
+        println!("{}", json);
+    }
+}
@@ -0,0 +1,40 @@
+//! Plays the engine against itself and writes training records as JSON lines.
+//!
+//! Usage: `cargo run --release --features datagen --bin datagen -- <games> <output.jsonl> [snakes] [timeout_ms]`
+
+use ndsquared_rustapi::battlesnake::{self_play, SelfPlayConfig};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+
+fn main() -> std::io::Result<()> {
+    ndsquared_rustapi::trace::init("warn");
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        eprintln!("usage: datagen <games> <output.jsonl> [snakes] [timeout_ms]");
+        std::process::exit(2);
+    }
+    let games: u32 = args[1].parse().expect("games must be a number");
+    let mut config = SelfPlayConfig::default();
+    if let Some(snakes) = args.get(3) {
+        config.snakes = snakes.parse().expect("snakes must be a number");
+    }
+    if let Some(timeout) = args.get(4) {
+        config.timeout = timeout.parse().expect("timeout_ms must be a number");
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&args[2])?;
+    let mut writer = BufWriter::new(file);
+    for game in 0..games {
+        let records = self_play(game, &config);
+        println!("game {}: {} records", game, records.len());
+        for record in records {
+            serde_json::to_writer(&mut writer, &record)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Writes the labeled moves of every recorded game as training records, one JSON object per
+//! line, for tuning on real games the way `datagen` records tune on self-play.
+//!
+//! Usage: `cargo run --release --bin export -- <recordings> <output.jsonl>`
+
+use ndsquared_rustapi::recorder::GameRecorder;
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    ndsquared_rustapi::trace::init("warn");
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        eprintln!("usage: export <recordings> <output.jsonl>");
+        std::process::exit(2);
+    }
+    let recorder = GameRecorder::new(Some(PathBuf::from(&args[1])));
+    let mut writer = BufWriter::new(File::create(&args[2])?);
+    let (mut games, mut records) = (0, 0);
+    for id in recorder.games() {
+        let turns = recorder.labeled_turns(&id, None);
+        if turns.is_empty() {
+            continue;
+        }
+        games += 1;
+        records += turns.len();
+        for turn in turns {
+            serde_json::to_writer(&mut writer, &turn)?;
+            writer.write_all(b"\n")?;
+        }
+    }
+    writer.flush()?;
+    println!("{} records from {} games", records, games);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Counts the joint moves and positions reachable from a board, depth by depth.
+//!
+//! Usage: `cargo run --release --bin perft -- <depth> [file]`
+//!
+//! Reads board text or a JSON game state from the file, or standard input when none is given,
+//! and prints the leaves and distinct positions at every depth up to `depth`.
+
+use ndsquared_rustapi::battlesnake::{perft, GameState};
+use ndsquared_rustapi::boards;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::time::Instant;
+
+fn main() {
+    ndsquared_rustapi::trace::init("warn");
+    let args: Vec<String> = env::args().collect();
+    let depth: u32 = match args.get(1).and_then(|depth| depth.parse().ok()) {
+        Some(depth) => depth,
+        None => {
+            eprintln!("usage: perft <depth> [file]");
+            std::process::exit(2);
+        }
+    };
+    let input = match args.get(2) {
+        Some(path) => fs::read_to_string(path).expect("unable to read board"),
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .expect("unable to read board");
+            input
+        }
+    };
+    let gs: GameState = if input.trim_start().starts_with('{') {
+        serde_json::from_str(&input).expect("invalid game state")
+    } else {
+        boards::parse(&input).unwrap_or_else(|e| {
+            eprintln!("invalid board: {}", e);
+            std::process::exit(1);
+        })
+    };
+    if let Err(e) = gs.validate() {
+        eprintln!("invalid game state: {}", e);
+        std::process::exit(1);
+    }
+    println!("depth leaves states time");
+    for depth in 0..=depth {
+        let start = Instant::now();
+        let counts = perft(gs.clone(), depth);
+        println!(
+            "{} {} {} {}ms",
+            depth,
+            counts.leaves,
+            counts.states,
+            start.elapsed().as_millis()
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Runs a strategy against suites of puzzle positions and reports which it solved.
+//!
+//! Usage: `cargo run --release --bin puzzles -- [--strategy <strategy>] <suite>...`
+//!
+//! Each result is printed as it finishes, followed by the totals. Exits with an error when any
+//! position fails.
+
+use ndsquared_rustapi::battlesnake::Strategy;
+use ndsquared_rustapi::puzzles::{parse_suite, run, Puzzle};
+use std::env;
+use std::fs;
+
+fn main() {
+    ndsquared_rustapi::trace::init("warn");
+    let mut args = env::args().skip(1);
+    let mut strategy = Strategy::Minimax;
+    let mut puzzles: Vec<Puzzle> = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--strategy" {
+            let name = args.next().unwrap_or_default();
+            strategy = serde_json::from_value(serde_json::Value::String(name))
+                .expect("strategy must be minimax, basic, greedy or random");
+            continue;
+        }
+        let suite = fs::read_to_string(&arg).expect("unable to read suite");
+        let suite = parse_suite(&suite).unwrap_or_else(|e| {
+            eprintln!("invalid suite {}: {}", arg, e);
+            std::process::exit(2);
+        });
+        puzzles.extend(suite);
+    }
+    if puzzles.is_empty() {
+        eprintln!("usage: puzzles [--strategy <strategy>] <suite>...");
+        std::process::exit(2);
+    }
+    let report = run(&puzzles, strategy, |result| {
+        let status = if result.passed { "PASS" } else { "FAIL" };
+        println!(
+            "{} {} {} ({}ms)",
+            status, result.id, result.chosen, result.time
+        );
+    });
+    println!("{}/{} passed", report.passed(), report.results.len());
+    if report.passed() < report.results.len() {
+        std::process::exit(1);
+    }
+}
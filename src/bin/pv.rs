@@ -0,0 +1,156 @@
+//! Steps through the lines the engine expects after each root move of a position.
+//!
+//! Usage: `cargo run --release --features tui --bin pv -- [file]`
+//!
+//! Reads board text or a JSON game state from the file, or standard input when none is given,
+//! searches it like `POST /analyze` and shows the board along each line. Up and down (or `k` and
+//! `j`) pick a root move, left and right (or `h` and `l`) step through its line and `q` quits.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ndsquared_rustapi::battlesnake::{self, GameState, RenderFormat, Variation};
+use ndsquared_rustapi::boards;
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+
+/// The root move picked and how far along its line the board is shown.
+struct Viewer {
+    variations: Vec<Variation>,
+    /// Boards along each line, rendered up front so stepping is instant.
+    boards: Vec<Vec<String>>,
+    selected: usize,
+    step: usize,
+}
+
+impl Viewer {
+    fn new(variations: Vec<Variation>) -> Self {
+        let boards = variations
+            .iter()
+            .map(|variation| {
+                variation
+                    .positions
+                    .iter()
+                    .map(|gs| battlesnake::render(gs.clone(), RenderFormat::Ascii, false))
+                    .collect()
+            })
+            .collect();
+        Viewer {
+            variations,
+            boards,
+            selected: 0,
+            step: 0,
+        }
+    }
+    fn select(&mut self, offset: isize) {
+        let last = self.variations.len().saturating_sub(1) as isize;
+        self.selected = (self.selected as isize + offset).clamp(0, last) as usize;
+        self.step = self.step.min(self.boards[self.selected].len() - 1);
+    }
+    fn advance(&mut self, offset: isize) {
+        let last = self.boards[self.selected].len() as isize - 1;
+        self.step = (self.step as isize + offset).clamp(0, last) as usize;
+    }
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(28), Constraint::Min(0)])
+            .split(frame.size());
+        let items: Vec<ListItem> = self
+            .variations
+            .iter()
+            .map(|variation| {
+                let marker = if variation.best { "*" } else { " " };
+                ListItem::new(format!(
+                    "{} {:<5} {:>11} ({})",
+                    marker,
+                    variation.direction,
+                    variation.score,
+                    variation.positions.len() - 1
+                ))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Root moves"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+        frame.render_stateful_widget(list, columns[0], &mut state);
+        let boards = &self.boards[self.selected];
+        let title = format!(
+            "Turn {} ({}/{}) - left/right to step, up/down to pick a move, q to quit",
+            self.variations[self.selected].positions[self.step].turn(),
+            self.step,
+            boards.len() - 1
+        );
+        let board = Paragraph::new(boards[self.step].clone())
+            .block(Block::default().borders(Borders::ALL).title(title));
+        frame.render_widget(board, columns[1]);
+    }
+}
+
+fn run<B: Backend>(terminal: &mut Terminal<B>, mut viewer: Viewer) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| viewer.draw(frame))?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => viewer.select(-1),
+            KeyCode::Down | KeyCode::Char('j') => viewer.select(1),
+            KeyCode::Left | KeyCode::Char('h') => viewer.advance(-1),
+            KeyCode::Right | KeyCode::Char('l') => viewer.advance(1),
+            KeyCode::Home => viewer.step = 0,
+            _ => {}
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    ndsquared_rustapi::trace::init("off");
+    let input = match env::args().nth(1) {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+    let gs: GameState = if input.trim_start().starts_with('{') {
+        serde_json::from_str(&input).expect("invalid game state")
+    } else {
+        boards::parse(&input).unwrap_or_else(|e| {
+            eprintln!("invalid board: {}", e);
+            std::process::exit(1);
+        })
+    };
+    if let Err(e) = gs.validate() {
+        eprintln!("invalid game state: {}", e);
+        std::process::exit(1);
+    }
+    let variations = battlesnake::variations(gs);
+    if variations.is_empty() {
+        eprintln!("the search found no moves");
+        std::process::exit(1);
+    }
+    let viewer = Viewer::new(variations);
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    let result = run(&mut terminal, viewer);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
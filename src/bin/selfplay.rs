@@ -0,0 +1,86 @@
+//! Plays strategies against each other in-process and prints their win rates.
+//!
+//! Usage: `cargo run --release --bin selfplay -- <games> <strategies> [snakes] [timeout_ms]`
+//!
+//! `strategies` is a comma separated list such as `minimax,basic`. Strategies are dealt out to
+//! the snakes in turn, rotating by one seat every game.
+
+use ndsquared_rustapi::battlesnake::{play, SelfPlayConfig, Strategy};
+use std::collections::HashMap;
+use std::env;
+
+fn main() {
+    ndsquared_rustapi::trace::init("warn");
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        eprintln!("usage: selfplay <games> <strategies> [snakes] [timeout_ms]");
+        std::process::exit(2);
+    }
+    let games: u32 = args[1].parse().expect("games must be a number");
+    let strategies: Vec<Strategy> = args[2]
+        .split(',')
+        .map(|name| {
+            serde_json::from_value(serde_json::Value::String(name.trim().to_owned()))
+                .unwrap_or_else(|_| panic!("unknown strategy {:?}", name))
+        })
+        .collect();
+    let mut config = SelfPlayConfig {
+        players: strategies.iter().map(|&strategy| strategy.into()).collect(),
+        ..Default::default()
+    };
+    if let Some(snakes) = args.get(3) {
+        config.snakes = snakes.parse().expect("snakes must be a number");
+    }
+    if let Some(timeout) = args.get(4) {
+        config.timeout = timeout.parse().expect("timeout_ms must be a number");
+    }
+    // Games played and won by each strategy, counting every seat it played
+    let mut played: HashMap<Strategy, u32> = HashMap::new();
+    let mut won: HashMap<Strategy, u32> = HashMap::new();
+    let mut draws = 0;
+    let mut turns = 0;
+    for game in 0..games {
+        let result = play(game, &config);
+        for &player in result.players.values() {
+            *played.entry(strategies[player]).or_default() += 1;
+        }
+        let winner = result.winner().map(|player| strategies[player]);
+        match winner {
+            Some(strategy) => *won.entry(strategy).or_default() += 1,
+            None => draws += 1,
+        }
+        turns += result.turns;
+        println!("game {}: {} turns, winner {:?}", game, result.turns, winner);
+    }
+    println!();
+    println!(
+        "{:<10} {:>8} {:>8} {:>9}",
+        "strategy", "played", "won", "win rate"
+    );
+    let mut distinct: Vec<Strategy> = Vec::new();
+    for strategy in strategies {
+        if !distinct.contains(&strategy) {
+            distinct.push(strategy);
+        }
+    }
+    for strategy in distinct {
+        let played = played.get(&strategy).copied().unwrap_or(0);
+        let won = won.get(&strategy).copied().unwrap_or(0);
+        let rate = if played == 0 {
+            0.0
+        } else {
+            won as f64 / played as f64
+        };
+        println!(
+            "{:<10} {:>8} {:>8} {:>8.1}%",
+            format!("{:?}", strategy).to_lowercase(),
+            played,
+            won,
+            rate * 100.0
+        );
+    }
+    println!("draws: {}", draws);
+    if games > 0 {
+        println!("average turns: {:.1}", turns as f64 / games as f64);
+    }
+}
@@ -0,0 +1,33 @@
+//! Plays a round robin between named configurations and reports their Elo differences.
+//!
+//! Usage: `cargo run --release --bin tournament -- <config.json> [report.json]`
+//!
+//! The markdown report is printed once every pairing has finished, and the JSON report is also
+//! written to `report.json` when given.
+
+use ndsquared_rustapi::tournament::{run, TournamentConfig};
+use std::env;
+use std::fs;
+
+fn main() {
+    ndsquared_rustapi::trace::init("warn");
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: tournament <config.json> [report.json]");
+        std::process::exit(2);
+    }
+    let config = fs::read_to_string(&args[1]).expect("unable to read config");
+    let config: TournamentConfig = serde_json::from_str(&config).expect("invalid config");
+    let report = run(&config, |pairing| {
+        let score = &pairing.score;
+        eprintln!(
+            "{} vs {}: +{} ={} -{}",
+            pairing.first, pairing.second, score.wins, score.draws, score.losses
+        );
+    });
+    println!("{}", report.markdown());
+    if let Some(path) = args.get(2) {
+        let json = serde_json::to_string_pretty(&report).expect("unable to serialize report");
+        fs::write(path, json).expect("unable to write report");
+    }
+}
@@ -0,0 +1,447 @@
+use crate::battlesnake::GameState;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Letters that mark squares rather than snakes.
+const RESERVED_LETTERS: &str = "FGHSZ";
+
+/// Letters given to snakes whose ids aren't a free letter already, in order.
+const SNAKE_LETTERS: &str = "ABCDEIJKLMNOPQRTUVWX";
+
+const DEFAULT_HEALTH: i64 = 100;
+const DEFAULT_HAZARD_DAMAGE: i64 = 15;
+
+/// Settings given by `key: value` lines around the board.
+struct Annotations {
+    mode: String,
+    map: String,
+    turn: u64,
+    hazard_damage: i64,
+    you: char,
+    health: HashMap<char, i64>,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Annotations {
+            mode: "standard".to_owned(),
+            map: "standard".to_owned(),
+            turn: 0,
+            hazard_damage: DEFAULT_HAZARD_DAMAGE,
+            you: 'Y',
+            health: HashMap::new(),
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} must be a number, not {:?}", key, value))
+}
+
+fn parse_letter(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_uppercase() => Ok(letter),
+        _ => Err(format!("{:?} isn't a snake letter", value)),
+    }
+}
+
+impl Annotations {
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "mode" => self.mode = value.to_owned(),
+            "map" => self.map = value.to_owned(),
+            "turn" => self.turn = parse_number(key, value)?,
+            "hazard_damage" => self.hazard_damage = parse_number(key, value)?,
+            "you" => self.you = parse_letter(value)?,
+            "health" => {
+                for entry in value.split(|c: char| c == ',' || c.is_whitespace()) {
+                    if entry.is_empty() {
+                        continue;
+                    }
+                    let (letter, health) = entry
+                        .split_once('=')
+                        .ok_or_else(|| format!("expected health as `A=50`, not {:?}", entry))?;
+                    self.health
+                        .insert(parse_letter(letter)?, parse_number(key, health)?);
+                }
+            }
+            _ => return Err(format!("unknown annotation {:?}", key)),
+        }
+        Ok(())
+    }
+}
+
+/// Parses a board drawn as rows of `|`-separated cells, top row first:
+///
+/// ```text
+/// mode: wrapped
+/// health: A=40
+/// |  |F |  |
+/// |Y0|  |A0|
+/// |Y1|A2|A1|
+/// ```
+///
+/// A cell holds a snake letter followed by the index of its body part, or a range of indexes such
+/// as `A4-5` for parts stacked on one square. `S` followed by a letter is a snake stacked three
+/// deep at the start of a game. `F` is food, `H` a hazard, `G` a double hazard and `Z` a hazard
+/// with food. `Y` is our snake unless `you` says otherwise.
+///
+/// `mode`, `map`, `turn`, `hazard_damage`, `you` and `health` may be set on `key: value` lines,
+/// and lines starting with `#` are comments. Snake ids are their letters.
+pub fn parse(text: &str) -> Result<GameState, String> {
+    let mut annotations = Annotations::default();
+    let mut rows: Vec<Vec<&str>> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('|') {
+            let cells = line.trim_start_matches('|').split_terminator('|');
+            rows.push(cells.map(str::trim).collect());
+            continue;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("line {}: expected a board row or `key: value`", number + 1))?;
+        annotations
+            .set(key.trim(), value.trim())
+            .map_err(|e| format!("line {}: {}", number + 1, e))?;
+    }
+    let height = rows.len();
+    let width = rows.first().map(Vec::len).unwrap_or(0);
+    if width == 0 {
+        return Err("no board".to_owned());
+    }
+    let mut food: Vec<Value> = Vec::new();
+    let mut hazards: Vec<Value> = Vec::new();
+    let mut parts: BTreeMap<char, Vec<(u32, Value)>> = BTreeMap::new();
+    for (row, cells) in rows.iter().enumerate() {
+        if cells.len() != width {
+            return Err(format!(
+                "row {} has {} cells, but the first row has {}",
+                row + 1,
+                cells.len(),
+                width
+            ));
+        }
+        let y = height - 1 - row;
+        for (x, cell) in cells.iter().enumerate() {
+            let coord = json!({"x": x, "y": y});
+            let mut chars = cell.chars();
+            let (first, rest) = match chars.next() {
+                Some(first) => (first, chars.as_str()),
+                None => continue,
+            };
+            let invalid = || format!("row {}, column {}: invalid cell {:?}", row + 1, x + 1, cell);
+            match (first, rest) {
+                ('H', "") => hazards.push(coord),
+                ('F', "") => food.push(coord),
+                ('Z', "") => {
+                    hazards.push(coord.clone());
+                    food.push(coord);
+                }
+                ('G', "") => {
+                    hazards.push(coord.clone());
+                    hazards.push(coord);
+                }
+                ('S', letter) => {
+                    let letter = parse_letter(letter).map_err(|_| invalid())?;
+                    let body = parts.entry(letter).or_default();
+                    body.extend((0..=2).map(|i| (i, coord.clone())));
+                }
+                (letter, indexes) if letter.is_ascii_uppercase() => {
+                    let (start, end) = indexes.split_once('-').unwrap_or((indexes, indexes));
+                    let start: u32 = start.parse().map_err(|_| invalid())?;
+                    let end: u32 = end.parse().map_err(|_| invalid())?;
+                    let body = parts.entry(letter).or_default();
+                    body.extend((start..=end).map(|i| (i, coord.clone())));
+                }
+                _ => return Err(invalid()),
+            }
+        }
+    }
+    let mut snakes: Vec<Value> = Vec::new();
+    for (&letter, body) in parts.iter_mut() {
+        body.sort_by_key(|(i, _)| *i);
+        for (expected, (i, _)) in body.iter().enumerate() {
+            if *i != expected as u32 {
+                return Err(format!("snake {} has no part {}", letter, expected));
+            }
+        }
+        let body: Vec<Value> = body.iter().map(|(_, coord)| coord.clone()).collect();
+        let health = annotations.health.get(&letter).copied();
+        let snake = json!({
+            "id": letter.to_string(),
+            "name": "my_name",
+            "health": health.unwrap_or(DEFAULT_HEALTH),
+            "head": body[0],
+            "length": body.len(),
+            "body": body,
+            "latency": "100",
+            "shout": "shout!",
+            "squad": "squad",
+            "customizations": {"color": "color", "head": "head", "tail": "tail"},
+        });
+        // Our snake comes first, as it does in the tests this notation started in
+        if letter == annotations.you {
+            snakes.insert(0, snake);
+        } else {
+            snakes.push(snake);
+        }
+    }
+    let you = snakes
+        .iter()
+        .find(|snake| snake["id"] == annotations.you.to_string())
+        .cloned()
+        .ok_or_else(|| format!("no snake {} on the board", annotations.you))?;
+    let state = json!({
+        "game": {
+            "id": "gameid",
+            "ruleset": {
+                "name": annotations.mode,
+                "version": "1.13.0",
+                "settings": {
+                    "foodSpawnChance": 25,
+                    "minimumFood": 1,
+                    "hazardDamagePerTurn": annotations.hazard_damage,
+                    "royale": {"shrinkEveryNTurns": 5},
+                    "squad": {
+                        "allowBodyCollisions": true,
+                        "sharedElimination": true,
+                        "sharedHealth": true,
+                        "sharedLength": true,
+                    },
+                },
+            },
+            "map": annotations.map,
+            "timeout": 500,
+            "source": "custom",
+        },
+        "turn": annotations.turn,
+        "board": {
+            "height": height,
+            "width": width,
+            "food": food,
+            "hazards": hazards,
+            "snakes": snakes,
+        },
+        "you": you,
+    });
+    serde_json::from_value(state).map_err(|e| e.to_string())
+}
+
+fn coord(value: &Value) -> (i64, i64) {
+    (
+        value["x"].as_i64().unwrap_or(-1),
+        value["y"].as_i64().unwrap_or(-1),
+    )
+}
+
+/// Draws a game state in the notation `parse` reads.
+///
+/// Snakes whose ids aren't a free letter are given one, noted in a comment above the board.
+pub fn to_text(gs: &GameState) -> String {
+    let state = serde_json::to_value(gs).unwrap_or_default();
+    let board = &state["board"];
+    let width = board["width"].as_i64().unwrap_or(0).max(0);
+    let height = board["height"].as_i64().unwrap_or(0).max(0);
+    let you = state["you"]["id"].as_str().unwrap_or_default();
+    let snakes = board["snakes"].as_array().cloned().unwrap_or_default();
+
+    let mut letters: Vec<char> = Vec::new();
+    for snake in &snakes {
+        let id = snake["id"].as_str().unwrap_or_default();
+        let letter = if id == you {
+            'Y'
+        } else {
+            match parse_letter(id) {
+                Ok(letter) if !RESERVED_LETTERS.contains(letter) && letter != 'Y' => letter,
+                _ => '?',
+            }
+        };
+        letters.push(letter);
+    }
+    for i in 0..letters.len() {
+        if letters[i] == '?' || letters[..i].contains(&letters[i]) {
+            letters[i] = SNAKE_LETTERS
+                .chars()
+                .find(|letter| !letters.contains(letter))
+                .unwrap_or('?');
+        }
+    }
+
+    let mut cells: HashMap<(i64, i64), String> = HashMap::new();
+    let mut hazards: HashMap<(i64, i64), usize> = HashMap::new();
+    for hazard in board["hazards"].as_array().into_iter().flatten() {
+        *hazards.entry(coord(hazard)).or_default() += 1;
+    }
+    for (&square, &count) in &hazards {
+        cells.insert(square, if count > 1 { "G" } else { "H" }.to_owned());
+    }
+    for food in board["food"].as_array().into_iter().flatten() {
+        let square = coord(food);
+        let cell = if hazards.contains_key(&square) {
+            "Z"
+        } else {
+            "F"
+        };
+        cells.insert(square, cell.to_owned());
+    }
+    // Snakes are drawn over food and hazards, and the first snake on a square wins
+    let mut drawn: HashSet<(i64, i64)> = HashSet::new();
+    for (snake, letter) in snakes.iter().zip(&letters) {
+        let body: Vec<(i64, i64)> = snake["body"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(coord)
+            .collect();
+        if body.len() == 3 && body.iter().all(|&part| part == body[0]) {
+            if drawn.insert(body[0]) {
+                cells.insert(body[0], format!("S{}", letter));
+            }
+            continue;
+        }
+        // Stacked parts are drawn as a range of indexes
+        let mut i = 0;
+        while i < body.len() {
+            let mut end = i;
+            while end + 1 < body.len() && body[end + 1] == body[i] {
+                end += 1;
+            }
+            if drawn.insert(body[i]) {
+                let cell = if end == i {
+                    format!("{}{}", letter, i)
+                } else {
+                    format!("{}{}-{}", letter, i, end)
+                };
+                cells.insert(body[i], cell);
+            }
+            i = end + 1;
+        }
+    }
+
+    let mut text = String::new();
+    let ruleset = &state["game"]["ruleset"];
+    let mode = ruleset["name"].as_str().unwrap_or("standard");
+    if mode != "standard" {
+        text.push_str(&format!("mode: {}\n", mode));
+    }
+    let map = state["game"]["map"].as_str().unwrap_or("standard");
+    if map != "standard" {
+        text.push_str(&format!("map: {}\n", map));
+    }
+    let turn = state["turn"].as_u64().unwrap_or(0);
+    if turn != 0 {
+        text.push_str(&format!("turn: {}\n", turn));
+    }
+    let hazard_damage = ruleset["settings"]["hazardDamagePerTurn"]
+        .as_i64()
+        .unwrap_or(DEFAULT_HAZARD_DAMAGE);
+    if hazard_damage != DEFAULT_HAZARD_DAMAGE {
+        text.push_str(&format!("hazard_damage: {}\n", hazard_damage));
+    }
+    let health: Vec<String> = snakes
+        .iter()
+        .zip(&letters)
+        .filter_map(|(snake, letter)| {
+            let health = snake["health"].as_i64().unwrap_or(DEFAULT_HEALTH);
+            (health != DEFAULT_HEALTH).then(|| format!("{}={}", letter, health))
+        })
+        .collect();
+    if !health.is_empty() {
+        text.push_str(&format!("health: {}\n", health.join(" ")));
+    }
+    for (snake, letter) in snakes.iter().zip(&letters) {
+        let id = snake["id"].as_str().unwrap_or_default();
+        if id != letter.to_string() {
+            text.push_str(&format!("# {}: {}\n", letter, id));
+        }
+    }
+    let cell_width = cells.values().map(String::len).max().unwrap_or(0).max(2);
+    for y in (0..height).rev() {
+        text.push('|');
+        for x in 0..width {
+            let cell = cells.get(&(x, y)).map(String::as_str).unwrap_or("");
+            text.push_str(&format!("{:<width$}|", cell, width = cell_width));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let gs = parse(
+            "
+            mode: wrapped
+            turn: 7
+            health: A=40, Y=90
+            # Comments are ignored
+            |  |F |H  |
+            |Y0|Z |A0 |
+            |Y1|G |A1 |
+            |Y2|  |A2-3|
+            ",
+        )
+        .unwrap();
+        let state = serde_json::to_value(&gs).unwrap();
+        assert_eq!(state["game"]["ruleset"]["name"], "wrapped");
+        assert_eq!(state["turn"], 7);
+        assert_eq!(state["board"]["width"], 3);
+        assert_eq!(state["board"]["height"], 4);
+        assert_eq!(state["board"]["food"].as_array().unwrap().len(), 2);
+        assert_eq!(state["board"]["hazards"].as_array().unwrap().len(), 4);
+        assert_eq!(state["you"]["id"], "Y");
+        assert_eq!(state["you"]["health"], 90);
+        let snake = &state["board"]["snakes"][1];
+        assert_eq!(snake["id"], "A");
+        assert_eq!(snake["health"], 40);
+        assert_eq!(snake["length"], 4);
+        assert_eq!(snake["body"][3], json!({"x": 2, "y": 0}));
+        assert!(parse("|Y0|Y2|").is_err());
+        assert!(parse("|A0|").is_err());
+        assert!(parse("|Y0|\n|  |  |").is_err());
+        assert!(parse("size: 11\n|Y0|").is_err());
+    }
+
+    #[test]
+    fn test_long_snakes() {
+        let row = (0..12)
+            .map(|i| format!("Y{}", i))
+            .collect::<Vec<String>>()
+            .join("|");
+        let gs = parse(&format!("|{}|", row)).unwrap();
+        let state = serde_json::to_value(&gs).unwrap();
+        assert_eq!(state["you"]["length"], 12);
+        assert_eq!(state["you"]["body"][11], json!({"x": 11, "y": 0}));
+    }
+
+    #[test]
+    fn test_to_text() {
+        let text = "mode: royale
+turn: 3
+health: A=40
+# B: snake-two
+|F   |    |H   |    |
+|Y0  |    |A0  |    |
+|Y1  |Z   |A1  |    |
+|Y2  |G   |A2-3|SB  |
+";
+        let gs = parse(text).unwrap();
+        let mut state = serde_json::to_value(&gs).unwrap();
+        state["board"]["snakes"][2]["id"] = json!("snake-two");
+        let gs: GameState = serde_json::from_value(state).unwrap();
+        assert_eq!(to_text(&gs), text);
+        let again = parse(&to_text(&gs)).unwrap();
+        assert_eq!(to_text(&again), text.replace("# B: snake-two\n", ""));
+    }
+}
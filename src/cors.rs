@@ -0,0 +1,103 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::{Request, Response};
+use std::io::Cursor;
+
+/// Paths, and everything under them, that browsers on other origins may call. The game protocol
+/// endpoints are only ever called by the Battlesnake engine, so they're left alone.
+const CORS_PATHS: [&str; 4] = ["/analyze", "/simulate", "/render", "/games"];
+
+/// How long browsers may cache a preflight answer, in seconds.
+const PREFLIGHT_MAX_AGE: u32 = 3600;
+
+/// Lets browser tools hosted elsewhere, such as a board editor, call the analysis and game log
+/// endpoints, for the origins in the `cors_origins` config list. `"*"` allows any origin.
+///
+/// Preflight requests to those endpoints are answered here, as no route handles `OPTIONS`.
+#[derive(Debug, Clone)]
+pub struct Cors {
+    origins: Vec<String>,
+}
+
+impl Cors {
+    pub fn new(origins: Vec<String>) -> Self {
+        Cors { origins }
+    }
+    /// Whether requests from `origin` are allowed.
+    fn allows(&self, origin: &str) -> bool {
+        self.origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == origin)
+    }
+}
+
+/// Whether a request path is one of `CORS_PATHS` or under one.
+fn covered(path: &str) -> bool {
+    CORS_PATHS.iter().any(|covered| {
+        path.strip_prefix(covered)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if !covered(request.uri().path().as_str()) {
+            return;
+        }
+        let origin = match request.headers().get_one("Origin") {
+            Some(origin) if self.allows(origin) => origin.to_owned(),
+            _ => return,
+        };
+        response.set_header(Header::new("Access-Control-Allow-Origin", origin));
+        response.set_header(Header::new("Vary", "Origin"));
+        if request.method() == Method::Options {
+            response.set_status(Status::NoContent);
+            response.remove_header("Content-Type");
+            response.set_sized_body(0, Cursor::new(""));
+            response.set_header(Header::new(
+                "Access-Control-Allow-Methods",
+                "GET, POST, OPTIONS",
+            ));
+            response.set_header(Header::new(
+                "Access-Control-Allow-Headers",
+                "Content-Type, X-API-Key",
+            ));
+            response.set_header(Header::new(
+                "Access-Control-Max-Age",
+                PREFLIGHT_MAX_AGE.to_string(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_covered() {
+        assert!(covered("/analyze"));
+        assert!(covered("/analyze/board"));
+        assert!(covered("/games/abc/turns/3/render"));
+        assert!(!covered("/analyzed"));
+        assert!(!covered("/move"));
+        assert!(!covered("/v1/move"));
+    }
+
+    #[test]
+    fn test_allows() {
+        let cors = Cors::new(vec!["https://editor.example".to_owned()]);
+        assert!(cors.allows("https://editor.example"));
+        assert!(!cors.allows("https://other.example"));
+        assert!(Cors::new(vec!["*".to_owned()]).allows("anything"));
+        assert!(!Cors::new(Vec::new()).allows("https://editor.example"));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::battlesnake::EngineError;
+use rocket::http::Status;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::Responses;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use rocket_okapi::response::OpenApiResponderInner;
+use rocket_okapi::util::add_schema_response;
+use serde::{Deserialize, Serialize};
+
+/// Machine-readable error returned by every endpoint and catcher.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ApiError {
+    /// HTTP status code. Example: 404
+    code: u16,
+    /// Standard reason phrase for the status code. Example: "Not Found"
+    message: String,
+    /// What went wrong, when known. Example: "no route matches GET /snakes/unknown"
+    details: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(status: Status, details: Option<String>) -> Self {
+        ApiError {
+            code: status.code,
+            message: status.reason_lossy().to_owned(),
+            details,
+        }
+    }
+    pub fn internal(details: impl ToString) -> Self {
+        ApiError::new(Status::InternalServerError, Some(details.to_string()))
+    }
+}
+
+/// Input the engine can't play is the client's mistake.
+impl From<EngineError> for ApiError {
+    fn from(e: EngineError) -> Self {
+        ApiError::new(Status::UnprocessableEntity, Some(e.to_string()))
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ApiError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let status = Status::from_code(self.code).unwrap_or(Status::InternalServerError);
+        (status, Json(self)).respond_to(request)
+    }
+}
+
+impl OpenApiResponderInner for ApiError {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let schema = gen.json_schema::<ApiError>();
+        let mut responses = Responses::default();
+        for status in [404, 422, 500] {
+            add_schema_response(&mut responses, status, "application/json", schema.clone())?;
+        }
+        Ok(responses)
+    }
+}
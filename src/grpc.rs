@@ -0,0 +1,152 @@
+use crate::battlesnake::{self, GameState, RenderFormat, SimulateRequest};
+use crate::limits::Limits;
+use rocket::fairing::AdHoc;
+use rocket::tokio;
+use rocket::tokio::sync::mpsc;
+use rocket::tokio::task::spawn_blocking;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::net::SocketAddr;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+pub mod proto {
+    tonic::include_proto!("ndsquared.engine.v1");
+}
+
+use proto::engine_server::{Engine, EngineServer};
+
+/// Number of results buffered for each stream before the engine waits on the client.
+const STREAM_CAPACITY: usize = 16;
+
+type ResponseStream<T> = ReceiverStream<Result<T, Status>>;
+
+/// Serves the analysis tools over gRPC, from `grpc_address`, alongside the HTTP API.
+#[derive(Debug, Default)]
+pub struct EngineService {
+    /// The HTTP API's limits, so analyses here can't search bigger boards than there.
+    limits: Limits,
+}
+
+impl EngineService {
+    /// Starts the gRPC server once Rocket has launched, and stops it when Rocket shuts down.
+    pub fn fairing(address: SocketAddr) -> AdHoc {
+        AdHoc::on_liftoff("gRPC Server", move |rocket| {
+            let shutdown = rocket.shutdown();
+            let limits = rocket.state::<Limits>().cloned().unwrap_or_default();
+            Box::pin(async move {
+                tokio::spawn(async move {
+                    info!("serving gRPC on {}", address);
+                    let server = tonic::transport::Server::builder()
+                        .add_service(EngineServer::new(EngineService { limits }))
+                        .serve_with_shutdown(address, shutdown);
+                    if let Err(e) = server.await {
+                        warn!("gRPC server failed: {}", e);
+                    }
+                });
+            })
+        })
+    }
+}
+
+// Status is what every handler returns, however big
+#[allow(clippy::result_large_err)]
+fn decode<T: DeserializeOwned>(json: &str) -> Result<T, Status> {
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+#[allow(clippy::result_large_err)]
+fn encode<T: Serialize>(value: &T) -> Result<String, Status> {
+    serde_json::to_string(value).map_err(|e| Status::internal(e.to_string()))
+}
+
+/// Runs engine work off the async workers, like the HTTP handlers do.
+async fn blocking<T, F>(f: F) -> Result<T, Status>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    spawn_blocking(f)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))
+}
+
+async fn analyze(state: proto::State, limits: &Limits) -> Result<proto::Analysis, Status> {
+    let gs: GameState = decode(&state.json)?;
+    gs.validate()
+        .and_then(|()| limits.check_board(&gs))
+        .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let analysis = blocking(move || battlesnake::analyze(gs)).await?;
+    Ok(proto::Analysis {
+        json: encode(&analysis)?,
+    })
+}
+
+#[tonic::async_trait]
+impl Engine for EngineService {
+    type AnalyzeStream = ResponseStream<proto::Analysis>;
+    type SimulateStream = ResponseStream<proto::State>;
+
+    async fn analyze(
+        &self,
+        request: Request<Streaming<proto::State>>,
+    ) -> Result<Response<Self::AnalyzeStream>, Status> {
+        let mut states = request.into_inner();
+        let (sender, receiver) = mpsc::channel(STREAM_CAPACITY);
+        let limits = self.limits.clone();
+        tokio::spawn(async move {
+            loop {
+                let result = match states.message().await {
+                    Ok(Some(state)) => analyze(state, &limits).await,
+                    Ok(None) => break,
+                    Err(e) => Err(e),
+                };
+                // The response stream ends at its first error
+                let failed = result.is_err();
+                if sender.send(result).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn simulate(
+        &self,
+        request: Request<proto::SimulateRequest>,
+    ) -> Result<Response<Self::SimulateStream>, Status> {
+        let request: SimulateRequest = decode(&request.into_inner().json)?;
+        let states = blocking(move || battlesnake::simulate(request))
+            .await?
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let (sender, receiver) = mpsc::channel(STREAM_CAPACITY);
+        tokio::spawn(async move {
+            for state in states {
+                let result = encode(&state).map(|json| proto::State { json });
+                if sender.send(result).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn render(
+        &self,
+        request: Request<proto::RenderRequest>,
+    ) -> Result<Response<proto::Rendering>, Status> {
+        let request = request.into_inner();
+        let format = match request.format() {
+            proto::RenderFormat::Svg => RenderFormat::Svg,
+            proto::RenderFormat::Ascii => RenderFormat::Ascii,
+            proto::RenderFormat::Text => RenderFormat::Text,
+        };
+        let state = request
+            .state
+            .ok_or_else(|| Status::invalid_argument("missing state"))?;
+        let gs: GameState = decode(&state.json)?;
+        let territory = request.territory;
+        let content = blocking(move || battlesnake::render(gs, format, territory)).await?;
+        Ok(Response::new(proto::Rendering { content }))
+    }
+}
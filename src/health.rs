@@ -0,0 +1,81 @@
+use crate::battlesnake::SelfCheck;
+use rocket::http::Status;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::Responses;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use rocket_okapi::response::OpenApiResponderInner;
+use rocket_okapi::util::add_schema_response;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Search time given to the canned position searched by the readiness probe.
+pub const SELF_CHECK_BUDGET: Duration = Duration::from_millis(10);
+
+/// How long a probe waits for the blocking worker pool before reporting it wedged.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Result of a liveness or readiness probe.
+///
+/// Served with 200 when healthy and 503 otherwise, so orchestrators can gate on the status alone.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Health {
+    healthy: bool,
+    /// Move searches in progress.
+    searches: usize,
+    /// Time the probe took, in milliseconds.
+    elapsed: u64,
+    /// Result of searching the canned position, for readiness probes that got that far.
+    self_check: Option<SelfCheck>,
+    /// Why the probe failed. Example: "worker pool did not respond within 1s"
+    details: Option<String>,
+}
+
+impl Health {
+    pub fn new(searches: usize, elapsed: Duration) -> Self {
+        Health {
+            healthy: true,
+            searches,
+            elapsed: elapsed.as_millis() as u64,
+            self_check: None,
+            details: None,
+        }
+    }
+    pub fn with_self_check(mut self, self_check: SelfCheck) -> Self {
+        if !self_check.passed {
+            self = self.failed("canned search did not find the only safe move");
+        }
+        self.self_check = Some(self_check);
+        self
+    }
+    pub fn failed(mut self, details: impl ToString) -> Self {
+        self.healthy = false;
+        self.details = Some(details.to_string());
+        self
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Health {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let status = if self.healthy {
+            Status::Ok
+        } else {
+            Status::ServiceUnavailable
+        };
+        (status, Json(self)).respond_to(request)
+    }
+}
+
+impl OpenApiResponderInner for Health {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        let schema = gen.json_schema::<Health>();
+        let mut responses = Responses::default();
+        for status in [200, 503] {
+            add_schema_response(&mut responses, status, "application/json", schema.clone())?;
+        }
+        Ok(responses)
+    }
+}
@@ -0,0 +1,25 @@
+#[macro_use]
+extern crate rocket;
+
+pub mod admin;
+pub mod battlesnake;
+pub mod boards;
+pub mod cors;
+pub mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod limits;
+pub mod live;
+pub mod pool;
+pub mod profiling;
+pub mod puzzles;
+pub mod recorder;
+pub mod reload;
+pub mod replay;
+pub mod slo;
+pub mod stats;
+pub mod timing;
+pub mod tournament;
+pub mod trace;
+pub mod version;
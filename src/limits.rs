@@ -0,0 +1,290 @@
+use crate::battlesnake::{EngineError, GameState};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+/// Largest board, in squares a side, searched unless configured otherwise. The biggest official
+/// boards are 25x25.
+pub const DEFAULT_MAX_BOARD_SIZE: i32 = 25;
+
+/// Requests each client may make to the searching Battlesnake protocol endpoints when rate
+/// limiting is on and `move_rate_limit` isn't configured: enough for a game engine running a
+/// hundred games at once from one address.
+pub const DEFAULT_MOVE_RATE_LIMIT: RateLimit = RateLimit {
+    burst: 200,
+    per_second: 100.0,
+};
+
+/// Clients tracked before the least recently counted is forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Requests each client may make to the analysis and admin endpoints, read from the
+/// `rate_limit` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct RateLimit {
+    /// Requests a client may make at once after being idle.
+    pub burst: u32,
+    /// Requests a client gets back every second.
+    pub per_second: f64,
+}
+
+/// Tokens left in a client's bucket, as of when they were last counted.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    counted: Instant,
+}
+
+/// Every tracked client's bucket, and the clients in the order they were last counted so the
+/// stalest can be forgotten without looking at the rest.
+#[derive(Debug, Default)]
+struct Buckets {
+    by_client: HashMap<IpAddr, Bucket>,
+    by_counted: BTreeSet<(Instant, IpAddr)>,
+}
+
+/// A token bucket of requests per client IP, which lets everyone through without a rate.
+#[derive(Debug, Default)]
+struct Limiter {
+    rate: Option<RateLimit>,
+    buckets: Mutex<Buckets>,
+}
+
+impl Limiter {
+    fn new(rate: Option<RateLimit>) -> Self {
+        Limiter {
+            rate: rate.filter(|rate| rate.burst > 0 && rate.per_second > 0.0),
+            buckets: Mutex::default(),
+        }
+    }
+    /// Takes a request from the client's bucket, returning whether it had one left.
+    fn allow(&self, client: IpAddr, now: Instant) -> bool {
+        let rate = match self.rate {
+            Some(rate) => rate,
+            None => return true,
+        };
+        let burst = rate.burst as f64;
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let Buckets {
+            by_client,
+            by_counted,
+        } = &mut *buckets;
+        let bucket = by_client.entry(client).or_insert(Bucket {
+            tokens: burst,
+            counted: now,
+        });
+        by_counted.remove(&(bucket.counted, client));
+        let refilled = now.duration_since(bucket.counted).as_secs_f64() * rate.per_second;
+        bucket.tokens = (bucket.tokens + refilled).min(burst);
+        bucket.counted = now;
+        by_counted.insert((now, client));
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        // A client that comes back after being forgotten starts again from a full burst
+        while by_client.len() > MAX_TRACKED_CLIENTS {
+            match by_counted.pop_first() {
+                Some((_, stalest)) => by_client.remove(&stalest),
+                None => break,
+            };
+        }
+        allowed
+    }
+}
+
+/// What a public server is willing to spend its CPU on: token buckets of requests per client
+/// IP, when `rate_limit` is configured, and the largest board it will search. Clones share their
+/// buckets.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// For the analysis and admin endpoints.
+    analysis: Arc<Limiter>,
+    /// For the Battlesnake protocol endpoints that search, which game engines call far more.
+    moves: Arc<Limiter>,
+    max_board_size: i32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::new(None, DEFAULT_MAX_BOARD_SIZE)
+    }
+}
+
+impl Limits {
+    /// Limits clients to `rate` on the analysis endpoints and, when that's set, to
+    /// `DEFAULT_MOVE_RATE_LIMIT` on the protocol endpoints.
+    pub fn new(rate: Option<RateLimit>, max_board_size: i32) -> Self {
+        let analysis = Limiter::new(rate);
+        if analysis.rate.is_none() {
+            info!("no rate_limit configured, clients aren't rate limited");
+        }
+        let moves = Limiter::new(analysis.rate.map(|_| DEFAULT_MOVE_RATE_LIMIT));
+        Limits {
+            analysis: Arc::new(analysis),
+            moves: Arc::new(moves),
+            max_board_size,
+        }
+    }
+    /// Limits clients to `rate` on the protocol endpoints that search instead.
+    pub fn with_move_rate(mut self, rate: RateLimit) -> Self {
+        self.moves = Arc::new(Limiter::new(Some(rate)));
+        self
+    }
+    /// Takes an analysis request from the client's bucket, returning whether it had one left.
+    fn allow(&self, client: IpAddr, now: Instant) -> bool {
+        self.analysis.allow(client, now)
+    }
+    /// Takes a start or move request from the client's bucket, returning whether it had one left.
+    fn allow_move(&self, client: IpAddr, now: Instant) -> bool {
+        self.moves.allow(client, now)
+    }
+    /// Checks a game is on a board small enough to search, before any search is scheduled.
+    pub fn check_board(&self, gs: &GameState) -> Result<(), EngineError> {
+        gs.validate_board_size(self.max_board_size)
+    }
+}
+
+/// Turns away clients over the rate limit `allow` keeps with 429 Too Many Requests. Clients whose
+/// address isn't known, such as those of local test clients, aren't limited.
+fn throttle(
+    request: &Request<'_>,
+    allow: fn(&Limits, IpAddr, Instant) -> bool,
+) -> Result<(), (Status, &'static str)> {
+    let (limits, client) = match (request.rocket().state::<Limits>(), request.client_ip()) {
+        (Some(limits), Some(client)) => (limits, client),
+        _ => return Ok(()),
+    };
+    if allow(limits, client, Instant::now()) {
+        Ok(())
+    } else {
+        warn!("rate limited {}", client);
+        Err((Status::TooManyRequests, "rate limit exceeded"))
+    }
+}
+
+/// Request guard for the analysis and admin endpoints, turning away clients over the rate limit
+/// before their body is read.
+pub struct Throttle;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Throttle {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match throttle(request, Limits::allow) {
+            Ok(()) => Outcome::Success(Throttle),
+            Err(e) => Outcome::Error(e),
+        }
+    }
+}
+
+/// Request guard for the Battlesnake protocol endpoints that search, `/start` and `/move`. Their
+/// bucket is far bigger than the analysis one, as a game engine sends a burst of moves for games
+/// in parallel, but still stops a client hammering them with boards to search.
+pub struct MoveThrottle;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for MoveThrottle {
+    type Error = &'static str;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match throttle(request, Limits::allow_move) {
+            Ok(()) => Outcome::Success(MoveThrottle),
+            Err(e) => Outcome::Error(e),
+        }
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for MoveThrottle {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for Throttle {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_allow() {
+        let limits = Limits::new(
+            Some(RateLimit {
+                burst: 2,
+                per_second: 4.0,
+            }),
+            DEFAULT_MAX_BOARD_SIZE,
+        );
+        let (client, other) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        let now = Instant::now();
+        assert!(limits.allow(client, now));
+        assert!(limits.allow(client, now));
+        assert!(!limits.allow(client, now));
+        // Each client has a bucket of its own
+        assert!(limits.allow(other, now));
+        // A quarter of a second brings back one request, and idling never more than the burst
+        assert!(limits.allow(client, now + Duration::from_millis(250)));
+        assert!(!limits.allow(client, now + Duration::from_millis(250)));
+        let later = now + Duration::from_secs(60);
+        assert!(limits.allow(client, later));
+        assert!(limits.allow(client, later));
+        assert!(!limits.allow(client, later));
+        // The least recently counted clients are forgotten beyond the cap
+        for i in 0..MAX_TRACKED_CLIENTS as u32 {
+            limits.allow(IpAddr::from(i.to_be_bytes()), later);
+        }
+        let buckets = limits.analysis.buckets.lock().unwrap();
+        assert_eq!(buckets.by_client.len(), MAX_TRACKED_CLIENTS);
+        assert_eq!(buckets.by_counted.len(), MAX_TRACKED_CLIENTS);
+        assert!(!buckets.by_client.contains_key(&other));
+        drop(buckets);
+        // Unconfigured, nobody is limited
+        let unlimited = Limits::default();
+        assert!((0..100).all(|_| unlimited.allow(client, now)));
+        assert!((0..100).all(|_| unlimited.allow_move(client, now)));
+    }
+
+    #[test]
+    fn test_allow_move() {
+        let rate = RateLimit {
+            burst: 2,
+            per_second: 4.0,
+        };
+        let limits = Limits::new(Some(rate), DEFAULT_MAX_BOARD_SIZE);
+        let client = "10.0.0.1".parse().unwrap();
+        let now = Instant::now();
+        // Moves have a bucket of their own, far bigger than the analysis one
+        let burst = DEFAULT_MOVE_RATE_LIMIT.burst as usize;
+        assert!((0..burst).all(|_| limits.allow_move(client, now)));
+        assert!(!limits.allow_move(client, now));
+        assert!(limits.allow(client, now));
+        let limits = Limits::new(Some(rate), DEFAULT_MAX_BOARD_SIZE).with_move_rate(RateLimit {
+            burst: 1,
+            per_second: 1.0,
+        });
+        assert!(limits.allow_move(client, now));
+        assert!(!limits.allow_move(client, now));
+    }
+}
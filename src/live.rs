@@ -0,0 +1,31 @@
+use crate::battlesnake::Telemetry;
+use rocket::tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// Number of turns buffered for each subscriber before slow readers start skipping turns.
+const LIVE_CAPACITY: usize = 64;
+
+/// Broadcasts the telemetry of every turn played to any connected /live subscribers.
+#[derive(Debug)]
+pub struct LiveFeed {
+    sender: Sender<Telemetry>,
+}
+
+impl Default for LiveFeed {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(LIVE_CAPACITY);
+        LiveFeed { sender }
+    }
+}
+
+impl LiveFeed {
+    pub fn enabled(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+    pub fn publish(&self, telemetry: Telemetry) {
+        // Sending only fails when nobody is subscribed, which is fine to ignore.
+        let _ = self.sender.send(telemetry);
+    }
+    pub fn subscribe(&self) -> Receiver<Telemetry> {
+        self.sender.subscribe()
+    }
+}
@@ -1,22 +1,60 @@
 #[macro_use]
 extern crate rocket;
 
-use rocket::http::Status;
-use rocket::response::Debug;
+use ndsquared_rustapi::admin::{Admin, AdminAuth};
+use ndsquared_rustapi::battlesnake;
+use ndsquared_rustapi::battlesnake::{
+    Decision, Experiment, GameSessions, Personalities, Personality, RenderFormat, Shadow,
+    StrategyOverride, WARM_UP_BUDGET,
+};
+use ndsquared_rustapi::boards;
+use ndsquared_rustapi::cors::Cors;
+use ndsquared_rustapi::error::ApiError;
+#[cfg(feature = "grpc")]
+use ndsquared_rustapi::grpc::EngineService;
+use ndsquared_rustapi::health::{Health, PROBE_TIMEOUT, SELF_CHECK_BUDGET};
+use ndsquared_rustapi::limits::{
+    Limits, MoveThrottle, RateLimit, Throttle, DEFAULT_MAX_BOARD_SIZE,
+};
+use ndsquared_rustapi::live::LiveFeed;
+use ndsquared_rustapi::pool::SearchPool;
+use ndsquared_rustapi::recorder::{GameRecord, GameRecorder};
+use ndsquared_rustapi::reload::{self, ConfigWatch, LivePersonalities};
+use ndsquared_rustapi::replay::{self, EngineExport};
+use ndsquared_rustapi::slo::{self, ResponseSlo, SloConfig};
+use ndsquared_rustapi::stats::{Performance, Stats, StatsStore};
+use ndsquared_rustapi::timing::RequestStart;
+use ndsquared_rustapi::trace::{self, RequestSpan, Traced};
+use ndsquared_rustapi::version::BuildInfo;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::futures::stream::{BoxStream, StreamExt};
+use rocket::http::{ContentType, Status};
+use rocket::response::stream::{stream, Event, EventStream};
 use rocket::serde::json::Json;
-use rocket::tokio::task::{spawn_blocking, JoinError};
-use rocket_okapi::{openapi, openapi_get_routes, swagger_ui::*};
-use std::env;
-
-mod battlesnake;
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::tokio::task::spawn_blocking;
+use rocket::tokio::time::{sleep, timeout, timeout_at, Instant};
+use rocket::{Build, Orbit, Request, Rocket, Shutdown, State};
+use rocket_okapi::settings::OpenApiSettings;
+use rocket_okapi::{
+    mount_endpoints_and_merged_docs, openapi, openapi_get_routes_spec, swagger_ui::*,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::PoisonError;
+use std::time::Duration;
 
 /// # Get Info
 ///
 /// Returns Battlesnake info for health validation, customization, and latency.
 #[openapi(tag = "Battlesnake")]
 #[get("/")]
-fn handle_index() -> Json<battlesnake::Info> {
-    Json(battlesnake::info())
+fn handle_index(personalities: &State<LivePersonalities>) -> Json<battlesnake::Info> {
+    Json(battlesnake::info(
+        personalities.current().default_personality(),
+    ))
 }
 
 /// # Game Start
@@ -24,8 +62,66 @@ fn handle_index() -> Json<battlesnake::Info> {
 /// This request is received when the Battlesnake has been entered into a new game.
 #[openapi(tag = "Battlesnake")]
 #[post("/start", format = "json", data = "<gs>")]
-fn handle_start(gs: Json<battlesnake::GameState>) -> Status {
-    battlesnake::start(gs.into_inner());
+async fn handle_start(
+    _throttle: MoveThrottle,
+    gs: Json<battlesnake::GameState>,
+    sessions: &State<GameSessions>,
+    recorder: &State<GameRecorder>,
+    pool: &State<SearchPool>,
+    limits: &State<Limits>,
+    personalities: &State<LivePersonalities>,
+) -> Status {
+    let personality = personalities.current().default_personality().clone();
+    start_game(
+        gs.into_inner(),
+        sessions,
+        recorder,
+        pool,
+        limits,
+        personality,
+    )
+    .await
+}
+
+async fn start_game(
+    gs: battlesnake::GameState,
+    sessions: &GameSessions,
+    recorder: &GameRecorder,
+    pool: &SearchPool,
+    limits: &Limits,
+    mut personality: Personality,
+) -> Status {
+    // Games that ended without an end request leave their sessions and preferred workers behind
+    for key in sessions.evict_idle(std::time::Instant::now()) {
+        pool.release(&key);
+    }
+    let session = sessions.start(&gs);
+    if recorder.enabled() {
+        recorder.record(&GameRecord::Start { state: gs.clone() });
+    }
+    let budget = sessions.warm_up();
+    if !budget.is_zero() && gs.validate().is_ok() && limits.check_board(&gs).is_ok() {
+        if let Some(variant) = session
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .variant()
+        {
+            personality = personality.with_strategy(variant);
+        }
+        let state = gs.clone();
+        // Warming up on the snake's worker leaves its caches ready for the first move, but isn't
+        // worth holding up anyone's search for
+        let warm_up = move || battlesnake::warm_up(state, &personality, budget);
+        let key = battlesnake::session_key(&gs);
+        match pool.run_spare(&key, std::time::Instant::now() + budget, warm_up) {
+            Some(warm_up) => match warm_up.await {
+                Ok(depth) => debug!("warmed up to depth {}", depth),
+                Err(e) => warn!("warm-up failed: {}", e),
+            },
+            None => debug!("skipped warm-up with every search worker busy"),
+        }
+    }
+    battlesnake::start(gs);
     Status::Ok
 }
 
@@ -34,11 +130,195 @@ fn handle_start(gs: Json<battlesnake::GameState>) -> Status {
 /// This request will be sent for every turn of the game. Use the information provided to determine how your Battlesnake will move on that turn, either up, down, left, or right.
 #[openapi(tag = "Battlesnake")]
 #[post("/move", format = "json", data = "<gs>")]
+#[allow(clippy::too_many_arguments)]
 async fn handle_move(
+    start: RequestStart,
+    span: RequestSpan,
+    _throttle: MoveThrottle,
     gs: Json<battlesnake::GameState>,
-) -> Result<Json<battlesnake::MoveResponse>, Debug<JoinError>> {
-    let result = spawn_blocking(move || Json(battlesnake::make_move(gs.into_inner()))).await?;
-    Ok(result)
+    sessions: &State<GameSessions>,
+    recorder: &State<GameRecorder>,
+    live: &State<LiveFeed>,
+    pool: &State<SearchPool>,
+    limits: &State<Limits>,
+    slo: &State<ResponseSlo>,
+    personalities: &State<LivePersonalities>,
+) -> Result<Traced<Json<battlesnake::MoveResponse>>, ApiError> {
+    let personality = personalities.current().default_personality().clone();
+    search_move(
+        start,
+        span,
+        gs.into_inner(),
+        sessions,
+        recorder,
+        live,
+        pool,
+        limits,
+        slo,
+        personality,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn search_move(
+    start: RequestStart,
+    span: RequestSpan,
+    mut gs: battlesnake::GameState,
+    sessions: &GameSessions,
+    recorder: &GameRecorder,
+    live: &LiveFeed,
+    pool: &SearchPool,
+    limits: &Limits,
+    slo: &ResponseSlo,
+    mut personality: Personality,
+) -> Result<Traced<Json<battlesnake::MoveResponse>>, ApiError> {
+    let span = span.parsed();
+    // An error would cost the game, so states the engine can't search still get a legal answer
+    if let Err(e) = gs.validate().and_then(|()| limits.check_board(&gs)) {
+        tracing::warn!(
+            game_id = gs.game_id(),
+            turn = gs.turn(),
+            error = %e,
+            "engine error, answering with the fallback move"
+        );
+        return Ok(Traced(Json(battlesnake::fallback_move(gs)), span));
+    }
+    let session = sessions.get(&gs);
+    let (turn, position) = (gs.turn(), gs.position_key());
+    // A retry of a request we've answered gets the same answer, without searching it again
+    if let Some(response) = session
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .cached_response(turn, position, start.0)
+    {
+        tracing::info!(
+            game_id = gs.game_id(),
+            turn,
+            "answered a repeated move request from the cache"
+        );
+        return Ok(Traced(Json(response), span));
+    }
+    let _search = sessions.begin_search();
+    let state = recorder.enabled().then(|| gs.clone());
+    let fallback = gs.clone();
+    let (deadline, profile, tendencies) = {
+        let mut session = session.lock().unwrap_or_else(PoisonError::into_inner);
+        session.observe(&gs);
+        // The engine reports how long our last move took to reach it, network and all
+        if let Some(alert) = session
+            .latency()
+            .and_then(|latency| slo.observe(latency, gs.timeout()))
+        {
+            let webhook = slo.webhook().map(str::to_owned);
+            spawn_blocking(move || slo::report(&alert, webhook.as_deref()));
+        }
+        gs.predict_shrink(session.hazard_schedule());
+        gs.predict_food(session.food_spawns());
+        if let Some(strategy) = session.strategy().or(session.variant()) {
+            personality = personality.with_strategy(strategy);
+        }
+        (
+            start.0 + battlesnake::move_deadline(&gs, &session),
+            session.profile(),
+            session.tendencies().clone(),
+        )
+    };
+    let search_span = tracing::info_span!(
+        parent: &span,
+        "search",
+        game_id = gs.game_id(),
+        turn = gs.turn()
+    );
+    let game_id = gs.game_id().to_owned();
+    let key = battlesnake::session_key(&gs);
+    let shadow = sessions
+        .shadow()
+        .cloned()
+        .map(|shadow| (shadow, gs.clone(), personality.clone(), tendencies.clone()));
+    let search = pool.run(&key, deadline, move || {
+        search_span
+            .in_scope(|| battlesnake::make_move(gs, &personality, deadline, profile, &tendencies))
+    });
+    // The shadow only runs on a worker left idle once the real search has one, and is skipped
+    // under load
+    let shadow = shadow.and_then(|(shadow, state, personality, tendencies)| {
+        let strategy = shadow.strategy();
+        let search = pool.run_spare(&key, deadline, move || {
+            battlesnake::shadow_move(state, &personality, &shadow, deadline, &tendencies)
+        })?;
+        Some((strategy, search))
+    });
+    // A late or missing response counts as a timeout, so always answer with something legal
+    let (result, searched) = match timeout_at(Instant::from_std(deadline), search).await {
+        Ok(Ok(Ok((result, telemetry)))) => {
+            let searched = (telemetry.score(), telemetry.depth());
+            if live.enabled() {
+                live.publish(telemetry);
+            }
+            (result, Some(searched))
+        }
+        Ok(Ok(Err(e))) => {
+            tracing::warn!(
+                game_id = fallback.game_id(),
+                turn = fallback.turn(),
+                error = %e,
+                "engine error, answering with the fallback move"
+            );
+            (battlesnake::fallback_move(fallback), None)
+        }
+        Ok(Err(e)) => {
+            warn!("search failed: {}", e);
+            (battlesnake::fallback_move(fallback), None)
+        }
+        Err(_) => {
+            warn!(
+                "search overran its deadline, {:?} after the request arrived",
+                deadline - start.0
+            );
+            (battlesnake::fallback_move(fallback), None)
+        }
+    };
+    // The shadow stops before the real deadline, so this only waits out what's left of its budget
+    let shadow = match shadow {
+        Some((strategy, search)) => match timeout_at(Instant::from_std(deadline), search).await {
+            Ok(Ok(Ok(direction))) => {
+                tracing::info!(
+                    game_id = game_id.as_str(),
+                    turn,
+                    strategy = ?strategy,
+                    played = ?result.direction(),
+                    shadow = ?direction,
+                    agreed = direction == result.direction(),
+                    "shadow move"
+                );
+                Some(direction)
+            }
+            _ => None,
+        },
+        None => None,
+    };
+    if let Some(state) = state {
+        recorder.record(&GameRecord::Move {
+            position_key: Some(format!("{:016x}", state.position_key())),
+            state,
+            response: result.clone(),
+            label: None,
+        });
+    }
+    let mut session = session.lock().unwrap_or_else(PoisonError::into_inner);
+    let elapsed = start.0.elapsed();
+    session.responded(elapsed);
+    session.decided(Decision {
+        turn,
+        direction: result.direction(),
+        score: searched.map(|(score, _)| score),
+        depth: searched.map_or(0, |(_, depth)| depth),
+        elapsed: elapsed.as_millis() as u32,
+        shadow,
+    });
+    session.answered(turn, position, result.clone(), start.0 + elapsed);
+    Ok(Traced(Json(result), span))
 }
 
 /// # Game End
@@ -46,11 +326,394 @@ async fn handle_move(
 /// Your Battlesnake will receive this request whenever a game it was playing has ended.
 #[openapi(tag = "Battlesnake")]
 #[post("/end", format = "json", data = "<gs>")]
-fn handle_end(gs: Json<battlesnake::GameState>) -> Status {
-    battlesnake::end(gs.into_inner());
+fn handle_end(
+    gs: Json<battlesnake::GameState>,
+    sessions: &State<GameSessions>,
+    recorder: &State<GameRecorder>,
+    stats: &State<StatsStore>,
+    pool: &State<SearchPool>,
+) -> Status {
+    let gs = gs.into_inner();
+    pool.release(&battlesnake::session_key(&gs));
+    let session = sessions
+        .end(&gs)
+        .map(|s| s.lock().unwrap_or_else(PoisonError::into_inner).clone());
+    let outcome = battlesnake::outcome(&gs, session.as_ref());
+    let post_mortem = battlesnake::post_mortem(&outcome, session.as_ref());
+    if stats.enabled() {
+        let moves = session.as_ref().map(|s| s.history()).unwrap_or_default();
+        stats.record(&outcome, moves);
+    }
+    if recorder.enabled() {
+        recorder.record(&GameRecord::End {
+            state: gs.clone(),
+            post_mortem: Some(post_mortem.clone()),
+        });
+        recorder.label(&outcome);
+    }
+    battlesnake::end(gs, &post_mortem);
     Status::Ok
 }
 
+/// # Get Personality Info
+///
+/// Returns Battlesnake info for the named personality.
+#[openapi(tag = "Personalities")]
+#[get("/snakes/<name>")]
+fn handle_snake_index(
+    name: &str,
+    personalities: &State<LivePersonalities>,
+) -> Option<Json<battlesnake::Info>> {
+    personalities
+        .current()
+        .get(name)
+        .map(|personality| Json(battlesnake::info(personality)))
+}
+
+/// # Personality Game Start
+///
+/// Game start for the named personality.
+#[openapi(tag = "Personalities")]
+#[post("/snakes/<name>/start", format = "json", data = "<gs>")]
+#[allow(clippy::too_many_arguments)]
+async fn handle_snake_start(
+    name: &str,
+    _throttle: MoveThrottle,
+    gs: Json<battlesnake::GameState>,
+    sessions: &State<GameSessions>,
+    recorder: &State<GameRecorder>,
+    pool: &State<SearchPool>,
+    limits: &State<Limits>,
+    personalities: &State<LivePersonalities>,
+) -> Status {
+    let personality = match personalities.current().get(name) {
+        Some(personality) => personality.clone(),
+        None => return Status::NotFound,
+    };
+    start_game(
+        gs.into_inner(),
+        sessions,
+        recorder,
+        pool,
+        limits,
+        personality,
+    )
+    .await
+}
+
+/// # Personality Move
+///
+/// Move for the named personality, using its own strategy.
+#[openapi(tag = "Personalities")]
+#[post("/snakes/<name>/move", format = "json", data = "<gs>")]
+#[allow(clippy::too_many_arguments)]
+async fn handle_snake_move(
+    name: &str,
+    start: RequestStart,
+    span: RequestSpan,
+    _throttle: MoveThrottle,
+    gs: Json<battlesnake::GameState>,
+    sessions: &State<GameSessions>,
+    recorder: &State<GameRecorder>,
+    live: &State<LiveFeed>,
+    pool: &State<SearchPool>,
+    limits: &State<Limits>,
+    slo: &State<ResponseSlo>,
+    personalities: &State<LivePersonalities>,
+) -> Option<Result<Traced<Json<battlesnake::MoveResponse>>, ApiError>> {
+    let personality = personalities.current().get(name)?.clone();
+    Some(
+        search_move(
+            start,
+            span,
+            gs.into_inner(),
+            sessions,
+            recorder,
+            live,
+            pool,
+            limits,
+            slo,
+            personality,
+        )
+        .await,
+    )
+}
+
+/// # Personality Game End
+///
+/// Game end for the named personality.
+#[openapi(tag = "Personalities")]
+#[post("/snakes/<name>/end", format = "json", data = "<gs>")]
+fn handle_snake_end(
+    name: &str,
+    gs: Json<battlesnake::GameState>,
+    sessions: &State<GameSessions>,
+    recorder: &State<GameRecorder>,
+    stats: &State<StatsStore>,
+    pool: &State<SearchPool>,
+    personalities: &State<LivePersonalities>,
+) -> Status {
+    if personalities.current().get(name).is_none() {
+        return Status::NotFound;
+    }
+    handle_end(gs, sessions, recorder, stats, pool)
+}
+
+/// # Analyze
+///
+/// Searches the posted game state like /move, but returns the full search results: score breakdown, principal variation as every snake's moves, root move scores, territory ownership, each of our four moves compared by the same shallow search, and search statistics. With `boards=true`, also the board text after every turn of the principal variation, which requires the render feature.
+#[openapi(tag = "Analysis")]
+#[post("/analyze?<boards>", format = "json", data = "<gs>")]
+async fn handle_analyze(
+    _throttle: Throttle,
+    gs: Json<battlesnake::GameState>,
+    boards: Option<bool>,
+    limits: &State<Limits>,
+) -> Result<Json<battlesnake::Analysis>, ApiError> {
+    gs.validate()?;
+    limits.check_board(&gs)?;
+    let boards = boards.unwrap_or(false);
+    spawn_blocking(move || analyze(gs.into_inner(), boards))
+        .await
+        .map_err(ApiError::internal)?
+        .map(Json)
+}
+
+#[cfg(feature = "render")]
+fn analyze(gs: battlesnake::GameState, boards: bool) -> Result<battlesnake::Analysis, ApiError> {
+    if boards {
+        return Ok(battlesnake::analyze_with_boards(gs));
+    }
+    Ok(battlesnake::analyze(gs))
+}
+
+#[cfg(not(feature = "render"))]
+fn analyze(gs: battlesnake::GameState, boards: bool) -> Result<battlesnake::Analysis, ApiError> {
+    if boards {
+        return Err(ApiError::new(
+            Status::NotImplemented,
+            Some("built without the render feature".to_owned()),
+        ));
+    }
+    Ok(battlesnake::analyze(gs))
+}
+
+/// # Simulate
+///
+/// Steps the posted game state forward by applying the given moves for each snake, returning the game state after every turn.
+#[openapi(tag = "Analysis")]
+#[post("/simulate", format = "json", data = "<request>")]
+async fn handle_simulate(
+    _throttle: Throttle,
+    request: Json<battlesnake::SimulateRequest>,
+) -> Result<Json<Vec<battlesnake::GameState>>, ApiError> {
+    let states = spawn_blocking(move || battlesnake::simulate(request.into_inner()))
+        .await
+        .map_err(ApiError::internal)??;
+    Ok(Json(states))
+}
+
+/// # Import
+///
+/// Converts a game exported by the official Battlesnake engine (the game and its frames) into the game state the given snake, by id or name, received on every turn it was alive. Defaults to the first snake. The states can be posted to /analyze as they are.
+#[openapi(tag = "Analysis")]
+#[post("/import?<snake>", format = "json", data = "<export>")]
+fn handle_import(
+    _throttle: Throttle,
+    export: Json<EngineExport>,
+    snake: Option<&str>,
+) -> Result<Json<Vec<battlesnake::GameState>>, ApiError> {
+    replay::import(&export, snake)
+        .map(Json)
+        .map_err(|e| ApiError::new(Status::UnprocessableEntity, Some(e)))
+}
+
+/// # Parse Board
+///
+/// Converts a board drawn in the text notation of the unit tests into a game state, which can be posted to /analyze as it is. /render draws a game state back in the notation with `format=text`.
+#[openapi(tag = "Analysis")]
+#[post("/board", format = "plain", data = "<text>")]
+fn handle_board(
+    _throttle: Throttle,
+    text: String,
+) -> Result<Json<battlesnake::GameState>, ApiError> {
+    boards::parse(&text)
+        .map(Json)
+        .map_err(|e| ApiError::new(Status::UnprocessableEntity, Some(e)))
+}
+
+/// # Analyze Board
+///
+/// Analyzes a board drawn in the text notation read by /board, like /analyze.
+#[openapi(tag = "Analysis")]
+#[post("/analyze/board", format = "plain", data = "<text>")]
+async fn handle_analyze_board(
+    _throttle: Throttle,
+    text: String,
+    limits: &State<Limits>,
+) -> Result<Json<battlesnake::Analysis>, ApiError> {
+    let gs =
+        boards::parse(&text).map_err(|e| ApiError::new(Status::UnprocessableEntity, Some(e)))?;
+    gs.validate()?;
+    limits.check_board(&gs)?;
+    spawn_blocking(move || Json(battlesnake::analyze(gs)))
+        .await
+        .map_err(ApiError::internal)
+}
+
+/// # Render
+///
+/// Draws the posted game state as SVG (the default), ASCII or board text, optionally shading the squares each snake controls. Requires the render feature.
+#[openapi(tag = "Analysis")]
+#[post("/render?<format>&<territory>", format = "json", data = "<gs>")]
+async fn handle_render(
+    _throttle: Throttle,
+    gs: Json<battlesnake::GameState>,
+    format: Option<RenderFormat>,
+    territory: Option<bool>,
+) -> Result<(ContentType, String), ApiError> {
+    render(gs.into_inner(), format, territory).await
+}
+
+#[cfg(feature = "render")]
+async fn render(
+    gs: battlesnake::GameState,
+    format: Option<RenderFormat>,
+    territory: Option<bool>,
+) -> Result<(ContentType, String), ApiError> {
+    let format = format.unwrap_or_default();
+    let content_type = match format {
+        RenderFormat::Svg => ContentType::SVG,
+        RenderFormat::Ascii | RenderFormat::Text => ContentType::Plain,
+    };
+    let territory = territory.unwrap_or(false);
+    let image = spawn_blocking(move || battlesnake::render(gs, format, territory))
+        .await
+        .map_err(ApiError::internal)?;
+    Ok((content_type, image))
+}
+
+#[cfg(not(feature = "render"))]
+async fn render(
+    _gs: battlesnake::GameState,
+    _format: Option<RenderFormat>,
+    _territory: Option<bool>,
+) -> Result<(ContentType, String), ApiError> {
+    Err(ApiError::new(
+        Status::NotImplemented,
+        Some("built without the render feature".to_owned()),
+    ))
+}
+
+/// # List Games
+///
+/// Returns the ids of all recorded games.
+#[openapi(tag = "Games")]
+#[get("/games")]
+fn handle_games(
+    _throttle: Throttle,
+    _admin: Admin,
+    recorder: &State<GameRecorder>,
+) -> Json<Vec<String>> {
+    Json(recorder.games())
+}
+
+/// # Get Game
+///
+/// Returns every request and response recorded for a game, in the order they were received.
+#[openapi(tag = "Games")]
+#[get("/games/<id>")]
+fn handle_game(
+    id: &str,
+    _throttle: Throttle,
+    _admin: Admin,
+    recorder: &State<GameRecorder>,
+) -> Result<Json<Vec<GameRecord>>, ApiError> {
+    recorder.game(id).map(Json).ok_or_else(|| {
+        ApiError::new(
+            Status::NotFound,
+            Some(format!("no recording of game {:?}", id)),
+        )
+    })
+}
+
+/// # Render Game Turn
+///
+/// Draws the board of a recorded game at the given turn, like /render.
+#[openapi(tag = "Games")]
+#[get("/games/<id>/turns/<turn>/render?<format>&<territory>")]
+async fn handle_game_render(
+    id: &str,
+    turn: u32,
+    format: Option<RenderFormat>,
+    territory: Option<bool>,
+    _throttle: Throttle,
+    _admin: Admin,
+    recorder: &State<GameRecorder>,
+) -> Result<(ContentType, String), ApiError> {
+    let gs = recorder.turn(id, turn).ok_or_else(|| {
+        ApiError::new(
+            Status::NotFound,
+            Some(format!("no turn {} recorded for game {:?}", turn, id)),
+        )
+    })?;
+    render(gs, format, territory).await
+}
+
+/// # Get Stats
+///
+/// Returns win rates across all recorded game outcomes, grouped by mode, map and opponent, and the response time percentiles, average search depth by turn and fallback moves of each mode. Requires the persistence feature and a configured stats database.
+#[openapi(tag = "Games")]
+#[get("/stats")]
+fn handle_stats(
+    _throttle: Throttle,
+    _admin: Admin,
+    stats: &State<StatsStore>,
+) -> Option<Json<Stats>> {
+    stats.stats().map(Json)
+}
+
+/// # Get Game Stats
+///
+/// Returns the response time percentiles, average search depth by turn and fallback moves of a finished game. Requires the persistence feature and a configured stats database.
+#[openapi(tag = "Games")]
+#[get("/stats/games/<id>")]
+fn handle_game_stats(
+    id: &str,
+    _throttle: Throttle,
+    _admin: Admin,
+    stats: &State<StatsStore>,
+) -> Option<Json<Performance>> {
+    stats.game(id).map(Json)
+}
+
+/// # Live Telemetry
+///
+/// Streams server-sent events for every turn played by any active game: the board, the chosen move, the score breakdown and search timing.
+#[openapi(tag = "Games")]
+#[get("/live")]
+fn handle_live(
+    _admin: Admin,
+    live: &State<LiveFeed>,
+    mut end: Shutdown,
+) -> EventStream<BoxStream<'static, Event>> {
+    let mut receiver = live.subscribe();
+    let events = stream! {
+        loop {
+            let telemetry = select! {
+                telemetry = receiver.recv() => match telemetry {
+                    Ok(telemetry) => telemetry,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut end => break,
+            };
+            yield Event::json(&telemetry).event("turn");
+        }
+    };
+    EventStream::from(events.boxed())
+}
+
 /// # Ping
 ///
 /// Returns a pong.
@@ -60,29 +723,627 @@ fn handle_ping() -> &'static str {
     "pong"
 }
 
+/// # Version
+///
+/// Returns the version, git commit, build time and features of this build, and the active experiment.
+#[openapi(tag = "Health")]
+#[get("/version")]
+fn handle_version(sessions: &State<GameSessions>) -> Json<BuildInfo> {
+    Json(BuildInfo::new(sessions.experiment()))
+}
+
+/// # Liveness
+///
+/// Checks that every worker of the pool running move searches still picks up work. Returns 503 with diagnostics when one doesn't.
+#[openapi(tag = "Health")]
+#[get("/healthz")]
+async fn handle_healthz(sessions: &State<GameSessions>, pool: &State<SearchPool>) -> Health {
+    let start = Instant::now();
+    let result = timeout(PROBE_TIMEOUT, pool.ping()).await;
+    let health = Health::new(sessions.searches(), start.elapsed());
+    match result {
+        Ok(Ok(())) => health,
+        Ok(Err(e)) => health.failed(e),
+        Err(_) => health.failed(format!(
+            "search workers did not all respond within {:?}",
+            PROBE_TIMEOUT
+        )),
+    }
+}
+
+/// # Readiness
+///
+/// Searches a canned position for 10ms and checks that the only safe move is found. Returns 503 with diagnostics otherwise.
+#[openapi(tag = "Health")]
+#[get("/readyz")]
+async fn handle_readyz(sessions: &State<GameSessions>) -> Health {
+    let start = Instant::now();
+    let check = spawn_blocking(|| battlesnake::self_check(SELF_CHECK_BUDGET));
+    let result = timeout(PROBE_TIMEOUT, check).await;
+    let health = Health::new(sessions.searches(), start.elapsed());
+    match result {
+        Ok(Ok(check)) => health.with_self_check(check),
+        Ok(Err(e)) => health.failed(e),
+        Err(_) => health.failed(format!(
+            "canned search did not finish within {:?}",
+            PROBE_TIMEOUT
+        )),
+    }
+}
+
+/// # Override Game Strategy
+///
+/// Switches every one of our snakes in an in-progress game to the given strategy, or back to their personality's own with null. Takes effect from the next move.
+#[openapi(tag = "Admin")]
+#[post("/admin/games/<id>/strategy", format = "json", data = "<request>")]
+fn handle_strategy_override(
+    id: &str,
+    request: Json<StrategyOverride>,
+    _throttle: Throttle,
+    _admin: Admin,
+    sessions: &State<GameSessions>,
+) -> Result<Json<StrategyOverride>, ApiError> {
+    let request = request.into_inner();
+    if sessions.set_strategy(id, request.strategy) == 0 {
+        return Err(ApiError::new(
+            Status::NotFound,
+            Some(format!("game {:?} isn't in progress", id)),
+        ));
+    }
+    info!(
+        "strategy of game {:?} overridden with {:?}",
+        id, request.strategy
+    );
+    Ok(Json(request))
+}
+
+/// # Get Game History
+///
+/// Returns the move, best score, search depth and response time of every turn each of our snakes has played in a game, keyed by snake id, over the last 500 turns. Kept for games in progress and the last 100 to have ended.
+#[openapi(tag = "Admin")]
+#[get("/admin/games/<id>/history")]
+fn handle_game_history(
+    id: &str,
+    _throttle: Throttle,
+    _admin: Admin,
+    sessions: &State<GameSessions>,
+) -> Result<Json<HashMap<String, Vec<Decision>>>, ApiError> {
+    let history = sessions.history(id);
+    if history.is_empty() {
+        return Err(ApiError::new(
+            Status::NotFound,
+            Some(format!("no history of game {:?}", id)),
+        ));
+    }
+    Ok(Json(history))
+}
+
+/// Replaces Rocket's HTML error pages with JSON errors.
+#[catch(default)]
+fn handle_error(status: Status, request: &Request) -> ApiError {
+    let details = match status.code {
+        404 => Some(format!(
+            "no route matches {} {}",
+            request.method(),
+            request.uri()
+        )),
+        422 => Some("request body doesn't match the expected schema".to_owned()),
+        _ => None,
+    };
+    ApiError::new(status, details)
+}
+
+/// Waits for in-flight move searches to respond, then saves game sessions for a warm restart.
+///
+/// The game recorder and stats store write through on every request, so they have nothing left to flush.
+async fn save_sessions(rocket: &Rocket<Orbit>) {
+    let sessions = match rocket.state::<GameSessions>() {
+        Some(sessions) => sessions,
+        None => return,
+    };
+    let deadline = Instant::now() + Duration::from_secs(rocket.config().shutdown.grace as u64);
+    while sessions.searches() > 0 && Instant::now() < deadline {
+        sleep(Duration::from_millis(10)).await;
+    }
+    if sessions.searches() > 0 {
+        warn!(
+            "shutting down with {} searches in progress",
+            sessions.searches()
+        );
+    }
+    let path: PathBuf = match rocket.figment().extract_inner("sessions") {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    match sessions.save(&path) {
+        Ok(_) => info!("saved {} sessions to {:?}", sessions.len(), path),
+        Err(e) => warn!("unable to save sessions to {:?}: {}", path, e),
+    }
+}
+
 #[launch]
 fn launch() -> _ {
-    if env::var("RUST_LOG").is_err() {
-        env::set_var("RUST_LOG", "warn,ndsquared_rustapi::battlesnake=info/.*");
-    }
-    env_logger::init();
+    trace::init("warn,ndsquared_rustapi=info");
     info!("LAUNCH");
-    rocket::build()
-        .mount(
-            "/",
-            openapi_get_routes![
-                handle_index,
-                handle_start,
-                handle_move,
-                handle_end,
-                handle_ping
-            ],
+    rocket(rocket::Config::figment())
+}
+
+/// Builds the server from the given config, which tests replace with their own.
+fn rocket(figment: Figment) -> Rocket<Build> {
+    let rocket = rocket::custom(figment);
+    let snake_file: Option<PathBuf> = rocket.figment().extract_inner("snake_file").ok();
+    let snake_config = reload::snake_config(rocket.figment(), snake_file.as_deref())
+        .expect("invalid snake configuration");
+    let recordings: Option<PathBuf> = rocket.figment().extract_inner("recordings").ok();
+    let stats_db: Option<PathBuf> = rocket.figment().extract_inner("stats_db").ok();
+    let sessions: Option<PathBuf> = rocket.figment().extract_inner("sessions").ok();
+    let admin_key: Option<String> = rocket.figment().extract_inner("admin_key").ok();
+    let experiment: Option<Experiment> = rocket.figment().extract_inner("experiment").ok();
+    let shadow: Option<Shadow> = rocket.figment().extract_inner("shadow").ok();
+    let warm_up: u64 = rocket
+        .figment()
+        .extract_inner("warm_up")
+        .unwrap_or(WARM_UP_BUDGET.as_millis() as u64);
+    let search_threads: Option<usize> = rocket.figment().extract_inner("search_threads").ok();
+    let cors_origins: Vec<String> = rocket
+        .figment()
+        .extract_inner("cors_origins")
+        .unwrap_or_default();
+    let slo_config: SloConfig = rocket.figment().extract_inner("slo").unwrap_or_default();
+    let rate_limit: Option<RateLimit> = rocket.figment().extract_inner("rate_limit").ok();
+    let move_rate_limit: Option<RateLimit> = rocket.figment().extract_inner("move_rate_limit").ok();
+    let max_board_size: i32 = rocket
+        .figment()
+        .extract_inner("max_board_size")
+        .unwrap_or(DEFAULT_MAX_BOARD_SIZE);
+    let mut rocket = rocket
+        .manage(
+            sessions
+                .as_deref()
+                .map(GameSessions::load)
+                .unwrap_or_default()
+                .with_experiment(experiment)
+                .with_shadow(shadow)
+                .with_warm_up(Duration::from_millis(warm_up)),
         )
+        .manage(LivePersonalities::new(Personalities::new(&snake_config)))
+        .manage(GameRecorder::new(recordings))
+        .manage(StatsStore::new(stats_db))
+        .manage(LiveFeed::default())
+        .manage(search_threads.map(SearchPool::new).unwrap_or_default())
+        .manage(AdminAuth::new(admin_key))
+        .manage(match move_rate_limit {
+            Some(move_rate_limit) => {
+                Limits::new(rate_limit, max_board_size).with_move_rate(move_rate_limit)
+            }
+            None => Limits::new(rate_limit, max_board_size),
+        })
+        .manage(ResponseSlo::new(slo_config))
+        .attach(RequestStart::fairing())
+        .attach(RequestSpan::fairing())
+        .attach(AdHoc::on_shutdown("Save Sessions", |rocket| {
+            Box::pin(save_sessions(rocket))
+        }))
+        .attach(AdHoc::on_shutdown("Flush Traces", |_| {
+            Box::pin(async {
+                let _ = spawn_blocking(trace::shutdown).await;
+            })
+        }))
+        .register("/", catchers![handle_error])
         .mount(
             "/docs",
             make_swagger_ui(&SwaggerUIConfig {
                 url: "../openapi.json".to_owned(),
                 ..Default::default()
             }),
-        )
+        );
+    if let Some(snake_file) = snake_file {
+        rocket = rocket.attach(ConfigWatch::new(snake_file).fairing());
+    }
+    if !cors_origins.is_empty() {
+        rocket = rocket.attach(Cors::new(cors_origins));
+    }
+    #[cfg(feature = "grpc")]
+    if let Ok(address) = rocket.figment().extract_inner("grpc_address") {
+        rocket = rocket.attach(EngineService::fairing(address));
+    }
+    // The Battlesnake protocol is versioned so a future revision can live alongside it under
+    // /v2; tooling endpoints aren't part of the protocol and stay at the root.
+    let openapi_settings = OpenApiSettings::default();
+    mount_endpoints_and_merged_docs! {
+        rocket, "/".to_owned(), openapi_settings,
+        "/v1" => openapi_get_routes_spec![
+            openapi_settings:
+            handle_index,
+            handle_start,
+            handle_move,
+            handle_end,
+            handle_snake_index,
+            handle_snake_start,
+            handle_snake_move,
+            handle_snake_end
+        ],
+        "/" => openapi_get_routes_spec![
+            openapi_settings:
+            handle_analyze,
+            handle_simulate,
+            handle_import,
+            handle_board,
+            handle_analyze_board,
+            handle_render,
+            handle_games,
+            handle_game,
+            handle_game_render,
+            handle_stats,
+            handle_game_stats,
+            handle_strategy_override,
+            handle_game_history,
+            handle_live,
+            handle_ping,
+            handle_healthz,
+            handle_readyz,
+            handle_version
+        ],
+    };
+    // Snakes registered before /v1 existed still point at the root
+    rocket.mount(
+        "/",
+        routes![
+            handle_index,
+            handle_start,
+            handle_move,
+            handle_end,
+            handle_snake_index,
+            handle_snake_start,
+            handle_snake_move,
+            handle_snake_end
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndsquared_rustapi::admin::API_KEY_HEADER;
+    use rocket::http::Header;
+    use rocket::local::asynchronous::{Client, LocalResponse};
+    use serde_json::{json, Value};
+
+    const ADMIN_KEY: &str = "secret";
+
+    const BOARD: &str = "
+    |  |F |  |  |H |
+    |  |Y0|  |A2|  |
+    |  |Y1|  |A1|  |
+    |  |Y2|  |A0|  |
+    |  |  |F |  |  |
+    ";
+
+    async fn client() -> Client {
+        let figment = rocket::Config::figment().merge(("admin_key", ADMIN_KEY));
+        Client::tracked(rocket(figment)).await.unwrap()
+    }
+
+    fn game_state() -> Value {
+        serde_json::to_value(boards::parse(BOARD).unwrap()).unwrap()
+    }
+
+    async fn post_json<'c>(client: &'c Client, uri: &str, body: &Value) -> LocalResponse<'c> {
+        client
+            .post(uri.to_owned())
+            .header(ContentType::JSON)
+            .body(body.to_string())
+            .dispatch()
+            .await
+    }
+
+    /// Checks the response is a JSON error with the given status, and returns its details.
+    async fn error_details(response: LocalResponse<'_>, status: Status) -> Option<String> {
+        assert_eq!(response.status(), status);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let error: Value = response.into_json().await.unwrap();
+        assert_eq!(error["code"], status.code);
+        assert_eq!(error["message"], status.reason_lossy());
+        error["details"].as_str().map(str::to_owned)
+    }
+
+    #[rocket::async_test]
+    async fn test_index() {
+        let client = client().await;
+        for uri in ["/", "/v1", "/snakes/greedy", "/v1/snakes/greedy"] {
+            let response = client.get(uri).dispatch().await;
+            assert_eq!(response.status(), Status::Ok);
+            let info: Value = response.into_json().await.unwrap();
+            assert_eq!(info["apiversion"], "1");
+            assert!(info["color"].is_string());
+            assert!(info["version"].is_string());
+        }
+        let response = client.get("/snakes/unknown").dispatch().await;
+        let details = error_details(response, Status::NotFound).await;
+        assert!(details.is_some());
+    }
+
+    #[rocket::async_test]
+    async fn test_game() {
+        let client = client().await;
+        let gs = game_state();
+        for prefix in ["", "/v1", "/snakes/basic"] {
+            let response = post_json(&client, &format!("{}/start", prefix), &gs).await;
+            assert_eq!(response.status(), Status::Ok);
+            let response = post_json(&client, &format!("{}/move", prefix), &gs).await;
+            assert_eq!(response.status(), Status::Ok);
+            let mr: Value = response.into_json().await.unwrap();
+            let direction = mr["move"].as_str().unwrap();
+            assert!(["up", "down", "left", "right"].contains(&direction));
+            assert!(mr["shout"].is_string());
+            let response = post_json(&client, &format!("{}/end", prefix), &gs).await;
+            assert_eq!(response.status(), Status::Ok);
+        }
+        let response = post_json(&client, "/snakes/unknown/move", &gs).await;
+        error_details(response, Status::NotFound).await;
+    }
+
+    #[rocket::async_test]
+    async fn test_malformed_payloads() {
+        let client = client().await;
+        let response = client
+            .post("/move")
+            .header(ContentType::JSON)
+            .body("{\"game\":")
+            .dispatch()
+            .await;
+        error_details(response, Status::BadRequest).await;
+        let response = post_json(&client, "/move", &json!({"turn": 1})).await;
+        let details = error_details(response, Status::UnprocessableEntity).await;
+        assert!(details.is_some());
+        // Well-formed states the engine can't search get the fallback move on /move, and are
+        // rejected before searching elsewhere
+        let mut gs = game_state();
+        gs["board"]["width"] = json!(0);
+        let response = post_json(&client, "/move", &gs).await;
+        assert_eq!(response.status(), Status::Ok);
+        let mr: Value = response.into_json().await.unwrap();
+        assert_eq!(mr["move"], "up");
+        let response = post_json(&client, "/analyze", &gs).await;
+        let details = error_details(response, Status::UnprocessableEntity).await;
+        assert!(details.unwrap().contains("board is 0x5"));
+        let response = client.post("/move").body(gs.to_string()).dispatch().await;
+        error_details(response, Status::NotFound).await;
+    }
+
+    #[rocket::async_test]
+    async fn test_unknown_variants() {
+        let client = client().await;
+        let mut gs = game_state();
+        gs["game"]["ruleset"]["name"] = json!("chess");
+        let response = post_json(&client, "/move", &gs).await;
+        error_details(response, Status::UnprocessableEntity).await;
+        let mut gs = game_state();
+        gs["game"]["source"] = json!("backyard");
+        let response = post_json(&client, "/analyze", &gs).await;
+        error_details(response, Status::UnprocessableEntity).await;
+        let request = json!({"state": game_state(), "moves": {"Y": ["sideways"]}});
+        let response = post_json(&client, "/simulate", &request).await;
+        error_details(response, Status::UnprocessableEntity).await;
+        let request = json!({"state": game_state(), "moves": {"Q": ["up"]}});
+        let response = post_json(&client, "/simulate", &request).await;
+        let details = error_details(response, Status::UnprocessableEntity).await;
+        assert_eq!(details.unwrap(), "no snake \"Q\" on the board");
+    }
+
+    #[rocket::async_test]
+    async fn test_oversized_body() {
+        let client = client().await;
+        let mut gs = game_state();
+        gs["you"]["shout"] = json!("!".repeat(2 << 20));
+        let response = post_json(&client, "/move", &gs).await;
+        error_details(response, Status::PayloadTooLarge).await;
+    }
+
+    #[rocket::async_test]
+    async fn test_analysis() {
+        let client = client().await;
+        let response = post_json(&client, "/analyze", &game_state()).await;
+        assert_eq!(response.status(), Status::Ok);
+        let analysis: Value = response.into_json().await.unwrap();
+        assert!(analysis["move"].is_string());
+        assert!(analysis["pv"].is_array());
+        assert_eq!(analysis["pv_lines"][0]["moves"], analysis["pv"]);
+        assert_eq!(analysis.get("pv_boards"), None);
+        let response = post_json(&client, "/analyze?boards=true", &game_state()).await;
+        if cfg!(feature = "render") {
+            let analysis: Value = response.into_json().await.unwrap();
+            assert!(analysis["pv_boards"].is_array());
+        } else {
+            error_details(response, Status::NotImplemented).await;
+        }
+        assert!(analysis["score_breakdown"].is_object());
+        assert_eq!(analysis["territory"].as_array().unwrap().len(), 5);
+        assert_eq!(analysis["candidates"].as_array().unwrap().len(), 4);
+        let request = json!({"state": game_state(), "moves": {"Y": ["right", "down"]}});
+        let response = post_json(&client, "/simulate", &request).await;
+        assert_eq!(response.status(), Status::Ok);
+        let states: Value = response.into_json().await.unwrap();
+        assert_eq!(states.as_array().unwrap().len(), 2);
+        assert_eq!(states[1]["turn"], 2);
+        let response = client
+            .post("/board")
+            .header(ContentType::Plain)
+            .body(BOARD)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let gs: Value = response.into_json().await.unwrap();
+        assert_eq!(gs["you"]["id"], "Y");
+        let response = client
+            .post("/board")
+            .header(ContentType::Plain)
+            .body("|Q0|")
+            .dispatch()
+            .await;
+        error_details(response, Status::UnprocessableEntity).await;
+    }
+
+    #[rocket::async_test]
+    async fn test_render() {
+        let client = client().await;
+        let response = post_json(&client, "/render?format=ascii", &game_state()).await;
+        if cfg!(feature = "render") {
+            assert_eq!(response.status(), Status::Ok);
+            assert_eq!(response.content_type(), Some(ContentType::Plain));
+            let ascii = response.into_string().await.unwrap();
+            assert!(ascii.starts_with("|  |F |  |  |H |\n"));
+        } else {
+            error_details(response, Status::NotImplemented).await;
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_health() {
+        let client = client().await;
+        let response = client.get("/ping").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.into_string().await.unwrap(), "pong");
+        let response = client.get("/version").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let version: Value = response.into_json().await.unwrap();
+        assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+        for uri in ["/healthz", "/readyz"] {
+            let response = client.get(uri).dispatch().await;
+            assert_eq!(response.status(), Status::Ok);
+            let health: Value = response.into_json().await.unwrap();
+            assert_eq!(health["healthy"], true);
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_limits() {
+        let figment = rocket::Config::figment()
+            .merge(("rate_limit", json!({"burst": 1, "per_second": 0.001})))
+            .merge(("move_rate_limit", json!({"burst": 2, "per_second": 0.001})))
+            .merge(("max_board_size", 4));
+        let client = Client::tracked(rocket(figment)).await.unwrap();
+        let gs = game_state();
+        // The board is 5x5, one square a side more than this server plays
+        let response = post_json(&client, "/analyze", &gs).await;
+        let details = error_details(response, Status::UnprocessableEntity).await;
+        assert!(details.unwrap().contains("up to 4x4"));
+        let remote = "203.0.113.7:4000".parse().unwrap();
+        let mut statuses = Vec::new();
+        // Moves have a bucket of their own, apart from the analysis endpoints'
+        for uri in ["/move", "/move", "/move", "/analyze", "/analyze", "/render"] {
+            let response = client
+                .post(uri)
+                .remote(remote)
+                .header(ContentType::JSON)
+                .body(gs.to_string())
+                .dispatch()
+                .await;
+            statuses.push(response.status());
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Ok,
+                Status::Ok,
+                Status::TooManyRequests,
+                Status::UnprocessableEntity,
+                Status::TooManyRequests,
+                Status::TooManyRequests
+            ]
+        );
+    }
+
+    #[rocket::async_test]
+    async fn test_cors() {
+        let figment =
+            rocket::Config::figment().merge(("cors_origins", vec!["https://editor.example"]));
+        let client = Client::tracked(rocket(figment)).await.unwrap();
+        let origin = Header::new("Origin", "https://editor.example");
+        let response = client
+            .options("/analyze")
+            .header(origin.clone())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::NoContent);
+        let headers = response.headers();
+        assert_eq!(
+            headers.get_one("Access-Control-Allow-Origin"),
+            Some("https://editor.example")
+        );
+        assert!(headers.get_one("Access-Control-Allow-Methods").is_some());
+        let response = client
+            .post("/analyze")
+            .header(origin.clone())
+            .header(ContentType::JSON)
+            .body(game_state().to_string())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://editor.example")
+        );
+        // Other origins and the game protocol get no CORS headers
+        let response = client
+            .post("/analyze")
+            .header(Header::new("Origin", "https://other.example"))
+            .header(ContentType::JSON)
+            .body(game_state().to_string())
+            .dispatch()
+            .await;
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            None
+        );
+        let response = client
+            .post("/move")
+            .header(origin)
+            .header(ContentType::JSON)
+            .body(game_state().to_string())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            None
+        );
+    }
+
+    #[rocket::async_test]
+    async fn test_admin() {
+        let client = client().await;
+        let response = client.get("/games").dispatch().await;
+        error_details(response, Status::Unauthorized).await;
+        let response = client
+            .get("/games")
+            .header(rocket::http::Header::new(API_KEY_HEADER, "guess"))
+            .dispatch()
+            .await;
+        error_details(response, Status::Unauthorized).await;
+        let response = client
+            .get("/games")
+            .header(rocket::http::Header::new(API_KEY_HEADER, ADMIN_KEY))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let games: Value = response.into_json().await.unwrap();
+        assert_eq!(games, json!([]));
+        let response = client
+            .post("/admin/games/unknown/strategy")
+            .header(ContentType::JSON)
+            .header(rocket::http::Header::new(API_KEY_HEADER, ADMIN_KEY))
+            .body(json!({"strategy": "greedy"}).to_string())
+            .dispatch()
+            .await;
+        error_details(response, Status::NotFound).await;
+        let response = client
+            .get("/admin/games/unknown/history")
+            .header(rocket::http::Header::new(API_KEY_HEADER, ADMIN_KEY))
+            .dispatch()
+            .await;
+        error_details(response, Status::NotFound).await;
+        let response = client.get("/live").dispatch().await;
+        error_details(response, Status::Unauthorized).await;
+    }
 }
@@ -0,0 +1,279 @@
+use dashmap::DashMap;
+use rocket::tokio::sync::oneshot;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Instant;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Why a job sent to the pool didn't return a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobFailed {
+    /// The job panicked.
+    Panicked,
+    /// The job's deadline passed before a worker got to it, so it was dropped without running.
+    Expired,
+    /// The worker is no longer running.
+    Stopped,
+}
+
+impl fmt::Display for JobFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobFailed::Panicked => write!(f, "search worker panicked"),
+            JobFailed::Expired => write!(f, "search job expired before a worker picked it up"),
+            JobFailed::Stopped => write!(f, "search worker isn't running"),
+        }
+    }
+}
+
+impl std::error::Error for JobFailed {}
+
+struct Worker {
+    sender: Mutex<Sender<Job>>,
+    /// Jobs sent to the worker that it hasn't finished, including the one it's running.
+    queued: Arc<AtomicUsize>,
+}
+
+/// Long-lived threads that searches run on, started with the server rather than borrowed from
+/// Tokio's blocking pool, which has no bound on how many searches share the cores.
+///
+/// Each worker is pinned to a core where the platform allows it. A session (one of our snakes in
+/// a game) prefers the worker with the fewest sessions the first time it's seen, so its searches
+/// find the caches as its last search left them. The preference gives way to load: a job goes to
+/// an idle worker when the preferred one is busy, and to the shortest queue when none is idle.
+pub struct SearchPool {
+    workers: Vec<Worker>,
+    /// The preferred worker of every session in progress, released when the session ends or is
+    /// dropped for going idle.
+    sessions: DashMap<String, usize>,
+}
+
+impl Default for SearchPool {
+    /// A worker per physical core.
+    fn default() -> Self {
+        SearchPool::new(num_cpus::get_physical())
+    }
+}
+
+impl SearchPool {
+    pub fn new(threads: usize) -> Self {
+        let cores = core_affinity::get_core_ids().unwrap_or_default();
+        let workers = (0..threads.max(1))
+            .map(|i| {
+                let (sender, receiver) = mpsc::channel::<Job>();
+                let core = cores.get(i % cores.len().max(1)).copied();
+                thread::Builder::new()
+                    .name(format!("search-{}", i))
+                    .spawn(move || {
+                        if let Some(core) = core {
+                            core_affinity::set_for_current(core);
+                        }
+                        for job in receiver {
+                            job();
+                        }
+                    })
+                    .expect("unable to start search worker");
+                Worker {
+                    sender: Mutex::new(sender),
+                    queued: Arc::new(AtomicUsize::new(0)),
+                }
+            })
+            .collect();
+        SearchPool {
+            workers,
+            sessions: DashMap::new(),
+        }
+    }
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+    fn queued(&self, worker: usize) -> usize {
+        self.workers[worker].queued.load(Ordering::SeqCst)
+    }
+    /// The preferred worker of a session, assigning it one if it hasn't got one yet.
+    fn preferred(&self, key: &str) -> usize {
+        if let Some(worker) = self.sessions.get(key) {
+            return *worker;
+        }
+        let mut load = vec![0; self.workers.len()];
+        for worker in self.sessions.iter() {
+            load[*worker.value()] += 1;
+        }
+        let least_loaded = (0..load.len()).min_by_key(|&i| load[i]).unwrap_or(0);
+        *self.sessions.entry(key.to_owned()).or_insert(least_loaded)
+    }
+    /// An idle worker, the session's own first.
+    fn idle(&self, key: &str) -> Option<usize> {
+        let preferred = self.preferred(key);
+        std::iter::once(preferred)
+            .chain(0..self.workers.len())
+            .find(|&worker| self.queued(worker) == 0)
+    }
+    /// The session's worker when it's idle, then any idle worker, then the shortest queue.
+    fn worker(&self, key: &str) -> usize {
+        let preferred = self.preferred(key);
+        self.idle(key).unwrap_or_else(|| {
+            (0..self.workers.len())
+                .min_by_key(|&worker| (self.queued(worker), worker != preferred))
+                .unwrap_or(preferred)
+        })
+    }
+    /// Sends `job` to a worker straight away, and returns the future of its result. The job is
+    /// dropped without running if `deadline` has passed by the time the worker gets to it.
+    fn send<T, F>(
+        &self,
+        worker: usize,
+        deadline: Option<Instant>,
+        job: F,
+    ) -> impl Future<Output = Result<T, JobFailed>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let worker = &self.workers[worker];
+        let queued = worker.queued.clone();
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            let result = if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                Err(JobFailed::Expired)
+            } else {
+                // A panic leaves the worker running
+                panic::catch_unwind(AssertUnwindSafe(job)).map_err(|_| JobFailed::Panicked)
+            };
+            // Done before answering, so whoever gets the answer finds the worker idle again
+            queued.fetch_sub(1, Ordering::SeqCst);
+            let _ = sender.send(result);
+        });
+        worker.queued.fetch_add(1, Ordering::SeqCst);
+        let sent = worker
+            .sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .send(job);
+        if sent.is_err() {
+            worker.queued.fetch_sub(1, Ordering::SeqCst);
+        }
+        // A job that never reached a worker drops its sender along with it
+        async move { receiver.await.unwrap_or(Err(JobFailed::Stopped)) }
+    }
+    /// Queues `job` for the session with key `key`, on the first free worker as described on
+    /// [`SearchPool`], and returns the future of its result.
+    pub fn run<T, F>(
+        &self,
+        key: &str,
+        deadline: Instant,
+        job: F,
+    ) -> impl Future<Output = Result<T, JobFailed>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        self.send(self.worker(key), Some(deadline), job)
+    }
+    /// Like [`SearchPool::run`], but only when a worker is idle, for work that's worth doing
+    /// with spare capacity and not worth delaying anyone's search for. `None` when every worker
+    /// is busy.
+    pub fn run_spare<T, F>(
+        &self,
+        key: &str,
+        deadline: Instant,
+        job: F,
+    ) -> Option<impl Future<Output = Result<T, JobFailed>>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let worker = self.idle(key)?;
+        Some(self.send(worker, Some(deadline), job))
+    }
+    /// Sends a no-op to every worker and waits for all of them to run it.
+    pub async fn ping(&self) -> Result<(), JobFailed> {
+        let pongs: Vec<_> = (0..self.workers.len())
+            .map(|worker| self.send(worker, None, || ()))
+            .collect();
+        for pong in pongs {
+            pong.await?;
+        }
+        Ok(())
+    }
+    /// Frees the preferred worker of a session that's ended for the sessions still to come.
+    pub fn release(&self, key: &str) {
+        self.sessions.remove(key);
+    }
+    /// Number of sessions with a preferred worker.
+    pub fn sessions(&self) -> usize {
+        self.sessions.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+    use std::time::Duration;
+
+    fn name() -> Option<String> {
+        thread::current().name().map(str::to_owned)
+    }
+
+    /// Keeps a worker busy until the returned sender is dropped.
+    fn block(pool: &SearchPool, key: &str) -> (Sender<()>, Receiver<Option<String>>) {
+        let (release, wait) = mpsc::channel::<()>();
+        let (started, running) = mpsc::channel();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        // Sent straight away, so the result doesn't need awaiting
+        let _blocking = pool.run(key, deadline, move || {
+            let _ = started.send(name());
+            let _ = wait.recv();
+        });
+        (release, running)
+    }
+
+    #[rocket::async_test]
+    async fn test_search_pool() {
+        let pool = SearchPool::new(2);
+        assert_eq!(pool.threads(), 2);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let first = pool.run("a/you", deadline, name).await.unwrap();
+        let again = pool.run("a/you", deadline, name).await.unwrap();
+        let other = pool.run("a/other", deadline, name).await.unwrap();
+        // A session keeps its worker while it's idle, and the next session gets the other one,
+        // even in the same game
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+        assert_eq!(pool.sessions(), 2);
+        // A busy worker's session goes to the idle one
+        let (release, running) = block(&pool, "a/you");
+        assert_eq!(running.recv().unwrap(), first);
+        assert_eq!(pool.run("a/you", deadline, name).await.unwrap(), other);
+        // With no worker idle there's no spare capacity, and jobs queue behind the shortest
+        // queue
+        let (release_other, running_other) = block(&pool, "a/other");
+        assert_eq!(running_other.recv().unwrap(), other);
+        assert!(pool.run_spare("a/you", deadline, name).is_none());
+        let expired = pool.run("a/you", Instant::now(), name);
+        drop(release);
+        assert_eq!(expired.await, Err(JobFailed::Expired));
+        drop(release_other);
+        assert_eq!(pool.ping().await, Ok(()));
+        assert_eq!(
+            pool.run_spare("a/you", deadline, name).unwrap().await,
+            Ok(first.clone())
+        );
+        assert_eq!(
+            pool.run("a/you", deadline, || panic!("lost")).await,
+            Err::<(), _>(JobFailed::Panicked)
+        );
+        assert_eq!(pool.run("a/you", deadline, || 1).await, Ok(1));
+        pool.release("a/you");
+        assert_eq!(pool.sessions(), 1);
+        pool.release("a/other");
+        assert_eq!(pool.sessions(), 0);
+    }
+}
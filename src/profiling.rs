@@ -0,0 +1,118 @@
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::Serialize;
+#[cfg(feature = "profiling")]
+use std::cell::RefCell;
+#[cfg(feature = "profiling")]
+use std::time::{Duration, Instant};
+
+/// A part of handling a move request that's timed on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Metadata,
+    MoveGeneration,
+    Advance,
+    Undo,
+    Evaluation,
+    Territory,
+}
+
+/// Time spent in each phase of a move request, in microseconds.
+///
+/// Phases nest: advance and undo include the threats they recompute as metadata, and evaluation
+/// includes territory. Only measured when built with the `profiling` feature, as reading the clock
+/// around every advance isn't free; otherwise every phase reads zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct PhaseTimes {
+    pub metadata: u64,
+    pub move_generation: u64,
+    pub advance: u64,
+    pub undo: u64,
+    pub evaluation: u64,
+    pub territory: u64,
+}
+
+#[cfg(feature = "profiling")]
+thread_local! {
+    /// Searches run on the thread that handles the request, so each request has its own timers.
+    static TIMES: RefCell<[Duration; 6]> = const { RefCell::new([Duration::ZERO; 6]) };
+}
+
+/// Adds the time until it's dropped to its phase.
+#[must_use]
+pub struct Timer {
+    #[cfg(feature = "profiling")]
+    phase: Phase,
+    #[cfg(feature = "profiling")]
+    start: Instant,
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        TIMES.with(|times| times.borrow_mut()[self.phase as usize] += elapsed);
+    }
+}
+
+/// Starts timing `phase` on this thread.
+#[cfg(feature = "profiling")]
+pub fn start(phase: Phase) -> Timer {
+    Timer {
+        phase,
+        start: Instant::now(),
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+#[inline]
+pub fn start(_phase: Phase) -> Timer {
+    Timer {}
+}
+
+/// Clears the timers of this thread, for a new request.
+pub fn reset() {
+    #[cfg(feature = "profiling")]
+    TIMES.with(|times| *times.borrow_mut() = [Duration::ZERO; 6]);
+}
+
+/// What's been timed on this thread since the last `reset`.
+#[cfg(feature = "profiling")]
+pub fn times() -> PhaseTimes {
+    TIMES.with(|times| {
+        let times = times.borrow();
+        let micros = |phase: Phase| times[phase as usize].as_micros() as u64;
+        PhaseTimes {
+            metadata: micros(Phase::Metadata),
+            move_generation: micros(Phase::MoveGeneration),
+            advance: micros(Phase::Advance),
+            undo: micros(Phase::Undo),
+            evaluation: micros(Phase::Evaluation),
+            territory: micros(Phase::Territory),
+        }
+    })
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn times() -> PhaseTimes {
+    PhaseTimes::default()
+}
+
+#[cfg(all(test, feature = "profiling"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timers() {
+        reset();
+        {
+            let _timer = start(Phase::Advance);
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        let measured = times();
+        assert!(measured.advance >= 2000);
+        assert_eq!(measured.undo, 0);
+        reset();
+        assert_eq!(times(), PhaseTimes::default());
+    }
+}
@@ -0,0 +1,228 @@
+use crate::battlesnake::{solve, GameState, Player, Strategy};
+use crate::boards;
+use std::fmt::Write;
+use std::time::Instant;
+
+const MOVES: [&str; 4] = ["up", "down", "left", "right"];
+
+/// A position with the moves that solve it.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub id: String,
+    pub state: GameState,
+    /// Moves that solve the puzzle. Any other move fails, unless this is empty.
+    pub best: Vec<String>,
+    /// Moves that fail the puzzle.
+    pub avoid: Vec<String>,
+    /// Search time, in milliseconds, when it differs from a move request's.
+    pub time: Option<u64>,
+}
+
+impl Puzzle {
+    pub fn solved_by(&self, chosen: &str) -> bool {
+        let best = self.best.is_empty() || self.best.iter().any(|m| m == chosen);
+        best && !self.avoid.iter().any(|m| m == chosen)
+    }
+}
+
+fn parse_moves(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split_whitespace()
+        .map(|m| {
+            if MOVES.contains(&m) {
+                Ok(m.to_owned())
+            } else {
+                Err(format!("{:?} isn't a move", m))
+            }
+        })
+        .collect()
+}
+
+fn parse_puzzle(block: &[&str]) -> Result<Option<Puzzle>, String> {
+    let mut id: Option<String> = None;
+    let mut best: Vec<String> = Vec::new();
+    let mut avoid: Vec<String> = Vec::new();
+    let mut time: Option<u64> = None;
+    let mut board: Vec<&str> = Vec::new();
+    for line in block {
+        let key_value = line.split_once(':').map(|(k, v)| (k.trim(), v.trim()));
+        match key_value {
+            Some(("id", value)) => id = Some(value.to_owned()),
+            Some(("best", value)) => best = parse_moves(value)?,
+            Some(("avoid", value)) => avoid = parse_moves(value)?,
+            Some(("time", value)) => {
+                let value = value
+                    .parse()
+                    .map_err(|_| format!("time must be a number, not {:?}", value))?;
+                time = Some(value);
+            }
+            _ => board.push(line),
+        }
+    }
+    // Blocks of comments describe the suite rather than a position
+    if id.is_none() && board.iter().all(|line| line.starts_with('#')) {
+        return Ok(None);
+    }
+    let id = id.ok_or("a position has no id")?;
+    if best.is_empty() && avoid.is_empty() {
+        return Err(format!("{}: no best or avoid moves", id));
+    }
+    let state = boards::parse(&board.join("\n")).map_err(|e| format!("{}: {}", id, e))?;
+    state.validate().map_err(|e| format!("{}: {}", id, e))?;
+    Ok(Some(Puzzle {
+        id,
+        state,
+        best,
+        avoid,
+        time,
+    }))
+}
+
+/// Parses a suite of positions separated by blank lines. Each position is a board in the notation
+/// of `boards::parse`, with extra `key: value` lines:
+///
+/// ```text
+/// id: avoid-head-to-head
+/// best: left
+/// avoid: right up
+/// time: 200
+/// |  |  |A0|A1|A2|
+/// |  |Y0|  |  |  |
+/// |  |Y1|  |  |  |
+/// ```
+///
+/// `best` lists the moves that solve the position and `avoid` the moves that fail it; a position
+/// needs at least one of them. `time` is the search time in milliseconds.
+pub fn parse_suite(text: &str) -> Result<Vec<Puzzle>, String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut puzzles: Vec<Puzzle> = Vec::new();
+    for block in lines.split(|line| line.is_empty()) {
+        if block.is_empty() {
+            continue;
+        }
+        if let Some(puzzle) = parse_puzzle(block)? {
+            puzzles.push(puzzle);
+        }
+    }
+    Ok(puzzles)
+}
+
+/// How the engine did on a position.
+#[derive(Debug, Clone)]
+pub struct PuzzleResult {
+    pub id: String,
+    pub chosen: String,
+    pub passed: bool,
+    /// Milliseconds taken to choose.
+    pub time: u128,
+}
+
+/// How the engine did on a whole suite.
+#[derive(Debug, Clone, Default)]
+pub struct SuiteReport {
+    pub results: Vec<PuzzleResult>,
+}
+
+impl SuiteReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed).count()
+    }
+    pub fn failures(&self) -> Vec<&PuzzleResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.passed)
+            .collect()
+    }
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for result in &self.results {
+            let _ = writeln!(
+                summary,
+                "{} {} {} ({}ms)",
+                if result.passed { "PASS" } else { "FAIL" },
+                result.id,
+                result.chosen,
+                result.time
+            );
+        }
+        let time: u128 = self.results.iter().map(|result| result.time).sum();
+        let _ = writeln!(
+            summary,
+            "{}/{} passed in {}ms",
+            self.passed(),
+            self.results.len(),
+            time
+        );
+        summary
+    }
+}
+
+/// Runs the strategy on every position of a suite, calling `on_result` as each finishes.
+pub fn run(
+    puzzles: &[Puzzle],
+    strategy: Strategy,
+    mut on_result: impl FnMut(&PuzzleResult),
+) -> SuiteReport {
+    let mut report = SuiteReport::default();
+    for puzzle in puzzles {
+        let player = Player {
+            strategy,
+            timeout: puzzle.time.map(u128::from),
+        };
+        let start = Instant::now();
+        let chosen = solve(puzzle.state.clone(), player);
+        let result = PuzzleResult {
+            id: puzzle.id.clone(),
+            passed: puzzle.solved_by(&chosen),
+            chosen,
+            time: start.elapsed().as_millis(),
+        };
+        on_result(&result);
+        report.results.push(result);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suite() {
+        let puzzles = parse_suite(
+            "
+            # Comments before the first position
+
+            id: first
+            best: left down
+            |Y0|  |
+            |Y1|  |
+
+            id: second
+            avoid: up
+            time: 50
+            mode: solo
+            |  |Y0|
+            ",
+        )
+        .unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles[0].id, "first");
+        assert_eq!(puzzles[0].best, vec!["left", "down"]);
+        assert!(puzzles[0].solved_by("down"));
+        assert!(!puzzles[0].solved_by("right"));
+        assert_eq!(puzzles[1].time, Some(50));
+        assert!(puzzles[1].solved_by("left"));
+        assert!(!puzzles[1].solved_by("up"));
+        assert!(parse_suite("best: up\n|Y0|").is_err());
+        assert!(parse_suite("id: none\n|Y0|").is_err());
+        assert!(parse_suite("id: bad\nbest: north\n|Y0|").is_err());
+    }
+
+    #[test]
+    fn test_tactics_suite() {
+        let puzzles = parse_suite(include_str!("../puzzles/tactics.txt")).unwrap();
+        let report = run(&puzzles, Strategy::Minimax, |_| {});
+        assert_eq!(report.failures().len(), 0, "{}", report.summary());
+    }
+}
@@ -0,0 +1,433 @@
+use crate::battlesnake::{
+    DeathCause, Direction, EncodedState, GameOutcome, GameState, MoveResponse, PostMortem,
+};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// How the game a recorded move was made in ended for our snake.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TurnLabel {
+    /// `1` if we won, `0` for a draw and `-1` for a loss, as in self-play records. Surviving a
+    /// game nobody won is a draw; being eliminated is a loss, even alongside the last opponent.
+    pub outcome: i8,
+    /// Turns from this move until our snake was eliminated. Absent if it survived the game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turns_to_death: Option<u32>,
+}
+
+impl TurnLabel {
+    /// The label of the move made on `turn` of the game that ended in `outcome`.
+    pub fn new(outcome: &GameOutcome, turn: u32) -> Self {
+        let survived = outcome.cause == DeathCause::Survived;
+        TurnLabel {
+            outcome: match (outcome.won, survived) {
+                (true, _) => 1,
+                (false, true) => 0,
+                (false, false) => -1,
+            },
+            turns_to_death: (!survived).then(|| outcome.turns.saturating_sub(turn)),
+        }
+    }
+}
+
+/// A recorded move with how its game ended, one line of the labeled dataset `export` writes.
+/// The state is encoded as in self-play records, so both train the same way.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LabeledTurn {
+    pub game_id: String,
+    pub turn: u32,
+    pub snake_id: String,
+    pub state: EncodedState,
+    #[serde(rename = "move")]
+    pub direction: Direction,
+    #[serde(flatten)]
+    pub label: TurnLabel,
+}
+
+/// A single request or response received during a game.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameRecord {
+    Start {
+        state: GameState,
+    },
+    Move {
+        state: GameState,
+        response: MoveResponse,
+        /// Zobrist key of the position, in hex, for matching positions across games. Absent from
+        /// older recordings.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position_key: Option<String>,
+        /// Added to every move of the game once it ends. Absent until then, and from older
+        /// recordings.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<TurnLabel>,
+    },
+    End {
+        state: GameState,
+        /// Added when the game ended, absent from older recordings.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        post_mortem: Option<PostMortem>,
+    },
+}
+
+impl GameRecord {
+    pub fn state(&self) -> &GameState {
+        match self {
+            GameRecord::Start { state } => state,
+            GameRecord::Move { state, .. } => state,
+            GameRecord::End { state, .. } => state,
+        }
+    }
+}
+
+/// Appends every game event to `<directory>/<game id>.jsonl`, one JSON record per line.
+///
+/// Recording is disabled when no directory is configured.
+#[derive(Debug, Default)]
+pub struct GameRecorder {
+    directory: Option<PathBuf>,
+    lock: Mutex<()>,
+}
+
+/// Game ids come from the network, so only allow characters that are safe in a file name.
+fn valid_game_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl GameRecorder {
+    pub fn new(directory: Option<PathBuf>) -> Self {
+        let directory = directory.filter(|directory| match fs::create_dir_all(directory) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!(
+                    "unable to create recordings directory {:?}: {}",
+                    directory, e
+                );
+                false
+            }
+        });
+        GameRecorder {
+            directory,
+            lock: Mutex::new(()),
+        }
+    }
+    pub fn enabled(&self) -> bool {
+        self.directory.is_some()
+    }
+    fn path(&self, id: &str) -> Option<PathBuf> {
+        if !valid_game_id(id) {
+            return None;
+        }
+        let directory = self.directory.as_ref()?;
+        Some(directory.join(format!("{}.jsonl", id)))
+    }
+    pub fn record(&self, record: &GameRecord) {
+        let path = match self.path(record.state().game_id()) {
+            Some(path) => path,
+            None => return,
+        };
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("unable to serialize game record: {}", e);
+                return;
+            }
+        };
+        line.push(b'\n');
+        let _guard = self.lock.lock();
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&line));
+        if let Err(e) = result {
+            warn!("unable to record game to {:?}: {}", path, e);
+        }
+    }
+    /// Labels the recorded moves of the snake the outcome is for with how its game ended,
+    /// rewriting the recording in place. Moves of our other snakes in the same game are left for
+    /// their own outcomes.
+    pub fn label(&self, outcome: &GameOutcome) {
+        let path = match self.path(&outcome.game_id) {
+            Some(path) => path,
+            None => return,
+        };
+        let _guard = self.lock.lock();
+        let mut records = match self.game(&outcome.game_id) {
+            Some(records) => records,
+            None => return,
+        };
+        for record in records.iter_mut() {
+            match record {
+                GameRecord::Move { state, label, .. } if state.you.id == outcome.snake_id => {
+                    *label = Some(TurnLabel::new(outcome, state.turn()));
+                }
+                _ => {}
+            }
+        }
+        let mut lines = Vec::new();
+        for record in &records {
+            match serde_json::to_vec(record) {
+                Ok(line) => lines.extend(line),
+                Err(e) => {
+                    warn!("unable to serialize game record: {}", e);
+                    return;
+                }
+            }
+            lines.push(b'\n');
+        }
+        // Written beside the recording and renamed over it, so readers never see half of it
+        let labeled = path.with_extension("jsonl.labeled");
+        if let Err(e) = fs::write(&labeled, &lines).and_then(|_| fs::rename(&labeled, &path)) {
+            warn!("unable to label game in {:?}: {}", path, e);
+        }
+    }
+    /// The labeled moves of a game, of one of our snakes or of all of them, leaving out those not
+    /// labeled yet.
+    pub fn labeled_turns(&self, id: &str, snake_id: Option<&str>) -> Vec<LabeledTurn> {
+        self.game(id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|record| match record {
+                GameRecord::Move {
+                    state,
+                    response,
+                    label: Some(label),
+                    ..
+                } if snake_id.is_none_or(|snake_id| state.you.id == snake_id) => {
+                    Some(LabeledTurn {
+                        game_id: id.to_owned(),
+                        turn: state.turn(),
+                        snake_id: state.you.id.clone(),
+                        state: state.encode(),
+                        direction: response.direction(),
+                        label,
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+    /// Ids of all recorded games.
+    pub fn games(&self) -> Vec<String> {
+        let directory = match &self.directory {
+            Some(directory) => directory,
+            None => return Vec::new(),
+        };
+        let mut games: Vec<String> = match fs::read_dir(directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let name = entry.file_name().into_string().ok()?;
+                    name.strip_suffix(".jsonl").map(str::to_owned)
+                })
+                .collect(),
+            Err(e) => {
+                warn!("unable to list recordings in {:?}: {}", directory, e);
+                Vec::new()
+            }
+        };
+        games.sort();
+        games
+    }
+    /// All records of a game, in the order they were received.
+    pub fn game(&self, id: &str) -> Option<Vec<GameRecord>> {
+        let file = File::open(self.path(id)?).ok()?;
+        let mut records: Vec<GameRecord> = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.ok()?;
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => warn!("skipping unreadable record in game {:?}: {}", id, e),
+            }
+        }
+        Some(records)
+    }
+    /// The first recorded state of a game at the given turn.
+    pub fn turn(&self, id: &str, turn: u32) -> Option<GameState> {
+        self.game(id)?
+            .into_iter()
+            .map(|record| record.state().clone())
+            .find(|state| state.turn() == turn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battlesnake::new_gamestate_from_text;
+
+    #[test]
+    fn test_record_and_read() {
+        let directory = std::env::temp_dir().join(format!("recorder-{}", std::process::id()));
+        let recorder = GameRecorder::new(Some(directory.clone()));
+        let gs = new_gamestate_from_text(
+            "
+        |  |  |  |
+        |Y0|Y1|Y2|
+        |  |  |  |
+        ",
+        );
+        recorder.record(&GameRecord::Start { state: gs.clone() });
+        recorder.record(&GameRecord::End {
+            state: gs,
+            post_mortem: None,
+        });
+        assert_eq!(recorder.games(), vec!["gameid".to_owned()]);
+        let records = recorder.game("gameid").unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(records[0], GameRecord::Start { .. }));
+        assert!(matches!(records[1], GameRecord::End { .. }));
+        assert!(recorder.turn("gameid", 0).is_some());
+        assert!(recorder.turn("gameid", 1).is_none());
+        assert!(recorder.game("../gameid").is_none());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_label() {
+        let directory = std::env::temp_dir().join(format!("labels-{}", std::process::id()));
+        let recorder = GameRecorder::new(Some(directory.clone()));
+        let mut gs = new_gamestate_from_text(
+            "
+        |  |  |  |
+        |Y0|Y1|Y2|
+        |  |  |  |
+        ",
+        );
+        recorder.record(&GameRecord::Start { state: gs.clone() });
+        let response: MoveResponse =
+            serde_json::from_str(r#"{"move": "up", "shout": ""}"#).unwrap();
+        for turn in 0..3 {
+            gs.turn = turn;
+            recorder.record(&GameRecord::Move {
+                state: gs.clone(),
+                response: response.clone(),
+                position_key: None,
+                label: None,
+            });
+        }
+        // Not labeled until the game ends
+        assert!(recorder.labeled_turns("gameid", None).is_empty());
+        let outcome = GameOutcome {
+            game_id: "gameid".to_owned(),
+            snake_id: gs.you.id.clone(),
+            mode: "standard".to_owned(),
+            map: "standard".to_owned(),
+            opponents: Vec::new(),
+            turns: 3,
+            placement: 2,
+            won: false,
+            cause: DeathCause::Starvation,
+            variant: None,
+        };
+        recorder.label(&outcome);
+        assert_eq!(recorder.game("gameid").unwrap().len(), 4);
+        let turns = recorder.labeled_turns("gameid", None);
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[0].label.outcome, -1);
+        assert_eq!(turns[0].label.turns_to_death, Some(3));
+        assert_eq!(turns[2].label.turns_to_death, Some(1));
+        assert_eq!(turns[2].direction, Direction::Up);
+
+        let won = GameOutcome {
+            placement: 1,
+            won: true,
+            cause: DeathCause::Survived,
+            ..outcome
+        };
+        assert_eq!(
+            TurnLabel::new(&won, 0),
+            TurnLabel {
+                outcome: 1,
+                turns_to_death: None,
+            }
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_label_snakes() {
+        let directory = std::env::temp_dir().join(format!("label-snakes-{}", std::process::id()));
+        let recorder = GameRecorder::new(Some(directory.clone()));
+        // Two of our snakes in the same game, each sending its own requests
+        let ours = new_gamestate_from_text(
+            "
+        |  |  |  |  |
+        |Y0|Y1|A1|A0|
+        |  |  |  |  |
+        ",
+        );
+        let mut other = ours.clone();
+        other.you = other.board.get_snake("A").unwrap().clone();
+        let response: MoveResponse =
+            serde_json::from_str(r#"{"move": "up", "shout": ""}"#).unwrap();
+        for turn in 0..2 {
+            for gs in [&ours, &other] {
+                let mut state = gs.clone();
+                state.turn = turn;
+                recorder.record(&GameRecord::Move {
+                    state,
+                    response: response.clone(),
+                    position_key: None,
+                    label: None,
+                });
+            }
+        }
+        let lost = GameOutcome {
+            game_id: "gameid".to_owned(),
+            snake_id: ours.you.id.clone(),
+            mode: "standard".to_owned(),
+            map: "standard".to_owned(),
+            opponents: Vec::new(),
+            turns: 2,
+            placement: 2,
+            won: false,
+            cause: DeathCause::HeadToHead,
+            variant: None,
+        };
+        let won = GameOutcome {
+            snake_id: other.you.id.clone(),
+            placement: 1,
+            won: true,
+            cause: DeathCause::Survived,
+            ..lost.clone()
+        };
+        // The first snake to end labels only its own moves
+        recorder.label(&lost);
+        assert_eq!(recorder.labeled_turns("gameid", None).len(), 2);
+        let turns = recorder.labeled_turns("gameid", Some(&ours.you.id));
+        assert_eq!(turns.len(), 2);
+        assert!(turns.iter().all(|turn| turn.label.outcome == -1));
+        assert!(turns.iter().all(|turn| turn.snake_id == ours.you.id));
+        assert!(recorder
+            .labeled_turns("gameid", Some(&other.you.id))
+            .is_empty());
+        recorder.label(&won);
+        let turns = recorder.labeled_turns("gameid", Some(&other.you.id));
+        assert_eq!(turns.len(), 2);
+        assert!(turns.iter().all(|turn| turn.label.outcome == 1));
+        // Labeling the other snake left the first one's labels alone
+        let turns = recorder.labeled_turns("gameid", Some(&ours.you.id));
+        assert!(turns.iter().all(|turn| turn.label.outcome == -1));
+        assert_eq!(recorder.labeled_turns("gameid", None).len(), 4);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_disabled() {
+        let recorder = GameRecorder::new(None);
+        assert!(!recorder.enabled());
+        assert!(recorder.games().is_empty());
+        assert!(recorder.game("gameid").is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::battlesnake::{Personalities, SnakeConfig};
+use rocket::fairing::AdHoc;
+use rocket::figment::providers::{Env, Format, Toml};
+use rocket::figment::{Figment, Profile};
+use rocket::tokio::time::sleep;
+use rocket::tokio::{self, select};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// How often the `snake_file` is checked for changes.
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reads the `snake` settings: the `snake` table of `figment`, then the settings in `file` if
+/// there is one, then `SNAKE_` environment variables, each over the ones before whatever profile
+/// they're in. A file that doesn't exist has no settings.
+pub fn snake_config(figment: &Figment, file: Option<&Path>) -> Result<SnakeConfig, String> {
+    let mut figment = figment.focus("snake");
+    if let Some(file) = file {
+        figment = figment.merge(Toml::file(file).profile(Profile::Global));
+    }
+    figment
+        .merge(Env::prefixed("SNAKE_").split("__").global())
+        .extract()
+        .map_err(|e| e.to_string())
+}
+
+/// The personalities being served, which a reload of the `snake` settings replaces whole. Clones
+/// share them.
+#[derive(Debug, Clone)]
+pub struct LivePersonalities {
+    current: Arc<RwLock<Arc<Personalities>>>,
+}
+
+impl LivePersonalities {
+    pub fn new(personalities: Personalities) -> Self {
+        LivePersonalities {
+            current: Arc::new(RwLock::new(Arc::new(personalities))),
+        }
+    }
+    /// The personalities as they are now. Each request takes them once, up front, so a reload
+    /// lands between turns and never changes a search under way.
+    pub fn current(&self) -> Arc<Personalities> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+    pub fn replace(&self, personalities: Personalities) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(personalities);
+    }
+}
+
+/// Watches the `snake_file` of `snake` settings for changes, by polling its modification time
+/// and size. The size catches an edit made within the filesystem's clock resolution of the last.
+#[derive(Debug)]
+pub struct ConfigWatch {
+    path: PathBuf,
+    /// Modification time and size of the file when last checked, or None if it didn't exist.
+    seen: Option<(SystemTime, u64)>,
+}
+
+impl ConfigWatch {
+    pub fn new(path: PathBuf) -> Self {
+        let seen = stamp(&path);
+        ConfigWatch { path, seen }
+    }
+    /// Whether the file was changed, created or removed since it was last checked.
+    pub fn changed(&mut self) -> bool {
+        let seen = stamp(&self.path);
+        if seen == self.seen {
+            return false;
+        }
+        self.seen = seen;
+        true
+    }
+    /// Checks the file every `CONFIG_POLL_INTERVAL` once Rocket has launched, until it shuts
+    /// down, replacing the managed `LivePersonalities` with ones built from the new settings
+    /// whenever it changes. Settings that don't parse are logged and leave them as they were.
+    pub fn fairing(self) -> AdHoc {
+        AdHoc::on_liftoff("Config Reload", move |rocket| {
+            let figment = rocket.figment().clone();
+            let live = rocket.state::<LivePersonalities>().cloned();
+            let shutdown = rocket.shutdown();
+            Box::pin(async move {
+                let live = match live {
+                    Some(live) => live,
+                    None => return,
+                };
+                info!("watching {:?} for snake settings", self.path);
+                tokio::spawn(async move {
+                    select! {
+                        _ = shutdown => {}
+                        _ = self.watch(figment, live) => {}
+                    }
+                });
+            })
+        })
+    }
+    async fn watch(mut self, figment: Figment, live: LivePersonalities) {
+        loop {
+            sleep(CONFIG_POLL_INTERVAL).await;
+            if !self.changed() {
+                continue;
+            }
+            match snake_config(&figment, Some(&self.path)) {
+                Ok(config) => {
+                    live.replace(Personalities::new(&config));
+                    info!("reloaded snake settings from {:?}", self.path);
+                }
+                Err(e) => warn!(
+                    "unable to reload snake settings from {:?}: {}",
+                    self.path, e
+                ),
+            }
+        }
+    }
+}
+
+/// Modification time and size of the file at `path`, if it exists.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snake_config() {
+        let dir = std::env::temp_dir().join(format!("snake-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snake.toml");
+        let _ = fs::remove_file(&path);
+        let figment = Figment::new().merge(("snake.author", "someone"));
+        let mut watch = ConfigWatch::new(path.clone());
+        assert!(!watch.changed());
+        // No file yet, so only the launch settings
+        let config = snake_config(&figment, Some(&path)).unwrap();
+        assert_eq!(config.author, "someone");
+        assert_eq!(config.full_evaluation_depth, None);
+
+        fs::write(&path, "full_evaluation_depth = 4\n").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+        let config = snake_config(&figment, Some(&path)).unwrap();
+        assert_eq!(config.author, "someone");
+        assert_eq!(config.full_evaluation_depth, Some(4));
+
+        fs::write(&path, "author = \"someone else\"\n").unwrap();
+        assert!(watch.changed());
+        let config = snake_config(&figment, Some(&path)).unwrap();
+        assert_eq!(config.author, "someone else");
+        assert_eq!(config.full_evaluation_depth, None);
+
+        fs::write(&path, "full_evaluation_depth = \"deep\"\n").unwrap();
+        assert!(snake_config(&figment, Some(&path)).is_err());
+
+        fs::remove_file(&path).unwrap();
+        assert!(watch.changed());
+        let _ = fs::remove_dir(&dir);
+    }
+
+    #[test]
+    fn test_live_personalities() {
+        let live = LivePersonalities::new(Personalities::default());
+        let before = live.current();
+        let config = SnakeConfig {
+            full_evaluation_depth: Some(2),
+            ..SnakeConfig::default()
+        };
+        live.clone().replace(Personalities::new(&config));
+        // Whoever took the personalities before keeps them
+        assert_eq!(before.default_personality().full_evaluation_depth, None);
+        assert_eq!(
+            live.current().default_personality().full_evaluation_depth,
+            Some(2)
+        );
+    }
+}
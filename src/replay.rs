@@ -0,0 +1,259 @@
+use crate::battlesnake::GameState;
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// A game exported by the official Battlesnake engine: the game from `/games/<id>` and its
+/// frames from `/games/<id>/frames`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct EngineExport {
+    game: EngineGame,
+    frames: Vec<EngineFrame>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+struct EngineGame {
+    /// Example: "totally-unique-game-id"
+    #[serde(rename = "ID")]
+    id: String,
+    width: i32,
+    height: i32,
+    /// Ruleset name and settings, which the engine exports as strings. Example: {"name": "standard", "foodSpawnChance": "15"}
+    #[serde(default)]
+    ruleset: HashMap<String, Value>,
+    #[serde(default)]
+    ruleset_version: String,
+    /// Milliseconds each snake had to respond. Example: 500
+    #[serde(default = "default_timeout")]
+    snake_timeout: u32,
+    /// Example: "league"
+    #[serde(default)]
+    source: String,
+    /// Example: "standard"
+    #[serde(default)]
+    map: String,
+}
+
+fn default_timeout() -> u32 {
+    500
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+struct EngineFrame {
+    turn: u32,
+    snakes: Vec<EngineSnake>,
+    #[serde(default)]
+    food: Vec<EngineCoord>,
+    #[serde(default)]
+    hazards: Vec<EngineCoord>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+struct EngineSnake {
+    #[serde(rename = "ID")]
+    id: String,
+    name: String,
+    body: Vec<EngineCoord>,
+    health: i32,
+    /// Set once the snake has been eliminated. Example: {"Cause": "wall-collision", "Turn": 12}
+    #[serde(default)]
+    death: Option<Value>,
+    #[serde(default)]
+    latency: String,
+    #[serde(default)]
+    shout: String,
+    #[serde(default)]
+    squad: String,
+    #[serde(default)]
+    color: String,
+    #[serde(default)]
+    head_type: String,
+    #[serde(default)]
+    tail_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+struct EngineCoord {
+    #[serde(rename = "X")]
+    x: i32,
+    #[serde(rename = "Y")]
+    y: i32,
+}
+
+impl EngineCoord {
+    fn to_json(&self) -> Value {
+        json!({ "x": self.x, "y": self.y })
+    }
+}
+
+impl EngineSnake {
+    /// The snake as it appears in a move request.
+    fn to_json(&self) -> Value {
+        let body: Vec<Value> = self.body.iter().map(EngineCoord::to_json).collect();
+        json!({
+            "id": self.id,
+            "name": self.name,
+            "health": self.health,
+            "head": body.first().cloned().unwrap_or(Value::Null),
+            "length": body.len(),
+            "body": body,
+            "latency": self.latency,
+            "shout": self.shout,
+            "squad": self.squad,
+            "customizations": {
+                "color": self.color,
+                "head": self.head_type,
+                "tail": self.tail_type,
+            },
+        })
+    }
+}
+
+impl EngineGame {
+    /// Ruleset settings may be exported as strings or as JSON values.
+    fn setting(&self, names: &[&str], default: Value) -> Value {
+        let value = names.iter().find_map(|name| self.ruleset.get(*name));
+        match value {
+            Some(Value::String(s)) => serde_json::from_str(s).unwrap_or(Value::String(s.clone())),
+            Some(value) => value.clone(),
+            None => default,
+        }
+    }
+    /// The game as it appears in a move request.
+    fn to_json(&self) -> Value {
+        let map = if self.map.is_empty() {
+            "standard"
+        } else {
+            &self.map
+        };
+        json!({
+            "id": self.id,
+            "ruleset": {
+                "name": self.setting(&["name"], json!("standard")),
+                "version": self.ruleset_version,
+                "settings": {
+                    "foodSpawnChance": self.setting(&["foodSpawnChance"], json!(15)),
+                    "minimumFood": self.setting(&["minimumFood"], json!(1)),
+                    "hazardDamagePerTurn": self.setting(&["hazardDamagePerTurn", "damagePerTurn"], json!(14)),
+                    "royale": {
+                        "shrinkEveryNTurns": self.setting(&["shrinkEveryNTurns"], json!(25)),
+                    },
+                    "squad": {
+                        "allowBodyCollisions": self.setting(&["allowBodyCollisions"], json!(false)),
+                        "sharedElimination": self.setting(&["sharedElimination"], json!(false)),
+                        "sharedHealth": self.setting(&["sharedHealth"], json!(false)),
+                        "sharedLength": self.setting(&["sharedLength"], json!(false)),
+                    },
+                },
+            },
+            "map": map,
+            "timeout": self.snake_timeout,
+            "source": self.source,
+        })
+    }
+}
+
+/// Converts every frame of an exported game into the game state the given snake would have
+/// received, picking the snake by id or name, or the first snake when none is given.
+///
+/// Frames after the snake was eliminated are skipped, as are snakes already eliminated in a frame.
+pub fn import(export: &EngineExport, snake: Option<&str>) -> Result<Vec<GameState>, String> {
+    let first = export.frames.first().and_then(|frame| frame.snakes.first());
+    let you = match snake {
+        Some(snake) => export
+            .frames
+            .iter()
+            .flat_map(|frame| &frame.snakes)
+            .find(|s| s.id == snake || s.name == snake)
+            .ok_or_else(|| format!("no snake named {:?} in game {:?}", snake, export.game.id))?,
+        None => first.ok_or_else(|| format!("game {:?} has no snakes", export.game.id))?,
+    };
+    let game = export.game.to_json();
+    let mut states: Vec<GameState> = Vec::new();
+    for frame in &export.frames {
+        let alive: Vec<&EngineSnake> = frame.snakes.iter().filter(|s| s.death.is_none()).collect();
+        let me = match alive.iter().find(|s| s.id == you.id) {
+            Some(me) => me,
+            None => continue,
+        };
+        let state = json!({
+            "game": game,
+            "turn": frame.turn,
+            "board": {
+                "height": export.game.height,
+                "width": export.game.width,
+                "food": frame.food.iter().map(EngineCoord::to_json).collect::<Vec<Value>>(),
+                "hazards": frame.hazards.iter().map(EngineCoord::to_json).collect::<Vec<Value>>(),
+                "snakes": alive.iter().map(|s| s.to_json()).collect::<Vec<Value>>(),
+            },
+            "you": me.to_json(),
+        });
+        let state = serde_json::from_value(state)
+            .map_err(|e| format!("unable to convert turn {}: {}", frame.turn, e))?;
+        states.push(state);
+    }
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "Game": {
+            "ID": "exported",
+            "Width": 7,
+            "Height": 7,
+            "Ruleset": {"name": "standard", "foodSpawnChance": "15", "minimumFood": "1", "damagePerTurn": "14"},
+            "SnakeTimeout": 500,
+            "Source": "league",
+            "Map": "standard"
+        },
+        "Frames": [
+            {
+                "Turn": 0,
+                "Snakes": [
+                    {"ID": "one", "Name": "One", "Body": [{"X": 1, "Y": 1}, {"X": 1, "Y": 1}, {"X": 1, "Y": 1}], "Health": 100, "Death": null},
+                    {"ID": "two", "Name": "Two", "Body": [{"X": 5, "Y": 5}, {"X": 5, "Y": 5}, {"X": 5, "Y": 5}], "Health": 100, "Death": null}
+                ],
+                "Food": [{"X": 3, "Y": 3}],
+                "Hazards": []
+            },
+            {
+                "Turn": 1,
+                "Snakes": [
+                    {"ID": "one", "Name": "One", "Body": [{"X": 1, "Y": 2}, {"X": 1, "Y": 1}, {"X": 1, "Y": 1}], "Health": 99, "Death": null},
+                    {"ID": "two", "Name": "Two", "Body": [{"X": 5, "Y": 6}, {"X": 5, "Y": 5}, {"X": 5, "Y": 5}], "Health": 99, "Death": {"Cause": "wall-collision", "Turn": 1}}
+                ],
+                "Food": [{"X": 3, "Y": 3}],
+                "Hazards": []
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_import() {
+        let export: EngineExport = serde_json::from_str(EXPORT).unwrap();
+        let states = import(&export, None).unwrap();
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].game_id(), "exported");
+        assert_eq!(states[1].turn(), 1);
+        let state = serde_json::to_value(&states[1]).unwrap();
+        assert_eq!(state["you"]["id"], "one");
+        assert_eq!(state["you"]["head"], json!({"x": 1, "y": 2}));
+        assert_eq!(state["board"]["snakes"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            state["game"]["ruleset"]["settings"]["hazardDamagePerTurn"],
+            14
+        );
+        // The second snake is eliminated on turn 1, so only its first turn can be replayed
+        assert_eq!(import(&export, Some("Two")).unwrap().len(), 1);
+        assert!(import(&export, Some("three")).is_err());
+    }
+}
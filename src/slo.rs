@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+/// Turns measured before the margin is judged, so one slow turn at the start can't alert.
+const MIN_SLO_TURNS: usize = 20;
+
+/// How much of the game timeout our responses should leave, read from the `slo` config table.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SloConfig {
+    /// Most recent turns the margin is measured over, across every game.
+    pub window: usize,
+    /// Margin under the game timeout, in milliseconds, that 99% of turns should leave.
+    pub min_margin: i64,
+    /// URL an alert is posted to as JSON when the margin drops below `min_margin`, and again
+    /// when it recovers.
+    pub webhook: Option<String>,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        SloConfig {
+            window: 200,
+            min_margin: 50,
+            webhook: None,
+        }
+    }
+}
+
+/// The margin crossing the threshold, one way or the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SloAlert {
+    /// Whether the margin dropped below the threshold, rather than recovered above it.
+    pub breached: bool,
+    /// Margin under the game timeout that 99% of turns in the window left, in milliseconds.
+    /// Negative once more than 1% of turns timed out.
+    pub p99_margin: i64,
+    pub min_margin: i64,
+    /// Turns in the window.
+    pub turns: usize,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    /// Timeout minus latency of each turn, oldest first.
+    margins: VecDeque<i64>,
+    breached: bool,
+}
+
+/// Tracks the latency the Battlesnake engine reports for our moves against each game's timeout,
+/// over a rolling window of turns, to alert before timeouts start losing games.
+#[derive(Debug, Default)]
+pub struct ResponseSlo {
+    config: SloConfig,
+    window: Mutex<Window>,
+}
+
+impl ResponseSlo {
+    pub fn new(config: SloConfig) -> Self {
+        ResponseSlo {
+            config,
+            window: Mutex::new(Window::default()),
+        }
+    }
+    /// Records a turn answered with `latency` in a game with a `timeout`, both in milliseconds,
+    /// returning an alert when the margin crosses the threshold.
+    pub fn observe(&self, latency: u32, timeout: u32) -> Option<SloAlert> {
+        let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+        if window.margins.len() >= self.config.window.max(1) {
+            window.margins.pop_front();
+        }
+        window.margins.push_back(timeout as i64 - latency as i64);
+        if window.margins.len() < MIN_SLO_TURNS {
+            return None;
+        }
+        let p99_margin = p99_margin(&window.margins);
+        let breached = p99_margin < self.config.min_margin;
+        if breached == window.breached {
+            return None;
+        }
+        window.breached = breached;
+        Some(SloAlert {
+            breached,
+            p99_margin,
+            min_margin: self.config.min_margin,
+            turns: window.margins.len(),
+        })
+    }
+    pub fn webhook(&self) -> Option<&str> {
+        self.config.webhook.as_deref()
+    }
+}
+
+/// The margin 99% of `margins` are at least.
+fn p99_margin(margins: &VecDeque<i64>) -> i64 {
+    let mut sorted: Vec<i64> = margins.iter().copied().collect();
+    sorted.sort_unstable();
+    sorted[sorted.len() / 100]
+}
+
+/// Reports an alert as a warning event, which reaches the collector with the `otlp` feature, and
+/// posts it to `webhook`. Blocks until the webhook answers.
+pub fn report(alert: &SloAlert, webhook: Option<&str>) {
+    if alert.breached {
+        tracing::warn!(
+            slo.p99_margin = alert.p99_margin,
+            slo.min_margin = alert.min_margin,
+            slo.turns = alert.turns,
+            "response time margin below the objective"
+        );
+    } else {
+        tracing::info!(
+            slo.p99_margin = alert.p99_margin,
+            slo.min_margin = alert.min_margin,
+            slo.turns = alert.turns,
+            "response time margin recovered"
+        );
+    }
+    if let Some(webhook) = webhook {
+        if let Err(e) = ureq::post(webhook).send_json(alert) {
+            warn!("unable to send response time alert to {}: {}", webhook, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe() {
+        let slo = ResponseSlo::new(SloConfig {
+            window: 100,
+            min_margin: 50,
+            webhook: None,
+        });
+        // Too few turns to judge, however slow
+        assert_eq!(slo.observe(490, 500), None);
+        for _ in 0..MIN_SLO_TURNS - 2 {
+            assert_eq!(slo.observe(200, 500), None);
+        }
+        // One slow turn in 20 is more than 1%
+        let alert = slo.observe(200, 500).unwrap();
+        assert!(alert.breached);
+        assert_eq!(alert.p99_margin, 10);
+        // Only crossing back alerts again, once the slow turn is under 1% of the window
+        for _ in 0..79 {
+            assert_eq!(slo.observe(200, 500), None);
+        }
+        let alert = slo.observe(200, 500).unwrap();
+        assert!(!alert.breached);
+        assert_eq!(alert.turns, 100);
+    }
+}
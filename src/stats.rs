@@ -0,0 +1,546 @@
+use crate::battlesnake::{Decision, GameOutcome};
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct WinRate {
+    /// The mode, map or opponent name these games are grouped by. Example: "standard"
+    key: String,
+    games: u32,
+    wins: u32,
+    /// Fraction of games won, between 0 and 1.
+    win_rate: f64,
+}
+
+#[cfg(feature = "persistence")]
+impl WinRate {
+    fn new(key: String, games: u32, wins: u32) -> Self {
+        let win_rate = if games == 0 {
+            0.0
+        } else {
+            wins as f64 / games as f64
+        };
+        WinRate {
+            key,
+            games,
+            wins,
+            win_rate,
+        }
+    }
+}
+
+/// Percentiles of the time from a move request arriving to responding, in milliseconds.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Percentiles {
+    p50: u32,
+    p90: u32,
+    p99: u32,
+    max: u32,
+}
+
+#[cfg(feature = "persistence")]
+impl Percentiles {
+    fn new(values: &mut [u32]) -> Self {
+        values.sort_unstable();
+        // Nearest rank: the smallest value at least `percent` of the values are no greater than
+        let at = |percent: usize| match values.len() {
+            0 => 0,
+            len => values[((len * percent) as f64 / 100.0).ceil().max(1.0) as usize - 1],
+        };
+        Percentiles {
+            p50: at(50),
+            p90: at(90),
+            p99: at(99),
+            max: at(100),
+        }
+    }
+}
+
+/// How fast and how deep we searched over a group of games, for seeing where in a game the
+/// search runs short.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Performance {
+    /// The mode or game id these games are grouped by. Example: "standard"
+    key: String,
+    /// Games with their moves stored. Games stored before moves were have none.
+    games: u32,
+    moves: u32,
+    response_time: Percentiles,
+    /// Average depth of the deepest completed search iteration on each turn, from turn 0, over
+    /// the moves that weren't fallbacks. Moves played by rule rather than searched, such as the
+    /// opening's, count as depth 0. Null for turns where every move was a fallback.
+    depth_by_turn: Vec<Option<f64>>,
+    /// Moves answered with the fallback move because the search failed or overran its deadline.
+    fallbacks: u32,
+}
+
+/// A move as stored for `Performance`.
+#[cfg(feature = "persistence")]
+#[derive(Debug, Clone, Copy)]
+struct StoredMove {
+    /// Row id of the outcome of the game the move was made in.
+    game: i64,
+    turn: u32,
+    elapsed: u32,
+    depth: u32,
+    fallback: bool,
+}
+
+#[cfg(feature = "persistence")]
+impl Performance {
+    fn new(key: String, moves: &[StoredMove]) -> Self {
+        let mut games: Vec<i64> = moves.iter().map(|m| m.game).collect();
+        games.sort_unstable();
+        games.dedup();
+        let mut elapsed: Vec<u32> = moves.iter().map(|m| m.elapsed).collect();
+        let last_turn = moves.iter().map(|m| m.turn as usize).max();
+        let mut depths: Vec<(u32, u32)> = vec![(0, 0); last_turn.map_or(0, |turn| turn + 1)];
+        for m in moves.iter().filter(|m| !m.fallback) {
+            let (sum, count) = &mut depths[m.turn as usize];
+            *sum += m.depth;
+            *count += 1;
+        }
+        Performance {
+            key,
+            games: games.len() as u32,
+            moves: moves.len() as u32,
+            response_time: Percentiles::new(&mut elapsed),
+            depth_by_turn: depths
+                .into_iter()
+                .map(|(sum, count)| (count > 0).then(|| sum as f64 / count as f64))
+                .collect(),
+            fallbacks: moves.iter().filter(|m| m.fallback).count() as u32,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Stats {
+    /// Win rate across every recorded game.
+    total: WinRate,
+    by_mode: Vec<WinRate>,
+    by_map: Vec<WinRate>,
+    by_opponent: Vec<WinRate>,
+    /// Win rate of each experiment variant, keyed "<experiment>/<strategy>".
+    by_variant: Vec<WinRate>,
+    /// Response times and search depths in each mode.
+    performance_by_mode: Vec<Performance>,
+}
+
+/// Stores game outcomes in SQLite when built with the `persistence` feature and given a database path.
+#[derive(Debug, Default)]
+pub struct StatsStore {
+    #[cfg(feature = "persistence")]
+    connection: Option<std::sync::Mutex<rusqlite::Connection>>,
+}
+
+#[cfg(not(feature = "persistence"))]
+impl StatsStore {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        if path.is_some() {
+            warn!("stats database configured, but built without the persistence feature");
+        }
+        StatsStore {}
+    }
+    pub fn enabled(&self) -> bool {
+        false
+    }
+    pub fn record(&self, _outcome: &GameOutcome, _moves: &[Decision]) {}
+    pub fn stats(&self) -> Option<Stats> {
+        None
+    }
+    pub fn game(&self, _id: &str) -> Option<Performance> {
+        None
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl StatsStore {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let connection = path.and_then(|path| match sqlite::open(&path) {
+            Ok(connection) => Some(std::sync::Mutex::new(connection)),
+            Err(e) => {
+                warn!("unable to open stats database {:?}: {}", path, e);
+                None
+            }
+        });
+        StatsStore { connection }
+    }
+    pub fn enabled(&self) -> bool {
+        self.connection.is_some()
+    }
+    /// Stores the outcome of a game with the moves we made in it.
+    pub fn record(&self, outcome: &GameOutcome, moves: &[Decision]) {
+        if let Some(connection) = &self.connection {
+            let mut connection = connection
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if let Err(e) = sqlite::insert(&mut connection, outcome, moves) {
+                warn!(
+                    "unable to record outcome of game {:?}: {}",
+                    outcome.game_id, e
+                );
+            }
+        }
+    }
+    pub fn stats(&self) -> Option<Stats> {
+        let connection = self
+            .connection
+            .as_ref()?
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match sqlite::stats(&connection) {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                warn!("unable to read stats: {}", e);
+                None
+            }
+        }
+    }
+    /// Response times and search depths of the stored game with the given id, if it has moves.
+    pub fn game(&self, id: &str) -> Option<Performance> {
+        let connection = self
+            .connection
+            .as_ref()?
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match sqlite::game(&connection, id) {
+            Ok(performance) => performance,
+            Err(e) => {
+                warn!("unable to read stats of game {:?}: {}", id, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "persistence")]
+mod sqlite {
+    use super::{Performance, Stats, StoredMove, WinRate};
+    use crate::battlesnake::{Decision, GameOutcome};
+    use rusqlite::{params, Connection, Params, Result};
+    use std::path::Path;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS outcomes (
+            id INTEGER PRIMARY KEY,
+            game_id TEXT NOT NULL,
+            mode TEXT NOT NULL,
+            map TEXT NOT NULL,
+            turns INTEGER NOT NULL,
+            placement INTEGER NOT NULL,
+            won INTEGER NOT NULL,
+            cause TEXT NOT NULL,
+            finished_at INTEGER NOT NULL,
+            variant TEXT
+        );
+        CREATE TABLE IF NOT EXISTS opponents (
+            outcome_id INTEGER NOT NULL REFERENCES outcomes(id),
+            name TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS moves (
+            outcome_id INTEGER NOT NULL REFERENCES outcomes(id),
+            turn INTEGER NOT NULL,
+            elapsed INTEGER NOT NULL,
+            depth INTEGER NOT NULL,
+            fallback INTEGER NOT NULL
+        );
+    ";
+
+    pub fn open(path: &Path) -> Result<Connection> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        migrate(&connection)?;
+        Ok(connection)
+    }
+
+    /// Brings databases created by older versions up to the current schema.
+    fn migrate(connection: &Connection) -> Result<()> {
+        if connection
+            .prepare("SELECT variant FROM outcomes LIMIT 0")
+            .is_err()
+        {
+            connection.execute_batch("ALTER TABLE outcomes ADD COLUMN variant TEXT")?;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Connection> {
+        let connection = Connection::open_in_memory()?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(connection)
+    }
+
+    pub fn insert(
+        connection: &mut Connection,
+        outcome: &GameOutcome,
+        moves: &[Decision],
+    ) -> Result<()> {
+        let finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let cause = serde_json::to_value(outcome.cause)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_owned))
+            .unwrap_or_default();
+        let tx = connection.transaction()?;
+        tx.execute(
+            "INSERT INTO outcomes (game_id, mode, map, turns, placement, won, cause, finished_at, variant)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                outcome.game_id,
+                outcome.mode,
+                outcome.map,
+                outcome.turns,
+                outcome.placement,
+                outcome.won,
+                cause,
+                finished_at,
+                outcome.variant
+            ],
+        )?;
+        let outcome_id = tx.last_insert_rowid();
+        for name in &outcome.opponents {
+            tx.execute(
+                "INSERT INTO opponents (outcome_id, name) VALUES (?1, ?2)",
+                params![outcome_id, name],
+            )?;
+        }
+        for decision in moves {
+            tx.execute(
+                "INSERT INTO moves (outcome_id, turn, elapsed, depth, fallback)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    outcome_id,
+                    decision.turn,
+                    decision.elapsed,
+                    decision.depth,
+                    decision.score.is_none()
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    fn win_rates(connection: &Connection, query: &str) -> Result<Vec<WinRate>> {
+        let mut statement = connection.prepare(query)?;
+        let rows = statement.query_map([], |row| {
+            Ok(WinRate::new(row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        rows.collect()
+    }
+
+    /// The performance of each group of moves selected by `query`, whose first column is the
+    /// group's key. Moves of a group must come together.
+    fn performances<P: Params>(
+        connection: &Connection,
+        query: &str,
+        params: P,
+    ) -> Result<Vec<Performance>> {
+        let mut statement = connection.prepare(query)?;
+        let rows = statement.query_map(params, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                StoredMove {
+                    game: row.get(1)?,
+                    turn: row.get(2)?,
+                    elapsed: row.get(3)?,
+                    depth: row.get(4)?,
+                    fallback: row.get(5)?,
+                },
+            ))
+        })?;
+        let mut performances = Vec::new();
+        let mut group: Option<(String, Vec<StoredMove>)> = None;
+        for row in rows {
+            let (key, stored) = row?;
+            match &mut group {
+                Some((group_key, moves)) if *group_key == key => moves.push(stored),
+                _ => {
+                    if let Some((key, moves)) = group.take() {
+                        performances.push(Performance::new(key, &moves));
+                    }
+                    group = Some((key, vec![stored]));
+                }
+            }
+        }
+        if let Some((key, moves)) = group {
+            performances.push(Performance::new(key, &moves));
+        }
+        Ok(performances)
+    }
+
+    pub fn game(connection: &Connection, id: &str) -> Result<Option<Performance>> {
+        let mut performances = performances(
+            connection,
+            "SELECT outcomes.game_id, outcomes.id, moves.turn, moves.elapsed, moves.depth,
+                 moves.fallback
+             FROM moves JOIN outcomes ON outcomes.id = moves.outcome_id
+             WHERE outcomes.game_id = ?1",
+            params![id],
+        )?;
+        Ok(performances.pop())
+    }
+
+    pub fn stats(connection: &Connection) -> Result<Stats> {
+        let total = win_rates(
+            connection,
+            "SELECT 'all', COUNT(*), COALESCE(SUM(won), 0) FROM outcomes",
+        )?
+        .pop()
+        .unwrap_or_else(|| WinRate::new("all".to_owned(), 0, 0));
+        Ok(Stats {
+            total,
+            by_mode: win_rates(
+                connection,
+                "SELECT mode, COUNT(*), SUM(won) FROM outcomes GROUP BY mode ORDER BY mode",
+            )?,
+            by_map: win_rates(
+                connection,
+                "SELECT map, COUNT(*), SUM(won) FROM outcomes GROUP BY map ORDER BY map",
+            )?,
+            by_opponent: win_rates(
+                connection,
+                "SELECT opponents.name, COUNT(*), SUM(outcomes.won) FROM opponents
+                 JOIN outcomes ON outcomes.id = opponents.outcome_id
+                 GROUP BY opponents.name ORDER BY opponents.name",
+            )?,
+            by_variant: win_rates(
+                connection,
+                "SELECT variant, COUNT(*), SUM(won) FROM outcomes WHERE variant IS NOT NULL
+                 GROUP BY variant ORDER BY variant",
+            )?,
+            performance_by_mode: performances(
+                connection,
+                "SELECT outcomes.mode, outcomes.id, moves.turn, moves.elapsed, moves.depth,
+                     moves.fallback
+                 FROM moves JOIN outcomes ON outcomes.id = moves.outcome_id
+                 ORDER BY outcomes.mode",
+                [],
+            )?,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "persistence"))]
+mod tests {
+    use super::*;
+    use crate::battlesnake::{DeathCause, Direction};
+
+    fn outcome(game_id: &str, map: &str, opponent: &str, won: bool) -> GameOutcome {
+        GameOutcome {
+            game_id: game_id.to_owned(),
+            snake_id: "you".to_owned(),
+            mode: "standard".to_owned(),
+            map: map.to_owned(),
+            opponents: vec![opponent.to_owned()],
+            turns: 100,
+            placement: if won { 1 } else { 2 },
+            won,
+            cause: if won {
+                DeathCause::Survived
+            } else {
+                DeathCause::HeadToHead
+            },
+            variant: Some(format!("search/{}", map)),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let store = StatsStore {
+            connection: Some(std::sync::Mutex::new(sqlite::open_in_memory().unwrap())),
+        };
+        assert_eq!(store.stats().unwrap().total.games, 0);
+        store.record(&outcome("one", "standard", "a", true), &[]);
+        store.record(&outcome("two", "standard", "b", false), &[]);
+        store.record(&outcome("three", "arcade_maze", "a", true), &[]);
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.total, WinRate::new("all".to_owned(), 3, 2));
+        assert_eq!(
+            stats.by_map,
+            vec![
+                WinRate::new("arcade_maze".to_owned(), 1, 1),
+                WinRate::new("standard".to_owned(), 2, 1),
+            ]
+        );
+        assert_eq!(
+            stats.by_opponent,
+            vec![
+                WinRate::new("a".to_owned(), 2, 2),
+                WinRate::new("b".to_owned(), 1, 0),
+            ]
+        );
+        assert_eq!(
+            stats.by_variant,
+            vec![
+                WinRate::new("search/arcade_maze".to_owned(), 1, 1),
+                WinRate::new("search/standard".to_owned(), 2, 1),
+            ]
+        );
+    }
+
+    fn decision(turn: u32, elapsed: u32, depth: u32, fallback: bool) -> Decision {
+        Decision {
+            turn,
+            direction: Direction::Up,
+            score: (!fallback).then_some(0),
+            depth,
+            elapsed,
+            shadow: None,
+        }
+    }
+
+    #[test]
+    fn test_performance() {
+        let store = StatsStore {
+            connection: Some(std::sync::Mutex::new(sqlite::open_in_memory().unwrap())),
+        };
+        assert_eq!(store.game("one"), None);
+        let one: Vec<Decision> = (0..100)
+            .map(|turn| decision(turn, turn + 1, 10, false))
+            .collect();
+        let two = vec![
+            decision(0, 300, 0, true),
+            decision(1, 200, 4, false),
+            decision(2, 100, 2, false),
+        ];
+        store.record(&outcome("one", "standard", "a", true), &one);
+        store.record(&outcome("two", "standard", "b", false), &two);
+        store.record(&outcome("three", "standard", "a", true), &[]);
+
+        let game = store.game("two").unwrap();
+        assert_eq!(game.key, "two");
+        assert_eq!((game.games, game.moves, game.fallbacks), (1, 3, 1));
+        assert_eq!(game.depth_by_turn, vec![None, Some(4.0), Some(2.0)]);
+        assert_eq!(
+            game.response_time,
+            Percentiles {
+                p50: 200,
+                p90: 300,
+                p99: 300,
+                max: 300,
+            }
+        );
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.performance_by_mode.len(), 1);
+        let standard = &stats.performance_by_mode[0];
+        assert_eq!(standard.key, "standard");
+        assert_eq!(
+            (standard.games, standard.moves, standard.fallbacks),
+            (2, 103, 1)
+        );
+        assert_eq!(standard.depth_by_turn.len(), 100);
+        assert_eq!(standard.depth_by_turn[0], Some(10.0));
+        assert_eq!(standard.depth_by_turn[1], Some(7.0));
+        assert_eq!(standard.depth_by_turn[99], Some(10.0));
+        assert_eq!(standard.response_time.p50, 52);
+        assert_eq!(standard.response_time.max, 300);
+    }
+}
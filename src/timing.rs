@@ -0,0 +1,42 @@
+use rocket::fairing::AdHoc;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use std::time::Instant;
+
+/// When a request arrived, before its body was read or parsed.
+///
+/// Move deadlines are measured from here, so time spent queueing and parsing counts against them.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestStart(pub Instant);
+
+impl RequestStart {
+    /// Records the arrival time of every request as soon as its headers are read.
+    pub fn fairing() -> AdHoc {
+        AdHoc::on_request("Request Start", |request, _| {
+            Box::pin(async move {
+                request.local_cache(|| RequestStart(Instant::now()));
+            })
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestStart {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(*request.local_cache(|| RequestStart(Instant::now())))
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for RequestStart {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
@@ -0,0 +1,421 @@
+use crate::battlesnake::{play, Player, SelfPlayConfig, Strategy};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// A named configuration entered into a tournament.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entrant {
+    /// Example: "minimax-100ms"
+    pub name: String,
+    pub strategy: Strategy,
+    /// Search time per turn, in milliseconds, when it differs from the tournament's.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+impl Entrant {
+    fn player(&self) -> Player {
+        Player {
+            strategy: self.strategy,
+            timeout: self.timeout.map(u128::from),
+        }
+    }
+}
+
+/// Sequential probability ratio test of whether the first entrant of a pairing is at least
+/// `elo1` stronger than the second rather than at most `elo0`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Sprt {
+    #[serde(default)]
+    pub elo0: f64,
+    #[serde(default = "default_elo1")]
+    pub elo1: f64,
+    /// Chance of accepting `elo1` when `elo0` holds.
+    #[serde(default = "default_error")]
+    pub alpha: f64,
+    /// Chance of accepting `elo0` when `elo1` holds.
+    #[serde(default = "default_error")]
+    pub beta: f64,
+}
+
+fn default_elo1() -> f64 {
+    10.0
+}
+
+fn default_error() -> f64 {
+    0.05
+}
+
+impl Sprt {
+    /// The log-likelihood ratios at which `elo0` and `elo1` are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentConfig {
+    pub entrants: Vec<Entrant>,
+    /// Games played by every pair of entrants, alternating who takes the first seat.
+    pub games: u32,
+    #[serde(default = "default_board_size")]
+    pub board_size: i32,
+    /// Search time per turn for entrants that don't set their own, in milliseconds.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    #[serde(default = "default_max_turns")]
+    pub max_turns: u32,
+    /// Stops each pairing early once the test accepts either hypothesis.
+    #[serde(default)]
+    pub sprt: Option<Sprt>,
+}
+
+fn default_board_size() -> i32 {
+    11
+}
+
+fn default_timeout() -> u64 {
+    50
+}
+
+fn default_max_turns() -> u32 {
+    500
+}
+
+/// Wins, draws and losses of one side of a set of games, and the Elo difference they suggest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Score {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+    /// Points per game, counting draws as half a win.
+    pub fn score(&self) -> f64 {
+        match self.games() {
+            0 => 0.5,
+            games => (self.wins as f64 + self.draws as f64 / 2.0) / games as f64,
+        }
+    }
+    /// Variance of the points scored in a single game.
+    fn variance(&self) -> f64 {
+        let games = self.games() as f64;
+        let s = self.score();
+        (self.wins as f64 * (1.0 - s).powi(2)
+            + self.draws as f64 * (0.5 - s).powi(2)
+            + self.losses as f64 * s.powi(2))
+            / games
+    }
+    /// Estimated Elo difference, which is unbounded until a game has been won and lost.
+    pub fn elo(&self) -> Option<f64> {
+        let s = self.score();
+        if self.games() == 0 || s <= 0.0 || s >= 1.0 {
+            return None;
+        }
+        Some(elo(s))
+    }
+    /// Half the width of the 95% confidence interval of the Elo difference.
+    pub fn elo_margin(&self) -> Option<f64> {
+        self.elo()?;
+        let error = 1.96 * (self.variance() / self.games() as f64).sqrt();
+        let s = self.score();
+        let (low, high) = ((s - error).max(1e-6), (s + error).min(1.0 - 1e-6));
+        Some((elo(high) - elo(low)) / 2.0)
+    }
+    /// Log-likelihood ratio of the test's hypotheses, using the normal approximation.
+    pub fn llr(&self, sprt: &Sprt) -> f64 {
+        let variance = self.variance();
+        if self.games() == 0 || variance <= 0.0 {
+            return 0.0;
+        }
+        let (s0, s1) = (expected_score(sprt.elo0), expected_score(sprt.elo1));
+        let s = self.score();
+        (s1 - s0) * (2.0 * s - s0 - s1) / (2.0 * variance / self.games() as f64)
+    }
+    fn reversed(&self) -> Score {
+        Score {
+            wins: self.losses,
+            draws: self.draws,
+            losses: self.wins,
+        }
+    }
+    fn add(&mut self, other: &Score) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+    }
+}
+
+/// Elo difference at which the stronger side expects the given points per game.
+fn elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The first entrant is at least `elo1` stronger.
+    H1,
+    /// The first entrant is at most `elo0` stronger.
+    H0,
+    Inconclusive,
+}
+
+/// The games between two entrants, from the first entrant's side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pairing {
+    pub first: String,
+    pub second: String,
+    pub score: Score,
+    pub elo: Option<f64>,
+    pub elo_margin: Option<f64>,
+    /// Present when the tournament ran a sequential probability ratio test.
+    pub llr: Option<f64>,
+    pub verdict: Option<Verdict>,
+}
+
+/// An entrant's results against the whole field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Standing {
+    pub name: String,
+    pub score: Score,
+    /// Elo relative to the average opponent it played.
+    pub elo: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub standings: Vec<Standing>,
+    pub pairings: Vec<Pairing>,
+}
+
+fn verdict(llr: f64, sprt: &Sprt) -> Verdict {
+    let (lower, upper) = sprt.bounds();
+    if llr >= upper {
+        Verdict::H1
+    } else if llr <= lower {
+        Verdict::H0
+    } else {
+        Verdict::Inconclusive
+    }
+}
+
+/// Plays a round robin between every pair of entrants and reports their relative strength.
+///
+/// `on_game` is called after every game with the pairing's standing so far.
+pub fn run(config: &TournamentConfig, mut on_game: impl FnMut(&Pairing)) -> Report {
+    let entrants = &config.entrants;
+    let mut totals = vec![Score::default(); entrants.len()];
+    let mut pairings: Vec<Pairing> = Vec::new();
+    for (i, first) in entrants.iter().enumerate() {
+        for (j, second) in entrants.iter().enumerate().skip(i + 1) {
+            let self_play = SelfPlayConfig {
+                board_size: config.board_size,
+                snakes: 2,
+                timeout: config.timeout as u128,
+                max_turns: config.max_turns,
+                players: vec![first.player(), second.player()],
+            };
+            let mut pairing = Pairing {
+                first: first.name.clone(),
+                second: second.name.clone(),
+                score: Score::default(),
+                elo: None,
+                elo_margin: None,
+                llr: None,
+                verdict: None,
+            };
+            for game in 0..config.games {
+                match play(game, &self_play).winner() {
+                    Some(0) => pairing.score.wins += 1,
+                    Some(_) => pairing.score.losses += 1,
+                    None => pairing.score.draws += 1,
+                }
+                pairing.elo = pairing.score.elo();
+                pairing.elo_margin = pairing.score.elo_margin();
+                if let Some(sprt) = &config.sprt {
+                    let llr = pairing.score.llr(sprt);
+                    pairing.llr = Some(llr);
+                    pairing.verdict = Some(verdict(llr, sprt));
+                }
+                on_game(&pairing);
+                if !matches!(pairing.verdict, None | Some(Verdict::Inconclusive)) {
+                    break;
+                }
+            }
+            totals[i].add(&pairing.score);
+            totals[j].add(&pairing.score.reversed());
+            pairings.push(pairing);
+        }
+    }
+    let mut standings: Vec<Standing> = entrants
+        .iter()
+        .zip(totals)
+        .map(|(entrant, score)| Standing {
+            name: entrant.name.clone(),
+            score,
+            elo: score.elo(),
+        })
+        .collect();
+    standings.sort_by(|a, b| b.score.score().total_cmp(&a.score.score()));
+    Report {
+        standings,
+        pairings,
+    }
+}
+
+fn format_elo(elo: Option<f64>, margin: Option<f64>) -> String {
+    match (elo, margin) {
+        (Some(elo), Some(margin)) => format!("{:+.1} ± {:.1}", elo, margin),
+        (Some(elo), None) => format!("{:+.1}", elo),
+        _ => "-".to_owned(),
+    }
+}
+
+impl Report {
+    pub fn markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("## Standings\n\n");
+        out.push_str("| # | Entrant | Games | W | D | L | Score | Elo |\n");
+        out.push_str("|---|---------|-------|---|---|---|-------|-----|\n");
+        for (rank, standing) in self.standings.iter().enumerate() {
+            let score = &standing.score;
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | {:.1}% | {} |",
+                rank + 1,
+                standing.name,
+                score.games(),
+                score.wins,
+                score.draws,
+                score.losses,
+                score.score() * 100.0,
+                format_elo(standing.elo, None)
+            );
+        }
+        out.push_str("\n## Pairings\n\n");
+        out.push_str("| Pairing | Games | W | D | L | Elo | LLR | Verdict |\n");
+        out.push_str("|---------|-------|---|---|---|-----|-----|---------|\n");
+        for pairing in &self.pairings {
+            let score = &pairing.score;
+            let llr = match pairing.llr {
+                Some(llr) => format!("{:.2}", llr),
+                None => "-".to_owned(),
+            };
+            let verdict = match pairing.verdict {
+                Some(Verdict::H1) => "H1",
+                Some(Verdict::H0) => "H0",
+                Some(Verdict::Inconclusive) => "inconclusive",
+                None => "-",
+            };
+            let _ = writeln!(
+                out,
+                "| {} vs {} | {} | {} | {} | {} | {} | {} | {} |",
+                pairing.first,
+                pairing.second,
+                score.games(),
+                score.wins,
+                score.draws,
+                score.losses,
+                format_elo(pairing.elo, pairing.elo_margin),
+                llr,
+                verdict
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let even = Score {
+            wins: 10,
+            draws: 0,
+            losses: 10,
+        };
+        assert_eq!(even.score(), 0.5);
+        assert_eq!(even.elo(), Some(0.0));
+        let ahead = Score {
+            wins: 15,
+            draws: 0,
+            losses: 5,
+        };
+        assert!((ahead.elo().unwrap() - 190.85).abs() < 0.01);
+        assert!(ahead.elo_margin().unwrap() > 0.0);
+        let perfect = Score {
+            wins: 5,
+            draws: 0,
+            losses: 0,
+        };
+        assert_eq!(perfect.elo(), None);
+        let behind = ahead.reversed().elo().unwrap();
+        assert!((behind + ahead.elo().unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sprt() {
+        let sprt = Sprt {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        };
+        let (lower, upper) = sprt.bounds();
+        assert!((upper - 2.944).abs() < 0.001);
+        assert_eq!(lower, -upper);
+        let strong = Score {
+            wins: 600,
+            draws: 0,
+            losses: 400,
+        };
+        assert_eq!(verdict(strong.llr(&sprt), &sprt), Verdict::H1);
+        assert_eq!(verdict(strong.reversed().llr(&sprt), &sprt), Verdict::H0);
+        let few = Score {
+            wins: 2,
+            draws: 1,
+            losses: 1,
+        };
+        assert_eq!(verdict(few.llr(&sprt), &sprt), Verdict::Inconclusive);
+    }
+
+    #[test]
+    fn test_run() {
+        let config: TournamentConfig = serde_json::from_str(
+            r#"{
+                "entrants": [
+                    {"name": "greedy", "strategy": "greedy"},
+                    {"name": "also-greedy", "strategy": "greedy", "timeout": 5},
+                    {"name": "basic", "strategy": "basic", "timeout": 5}
+                ],
+                "games": 2,
+                "board_size": 7,
+                "max_turns": 50
+            }"#,
+        )
+        .unwrap();
+        let mut games = 0;
+        let report = run(&config, |_| games += 1);
+        assert_eq!(games, 6);
+        assert_eq!(report.pairings.len(), 3);
+        assert_eq!(report.standings.len(), 3);
+        for standing in &report.standings {
+            assert_eq!(standing.score.games(), 4);
+        }
+        assert!(report.markdown().contains("| greedy vs also-greedy | 2 |"));
+    }
+}
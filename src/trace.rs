@@ -0,0 +1,160 @@
+use rocket::fairing::AdHoc;
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::{self, Responder};
+use rocket::Request;
+use rocket_okapi::gen::OpenApiGenerator;
+use rocket_okapi::okapi::openapi3::Responses;
+use rocket_okapi::request::{OpenApiFromRequest, RequestHeaderInput};
+use rocket_okapi::response::OpenApiResponderInner;
+use std::sync::{Mutex, PoisonError};
+use tracing::Span;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Installs the tracing subscriber, filtered by `RUST_LOG` or `default_filter` when it is unset.
+///
+/// Records from the `log` macros are forwarded to it. Built with the `otlp` feature, spans are
+/// also exported when `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+pub fn init(default_filter: &str) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+    #[cfg(feature = "otlp")]
+    let registry = registry.with(otlp::layer());
+    registry.init();
+}
+
+/// Flushes spans that haven't been exported yet.
+pub fn shutdown() {
+    #[cfg(feature = "otlp")]
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::sdk::trace::{self, Tracer};
+    use opentelemetry::sdk::Resource;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use tracing::Subscriber;
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    pub fn layer<S>() -> Option<OpenTelemetryLayer<S, Tracer>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+        // Spans are exported in batches from the Tokio runtime, which datagen doesn't start
+        rocket::tokio::runtime::Handle::try_current().ok()?;
+        let resource = Resource::new(vec![KeyValue::new("service.name", env!("CARGO_PKG_NAME"))]);
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(trace::config().with_resource(resource))
+            .install_batch(opentelemetry::runtime::Tokio);
+        match tracer {
+            Ok(tracer) => Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+            Err(e) => {
+                eprintln!("unable to export traces over OTLP: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Spans opened as soon as a request's headers are read.
+struct RequestTrace {
+    span: Span,
+    parse: Mutex<Option<Span>>,
+}
+
+impl RequestTrace {
+    fn new(request: &Request<'_>) -> Self {
+        let span = tracing::info_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri()
+        );
+        let parse = tracing::info_span!(parent: &span, "parse");
+        RequestTrace {
+            span,
+            parse: Mutex::new(Some(parse)),
+        }
+    }
+}
+
+/// The span covering a whole request, and the span covering the time until its body is parsed.
+///
+/// Rocket runs the data guard after every request guard, so handlers close the parse span with
+/// [`RequestSpan::parsed`] once they start.
+pub struct RequestSpan {
+    span: Span,
+    parse: Option<Span>,
+}
+
+impl RequestSpan {
+    /// Opens the request spans of every request as soon as its headers are read.
+    pub fn fairing() -> AdHoc {
+        AdHoc::on_request("Request Span", |request, _| {
+            Box::pin(async move {
+                request.local_cache(|| RequestTrace::new(request));
+            })
+        })
+    }
+    pub fn parsed(self) -> Span {
+        let RequestSpan { span, parse } = self;
+        drop(parse);
+        span
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestSpan {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let trace = request.local_cache(|| RequestTrace::new(request));
+        Outcome::Success(RequestSpan {
+            span: trace.span.clone(),
+            parse: trace
+                .parse
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take(),
+        })
+    }
+}
+
+impl<'a> OpenApiFromRequest<'a> for RequestSpan {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
+
+/// A response serialized inside a `serialize` span under the given span.
+pub struct Traced<R>(pub R, pub Span);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Traced<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let _serialize = tracing::info_span!(parent: &self.1, "serialize").entered();
+        self.0.respond_to(request)
+    }
+}
+
+impl<R: OpenApiResponderInner> OpenApiResponderInner for Traced<R> {
+    fn responses(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        R::responses(gen)
+    }
+}
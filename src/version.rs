@@ -0,0 +1,46 @@
+use rocket_okapi::okapi::schemars;
+use rocket_okapi::okapi::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Identifies the build serving requests, so a game can be traced back to the deployment that played it.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BuildInfo {
+    /// Crate version. Example: "1.13.0"
+    version: String,
+    /// Git commit the binary was built from, when GIT_SHA was set at build time.
+    git_sha: Option<String>,
+    /// When the binary was built, in seconds since the Unix epoch.
+    built_at: u64,
+    /// Cargo features compiled in. Example: ["persistence"]
+    features: Vec<String>,
+    /// Name of the experiment new ladder games are assigned to, if any.
+    experiment: Option<String>,
+}
+
+impl BuildInfo {
+    pub fn new(experiment: Option<&str>) -> Self {
+        let features = [
+            ("datagen", cfg!(feature = "datagen")),
+            ("grpc", cfg!(feature = "grpc")),
+            ("mcts", cfg!(feature = "mcts")),
+            ("otlp", cfg!(feature = "otlp")),
+            ("persistence", cfg!(feature = "persistence")),
+            ("profiling", cfg!(feature = "profiling")),
+            ("render", cfg!(feature = "render")),
+            ("tui", cfg!(feature = "tui")),
+        ];
+        BuildInfo {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_sha: option_env!("GIT_SHA")
+                .filter(|sha| !sha.is_empty())
+                .map(str::to_owned),
+            built_at: env!("BUILD_TIMESTAMP").parse().unwrap_or(0),
+            features: features
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            experiment: experiment.map(str::to_owned),
+        }
+    }
+}
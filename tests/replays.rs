@@ -0,0 +1,147 @@
+//! Replays every recorded game in `tests/replays/` through `make_move`, and checks that the engine
+//! never picks the move our snake died making, when it had one that survived the moves the
+//! other snakes actually made.
+//!
+//! Recordings are the `<game id>.jsonl` files written by the game recorder, so games can be
+//! added by copying them from the recordings directory.
+
+use ndsquared_rustapi::battlesnake::{
+    make_move, simulate, GameState, Personalities, Profile, SimulateRequest,
+};
+use ndsquared_rustapi::recorder::GameRecord;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Nodes each search may evaluate, so the replays play the same way on any machine.
+const NODE_BUDGET: u32 = 2_000;
+
+const MOVES: [&str; 4] = ["up", "down", "left", "right"];
+
+fn read_game(path: &Path) -> Vec<GameRecord> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn head(snake: &Value) -> (i64, i64) {
+    let head = &snake["head"];
+    (head["x"].as_i64().unwrap(), head["y"].as_i64().unwrap())
+}
+
+/// The move that took a head from one square to the other, wrapping around the board edges.
+fn direction(from: (i64, i64), to: (i64, i64), width: i64, height: i64) -> Option<&'static str> {
+    let dx = (to.0 - from.0 + width) % width;
+    let dy = (to.1 - from.1 + height) % height;
+    match (dx, dy) {
+        (1, 0) => Some("right"),
+        (0, 1) => Some("up"),
+        (0, _) if dy == height - 1 => Some("down"),
+        (_, 0) if dx == width - 1 => Some("left"),
+        _ => None,
+    }
+}
+
+/// Moves every other snake made this turn, as far as the next state shows them.
+fn opponent_moves(state: &Value, next: &Value) -> serde_json::Map<String, Value> {
+    let width = state["board"]["width"].as_i64().unwrap();
+    let height = state["board"]["height"].as_i64().unwrap();
+    let mut moves = serde_json::Map::new();
+    for snake in state["board"]["snakes"].as_array().unwrap() {
+        if snake["id"] == state["you"]["id"] {
+            continue;
+        }
+        let after = next["board"]["snakes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["id"] == snake["id"]);
+        if let Some(m) = after.and_then(|s| direction(head(snake), head(s), width, height)) {
+            moves.insert(snake["id"].as_str().unwrap().to_owned(), json!([m]));
+        }
+    }
+    moves
+}
+
+/// Whether our snake survives the turn making the given move against the recorded ones.
+fn survives(state: &Value, opponents: &serde_json::Map<String, Value>, ours: &str) -> bool {
+    let mut moves = opponents.clone();
+    let id = state["you"]["id"].as_str().unwrap().to_owned();
+    moves.insert(id.clone(), json!([ours]));
+    let request: SimulateRequest =
+        serde_json::from_value(json!({"state": state, "moves": moves})).unwrap();
+    let after = serde_json::to_value(&simulate(request).unwrap()[0]).unwrap();
+    let alive = after["board"]["snakes"].as_array().unwrap();
+    alive.iter().any(|snake| snake["id"] == id.as_str())
+}
+
+fn alive(state: &Value) -> bool {
+    let snakes = state["board"]["snakes"].as_array().unwrap();
+    snakes.iter().any(|snake| snake["id"] == state["you"]["id"])
+}
+
+#[test]
+fn test_replays() {
+    let personality = Personalities::default()
+        .default_personality()
+        .clone()
+        .with_node_budget(NODE_BUDGET);
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/replays");
+    let mut paths: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("jsonl".as_ref()))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let records = read_game(&path);
+        let states: Vec<&GameState> = records.iter().map(GameRecord::state).collect();
+        for (i, record) in records.iter().enumerate() {
+            let (gs, response) = match record {
+                GameRecord::Move {
+                    state, response, ..
+                } => (state, response),
+                _ => continue,
+            };
+            let deadline = Instant::now() + Duration::from_secs(60);
+            let (chosen, _) = make_move(
+                gs.clone(),
+                &personality,
+                deadline,
+                Profile::Tournament,
+                &HashMap::new(),
+            )
+            .unwrap();
+            let chosen = serde_json::to_value(&chosen).unwrap();
+            let chosen = chosen["move"].as_str().unwrap();
+            let next = match states.get(i + 1) {
+                Some(next) => serde_json::to_value(next).unwrap(),
+                None => continue,
+            };
+            let state = serde_json::to_value(gs).unwrap();
+            if alive(&next) {
+                continue;
+            }
+            // Our snake died this turn, so its move must be avoided if any move survived
+            let original = serde_json::to_value(response).unwrap();
+            let original = original["move"].as_str().unwrap();
+            let opponents = opponent_moves(&state, &next);
+            if !MOVES.iter().any(|m| survives(&state, &opponents, m)) {
+                continue;
+            }
+            assert_ne!(
+                chosen,
+                original,
+                "{:?} turn {}: the engine repeated the move that died",
+                path.file_name().unwrap(),
+                state["turn"]
+            );
+        }
+    }
+}